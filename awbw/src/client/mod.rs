@@ -0,0 +1,287 @@
+use std::fmt;
+use std::io::{Cursor, Read};
+
+use common::map::{from_awbw_text, AwbwMapError, Map};
+use common::GameState;
+use zip::ZipArchive;
+
+use crate::replay::{read_map_id, read_replay, ReplayError};
+
+/** The AWBW site every endpoint is relative to. */
+pub const AWBW_URL: &str = "https://awbw.amarriner.com";
+
+/**
+ * Why a game or map could not be fetched from AWBW.
+ */
+#[derive(Debug)]
+pub enum ClientError {
+    /** The request failed or AWBW answered with an error status. */
+    Http(ureq::Error),
+    Io(std::io::Error),
+    /** The replay download was not a zip archive holding the turn file. */
+    Archive(String),
+    Replay(ReplayError),
+    /** The map download did not hold a valid map. */
+    Map(AwbwMapError),
+    /** The game has not finished a turn yet. */
+    NoTurns,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Http(error) => write!(f, "{}", error),
+            ClientError::Io(error) => write!(f, "{}", error),
+            ClientError::Archive(reason) => write!(f, "Bad replay archive: {}", reason),
+            ClientError::Replay(error) => write!(f, "{}", error),
+            ClientError::Map(error) => write!(f, "Bad map: {}", error),
+            ClientError::NoTurns => write!(f, "Game has no turns yet"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<ureq::Error> for ClientError {
+    fn from(error: ureq::Error) -> Self {
+        ClientError::Http(error)
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(error: std::io::Error) -> Self {
+        ClientError::Io(error)
+    }
+}
+
+impl From<ReplayError> for ClientError {
+    fn from(error: ReplayError) -> Self {
+        ClientError::Replay(error)
+    }
+}
+
+/**
+ * Downloads games and maps from AWBW and turns them into `GameState`s.
+ */
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new()
+    }
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Client::with_base_url(AWBW_URL)
+    }
+
+    /**
+     * A client for a mirror of AWBW at `base_url`, e.g. a local server in
+     * tests.
+     */
+    pub fn with_base_url(base_url: &str) -> Self {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /**
+     * Downloads the terrain of map `map_id` from its text export.
+     */
+    pub fn map(&self, map_id: u64) -> Result<Map, ClientError> {
+        let page = self.download(&format!("text_map.php?maps_id={}", map_id))?;
+        map_from_page(&String::from_utf8_lossy(&page))
+    }
+
+    /**
+     * Downloads the terrain of the map game `game_id` is played on, finding
+     * the map from the game's replay.
+     */
+    pub fn game_map(&self, game_id: u64) -> Result<Map, ClientError> {
+        let archive = self.download(&format!("replay_download.php?games_id={}", game_id))?;
+        self.map(map_id_from_archive(&archive, game_id)?)
+    }
+
+    /**
+     * Downloads the replay of game `game_id` and returns the state at the
+     * start of every turn so far on `map`.
+     */
+    pub fn turns(&self, game_id: u64, map: &Map) -> Result<Vec<GameState>, ClientError> {
+        let archive = self.download(&format!("replay_download.php?games_id={}", game_id))?;
+        turns_from_archive(&archive, game_id, map)
+    }
+
+    /**
+     * Same as `turns` but only returns the state at the start of the latest
+     * turn.
+     */
+    pub fn latest_state(&self, game_id: u64, map: &Map) -> Result<GameState, ClientError> {
+        self.turns(game_id, map)?.pop().ok_or(ClientError::NoTurns)
+    }
+
+    fn download(&self, path: &str) -> Result<Vec<u8>, ClientError> {
+        let mut response = ureq::get(&format!("{}/{}", self.base_url, path)).call()?;
+
+        let mut body = Vec::new();
+        response.body_mut().as_reader().read_to_end(&mut body)?;
+        Ok(body)
+    }
+}
+
+/**
+ * Reads every turn out of a downloaded replay archive. The archive holds
+ * the turn file, named after the game, next to an action file which is not
+ * needed.
+ */
+fn turns_from_archive(
+    archive: &[u8],
+    game_id: u64,
+    map: &Map,
+) -> Result<Vec<GameState>, ClientError> {
+    read_turn_file(archive, game_id, |turn_file| {
+        Ok(read_replay(turn_file, map)?.turns)
+    })
+}
+
+/**
+ * Reads the ID of the game's map out of a downloaded replay archive.
+ */
+fn map_id_from_archive(archive: &[u8], game_id: u64) -> Result<u64, ClientError> {
+    read_turn_file(archive, game_id, |turn_file| read_map_id(turn_file))
+}
+
+/**
+ * Opens the turn file of a downloaded replay archive and hands it to
+ * `read`.
+ */
+fn read_turn_file<T>(
+    archive: &[u8],
+    game_id: u64,
+    read: impl FnOnce(&mut dyn Read) -> Result<T, ReplayError>,
+) -> Result<T, ClientError> {
+    let mut archive = ZipArchive::new(Cursor::new(archive))
+        .map_err(|error| ClientError::Archive(error.to_string()))?;
+    let mut turn_file = archive
+        .by_name(&game_id.to_string())
+        .map_err(|error| ClientError::Archive(format!("No turn file: {}", error)))?;
+
+    Ok(read(&mut turn_file)?)
+}
+
+/**
+ * Pulls the terrain IDs out of a text export page. The page wraps them in
+ * HTML so only lines made of IDs and commas are kept.
+ */
+fn map_from_page(page: &str) -> Result<Map, ClientError> {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for character in page.chars() {
+        match character {
+            '<' => {
+                in_tag = true;
+                text.push('\n');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(character),
+            _ => {}
+        }
+    }
+
+    let rows = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.contains(','))
+        .filter(|line| {
+            line.chars()
+                .all(|c| c.is_ascii_digit() || c == ',' || c.is_ascii_whitespace())
+        })
+        .collect::<Vec<&str>>()
+        .join("\n");
+
+    let (tiles, dimensions) = from_awbw_text(&rows).map_err(ClientError::Map)?;
+    Ok(Map::new(tiles, dimensions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    use common::map::TileKind;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    #[test]
+    fn map_from_page_strips_html() {
+        let page = "<html><body><table><tr><td>1,3,\n28,28</td></tr></table>\
+            <p>Size: 2, 2</p></body></html>";
+
+        assert_eq!(
+            Map::new(
+                vec![
+                    TileKind::Plain,
+                    TileKind::Forest,
+                    TileKind::Sea,
+                    TileKind::Sea
+                ],
+                (2, 2)
+            ),
+            map_from_page(page).unwrap()
+        );
+        assert!(matches!(
+            map_from_page("<html>Map not found</html>"),
+            Err(ClientError::Map(AwbwMapError::Empty))
+        ));
+    }
+
+    #[test]
+    fn turns_from_archive_errors() {
+        let map = Map::new(vec![TileKind::Plain], (1, 1));
+        assert!(matches!(
+            turns_from_archive(b"not a zip", 1, &map),
+            Err(ClientError::Archive(_))
+        ));
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("a1", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"actions").unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        assert!(matches!(
+            turns_from_archive(&archive, 1, &map),
+            Err(ClientError::Archive(_))
+        ));
+        assert!(matches!(
+            turns_from_archive(&archive, 2, &map),
+            Err(ClientError::Archive(_))
+        ));
+    }
+
+    #[test]
+    fn map_id_from_turn_file() {
+        let mut turn_file = GzEncoder::new(Vec::new(), Compression::default());
+        writeln!(turn_file, r#"O:8:"awbwGame":1:{{s:7:"maps_id";i:12;}}"#).unwrap();
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("7", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&turn_file.finish().unwrap()).unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        assert_eq!(12, map_id_from_archive(&archive, 7).unwrap());
+        assert!(matches!(
+            map_id_from_archive(&archive, 8),
+            Err(ClientError::Archive(_))
+        ));
+    }
+}
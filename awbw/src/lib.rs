@@ -1,14 +1,8 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+#[cfg(feature = "http")]
+pub mod client;
+#[cfg(feature = "live")]
+pub mod live;
+#[cfg(feature = "json")]
+pub mod message;
+pub mod php;
+pub mod replay;
@@ -0,0 +1,243 @@
+use std::fmt;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use common::action::{Action, ActionError};
+use common::cancel::CancelToken;
+use common::tracker::VisionDelta;
+use common::GameState;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::message::{actions_from_message, MessageError};
+
+#[cfg(feature = "live-async")]
+pub mod stream;
+
+/** How long a read may block before the subscriber checks for cancel. */
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/**
+ * Why a live message could not be followed.
+ */
+#[derive(Debug)]
+pub enum LiveError {
+    Socket(tungstenite::Error),
+    /** A message was not JSON or was missing fields its action needs. */
+    Format(String),
+    /** A decoded action could not be applied to the followed state, which
+     * has likely drifted from the game. */
+    Action(ActionError),
+}
+
+impl fmt::Display for LiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiveError::Socket(error) => write!(f, "{}", error),
+            LiveError::Format(reason) => write!(f, "Bad message: {}", reason),
+            LiveError::Action(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for LiveError {}
+
+impl From<tungstenite::Error> for LiveError {
+    fn from(error: tungstenite::Error) -> Self {
+        LiveError::Socket(error)
+    }
+}
+
+impl From<MessageError> for LiveError {
+    fn from(error: MessageError) -> Self {
+        LiveError::Format(error.0)
+    }
+}
+
+impl From<ActionError> for LiveError {
+    fn from(error: ActionError) -> Self {
+        LiveError::Action(error)
+    }
+}
+
+/**
+ * What the subscriber sends after each message of the game.
+ */
+#[derive(Debug)]
+pub enum LiveEvent {
+    /** Actions were applied and common vision changed by `delta`. */
+    Update {
+        actions: Vec<Action>,
+        game_state: Box<GameState>,
+        delta: VisionDelta,
+    },
+    /** The message was not an action this module understands, e.g. chat or
+     * an action that does not change units, and was skipped. */
+    Skipped(String),
+    /** The message could not be followed. The state is left as it was so
+     * the caller may resync, e.g. by downloading the game again. */
+    Error(LiveError),
+}
+
+/**
+ * Follows a game over AWBW's websocket on a background thread, keeping a
+ * `GameState` up to date and sending a `LiveEvent` per message over a
+ * blocking `std::sync::mpsc` channel.
+ *
+ * The thread reads with a timeout of `POLL_INTERVAL` so it notices a
+ * cancel, and stops when the socket closes, the receiver is dropped or
+ * `cancel` is called. Async callers should use `stream::LiveStream`
+ * instead, behind the `live-async` feature.
+ */
+pub struct Subscriber {
+    events: Receiver<LiveEvent>,
+    cancel: CancelToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Subscriber {
+    /**
+     * Connects to the websocket at `url` and follows it from `game_state`,
+     * which should be the state of the game when connecting.
+     */
+    pub fn connect(url: &str, game_state: GameState) -> Result<Subscriber, LiveError> {
+        let (socket, _) = tungstenite::connect(url)?;
+        match socket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(POLL_INTERVAL)),
+            MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(Some(POLL_INTERVAL)),
+            _ => Ok(()),
+        }
+        .map_err(tungstenite::Error::Io)?;
+
+        let (sender, events) = mpsc::channel();
+        let cancel = CancelToken::new();
+        let thread_cancel = cancel.clone();
+        let thread = thread::spawn(move || {
+            follow(socket, game_state, &thread_cancel, |event| {
+                sender.send(event).is_ok()
+            })
+        });
+
+        Ok(Subscriber {
+            events,
+            cancel,
+            thread: Some(thread),
+        })
+    }
+
+    /**
+     * The events of the game in the order their messages arrived.
+     */
+    pub fn events(&self) -> &Receiver<LiveEvent> {
+        &self.events
+    }
+
+    /**
+     * Stops following the game, events already sent can still be received.
+     */
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn follow(
+    mut socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    mut game_state: GameState,
+    cancel: &CancelToken,
+    mut send: impl FnMut(LiveEvent) -> bool,
+) {
+    while !cancel.is_cancelled() {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error))
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(tungstenite::Error::ConnectionClosed) => return,
+            Err(error) => {
+                send(LiveEvent::Error(error.into()));
+                return;
+            }
+        };
+
+        if !send(handle_message(&mut game_state, text.as_str())) {
+            return;
+        }
+    }
+}
+
+/**
+ * Applies one message to `game_state`, leaving it unchanged unless the
+ * whole message applied.
+ */
+fn handle_message(game_state: &mut GameState, text: &str) -> LiveEvent {
+    let actions = match actions_from_message(game_state, text) {
+        Ok(Some(actions)) => actions,
+        Ok(None) => return LiveEvent::Skipped(text.to_string()),
+        Err(error) => return LiveEvent::Error(error.into()),
+    };
+
+    let mut next = game_state.clone();
+    for action in actions.iter() {
+        if let Err(error) = next.apply_in_place(action) {
+            return LiveEvent::Error(error.into());
+        }
+    }
+
+    let delta = VisionDelta::diff(game_state, &next);
+    *game_state = next;
+    LiveEvent::Update {
+        actions,
+        game_state: Box::new(game_state.clone()),
+        delta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use common::ascii::parse_game_state;
+
+    #[test]
+    fn handle_message_keeps_state_on_error() {
+        let mut game_state = parse_game_state(".0inf . .1inf\n. . .").unwrap();
+        let before = game_state.clone();
+
+        assert!(matches!(
+            handle_message(
+                &mut game_state,
+                r#"{"action":"Move","paths":{"global":[{"x":1,"y":0},{"x":1,"y":1}]}}"#
+            ),
+            LiveEvent::Error(LiveError::Action(_))
+        ));
+        assert_eq!(before, game_state);
+
+        let LiveEvent::Update { actions, delta, .. } = handle_message(
+            &mut game_state,
+            r#"{"action":"Move","paths":{"global":[{"x":0,"y":0},{"x":0,"y":1}]}}"#,
+        ) else {
+            panic!("Move was not applied");
+        };
+        assert_eq!(vec![Action::Move { from: 0, to: 3 }], actions);
+        assert_eq!(VisionDelta::diff(&before, &game_state), delta);
+        assert!(game_state.unit(3).is_some());
+    }
+}
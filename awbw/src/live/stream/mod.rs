@@ -0,0 +1,120 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use common::GameState;
+use futures_util::{Stream, StreamExt};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tungstenite::Message;
+
+use super::{handle_message, LiveError, LiveEvent};
+
+/**
+ * Follows a game over AWBW's websocket from async code, keeping a
+ * `GameState` up to date and yielding a `LiveEvent` per message.
+ *
+ * Unlike `Subscriber` no thread is started and nothing polls: the socket is
+ * read by a task on the caller's tokio runtime which wakes only when a
+ * message arrives. The task stops when the socket closes or the stream is
+ * dropped.
+ */
+pub struct LiveStream {
+    events: UnboundedReceiver<LiveEvent>,
+}
+
+impl LiveStream {
+    /**
+     * Connects to the websocket at `url` and follows it from `game_state`,
+     * which should be the state of the game when connecting.
+     *
+     * Must be called from within a tokio runtime.
+     */
+    pub async fn connect(url: &str, game_state: GameState) -> Result<LiveStream, LiveError> {
+        let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+
+        let (sender, events) = mpsc::unbounded_channel();
+        tokio::spawn(follow(socket, game_state, sender));
+
+        Ok(LiveStream { events })
+    }
+}
+
+impl Stream for LiveStream {
+    type Item = LiveEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<LiveEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+async fn follow(
+    mut socket: impl Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+    mut game_state: GameState,
+    sender: UnboundedSender<LiveEvent>,
+) {
+    loop {
+        let message = tokio::select! {
+            message = socket.next() => message,
+            _ = sender.closed() => return,
+        };
+
+        let text = match message {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => return,
+            Some(Ok(_)) => continue,
+            Some(Err(tungstenite::Error::ConnectionClosed)) => return,
+            Some(Err(error)) => {
+                let _ = sender.send(LiveEvent::Error(error.into()));
+                return;
+            }
+        };
+
+        if sender
+            .send(handle_message(&mut game_state, text.as_str()))
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use common::action::Action;
+    use common::ascii::parse_game_state;
+    use futures_util::SinkExt;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn stream_follows_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            for text in [
+                r#"{"action":"Move","paths":{"global":[{"x":0,"y":0},{"x":0,"y":1}]}}"#,
+                r#"{"action":"Chat"}"#,
+            ] {
+                socket.send(Message::text(text)).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+        });
+
+        let game_state = parse_game_state(".0inf . .1inf\n. . .").unwrap();
+        let events = LiveStream::connect(&url, game_state)
+            .await
+            .unwrap()
+            .collect::<Vec<LiveEvent>>()
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(2, events.len());
+        let LiveEvent::Update { actions, .. } = &events[0] else {
+            panic!("Move was not applied");
+        };
+        assert_eq!(&vec![Action::Move { from: 0, to: 3 }], actions);
+        assert!(matches!(events[1], LiveEvent::Skipped(_)));
+    }
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+
+use common::action::Action;
+use common::coordinate::Position;
+use common::unit::UnitKind;
+use common::GameState;
+use serde_json::Value;
+
+use crate::replay::unit_from_name;
+
+/** What `buildings_capture` is reset to once a property is captured. */
+const CAPTURE_POINTS: i64 = 20;
+
+/**
+ * Why an AWBW action message could not be decoded: it was not JSON or was
+ * missing fields its action needs.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MessageError(pub String);
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bad message: {}", self.0)
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+/**
+ * Decodes the JSON AWBW records for an action, as sent over its websocket
+ * and stored in replays, into the actions it is made of, or None if it is
+ * not a unit action.
+ *
+ * AWBW sends the move a unit made before acting under `Move` alongside the
+ * action, so a single message can decode to two actions. A move onto a
+ * transport decodes to `Action::Load` instead.
+ */
+pub fn actions_from_message(
+    game_state: &GameState,
+    text: &str,
+) -> Result<Option<Vec<Action>>, MessageError> {
+    let message =
+        serde_json::from_str::<Value>(text).map_err(|error| MessageError(error.to_string()))?;
+    let Some(kind) = message.get("action").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+
+    let mut moved = if kind == "Move" {
+        move_ends(game_state, &message)?
+    } else {
+        match message.get("Move").filter(|value| value.is_object()) {
+            Some(value) => move_ends(game_state, value)?,
+            None => None,
+        }
+    };
+
+    let mut actions = Vec::new();
+    match kind {
+        "Load" => actions.extend(moved.take().map(|(from, to)| Action::Load {
+            location: from,
+            transport: to,
+        })),
+        "Build" => {
+            let unit = field(&message, &["newUnit", "global"])?;
+            actions.push(Action::Build {
+                location: unit_location(game_state, unit)?,
+                kind: unit_kind(unit)?,
+            });
+        }
+        "Capt" => {
+            let building = field(&message, &["Capt", "buildingInfo"])?;
+            let points = building
+                .get("buildings_capture")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| format_error("Missing `buildings_capture`"))?;
+            // Only a finished capture changes the owner.
+            if points >= CAPTURE_POINTS {
+                actions.push(Action::Capture {
+                    location: location(game_state, building, "buildings_x", "buildings_y")?,
+                });
+            }
+        }
+        "Unload" => {
+            let unit = field(&message, &["Unload", "unit", "global"])?;
+            let kind = unit_kind(unit)?;
+            let destination = unit_location(game_state, unit)?;
+
+            // The cargo is looked up where the transport was before moving,
+            // as the move is not applied yet.
+            let (before, transport) = match moved {
+                Some(moved) => moved,
+                None => {
+                    let transport = carrier_next_to(game_state, destination, &kind)?;
+                    (transport, transport)
+                }
+            };
+            let index = game_state
+                .unit(before)
+                .and_then(|unit| unit.cargo().iter().position(|cargo| *cargo.kind() == kind))
+                .ok_or_else(|| format_error(format!("No transport carrying {:?}", kind)))?;
+            actions.push(Action::Unload {
+                transport,
+                index,
+                destination,
+            });
+        }
+        "Fire" => {
+            let combat = field(
+                &message,
+                &["Fire", "combatInfoVision", "global", "combatInfo"],
+            )?;
+            let attacker = unit_location(game_state, field(combat, &["attacker"])?)?;
+            let defender = field(combat, &["defender"])?;
+            let target = unit_location(game_state, defender)?;
+            let hp = defender
+                .get("units_hit_points")
+                .and_then(Value::as_f64)
+                .ok_or_else(|| format_error("Missing `units_hit_points`"))?;
+
+            // The defender has not moved so its current HP is known even if
+            // the move above is not applied yet.
+            let before = game_state.unit(target).map(|unit| unit.hp()).unwrap_or(0);
+            actions.push(Action::Attack {
+                attacker,
+                target,
+                damage: before.saturating_sub(hp.max(0.0).ceil() as u8),
+            });
+        }
+        "Hide" => actions.push(Action::Dive {
+            location: unit_location(game_state, field(&message, &["Hide", "unit", "global"])?)?,
+        }),
+        "Unhide" => actions.push(Action::Surface {
+            location: unit_location(game_state, field(&message, &["Unhide", "unit", "global"])?)?,
+        }),
+        "End" => actions.push(Action::EndTurn),
+        _ => {}
+    }
+
+    if let Some((from, to)) = moved {
+        actions.insert(0, Action::Move { from, to });
+    }
+
+    if actions.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(actions))
+    }
+}
+
+/**
+ * The move under `paths.global` of `value` as the first and last tile of
+ * its path, or None if the unit did not move.
+ */
+fn move_ends(
+    game_state: &GameState,
+    value: &Value,
+) -> Result<Option<(usize, usize)>, MessageError> {
+    let path = field(value, &["paths", "global"])?
+        .as_array()
+        .ok_or_else(|| format_error("Path is not a list"))?;
+    let (Some(first), Some(last)) = (path.first(), path.last()) else {
+        return Ok(None);
+    };
+
+    let from = location(game_state, first, "x", "y")?;
+    let to = location(game_state, last, "x", "y")?;
+    if from == to {
+        return Ok(None);
+    }
+    Ok(Some((from, to)))
+}
+
+/**
+ * The tile next to `destination` holding a unit that carries a `kind`.
+ */
+fn carrier_next_to(
+    game_state: &GameState,
+    destination: usize,
+    kind: &UnitKind,
+) -> Result<usize, MessageError> {
+    let mut neighbors = game_state
+        .checked_neighbors(destination, 1)
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    neighbors.sort_unstable();
+    neighbors
+        .into_iter()
+        .find(|location| {
+            game_state
+                .unit(*location)
+                .is_some_and(|unit| unit.cargo().iter().any(|cargo| cargo.kind() == kind))
+        })
+        .ok_or_else(|| format_error(format!("No transport carrying {:?}", kind)))
+}
+
+fn field<'a>(value: &'a Value, path: &[&str]) -> Result<&'a Value, MessageError> {
+    path.iter().try_fold(value, |value, name| {
+        value
+            .get(name)
+            .ok_or_else(|| format_error(format!("Missing `{}`", path.join("."))))
+    })
+}
+
+fn unit_kind(unit: &Value) -> Result<UnitKind, MessageError> {
+    let name = unit
+        .get("units_name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| format_error("Missing `units_name`"))?;
+    unit_from_name(name).ok_or_else(|| format_error(format!("Unknown unit `{}`", name)))
+}
+
+fn unit_location(game_state: &GameState, unit: &Value) -> Result<usize, MessageError> {
+    location(game_state, unit, "units_x", "units_y")
+}
+
+fn location(
+    game_state: &GameState,
+    value: &Value,
+    x: &str,
+    y: &str,
+) -> Result<usize, MessageError> {
+    let coordinate = |name: &str| {
+        value
+            .get(name)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format_error(format!("Missing `{}`", name)))
+    };
+    let (x, y) = (coordinate(x)?, coordinate(y)?);
+
+    game_state
+        .index_of(Position::new(x as usize, y as usize))
+        .ok_or_else(|| format_error(format!("({}, {}) is off the map", x, y)))
+}
+
+fn format_error(reason: impl Into<String>) -> MessageError {
+    MessageError(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use common::ascii::parse_game_state;
+    use common::UnitState;
+
+    #[test]
+    fn actions_from_message_decodes() {
+        let game_state = parse_game_state(".0inf . .1inf\n. . .").unwrap();
+
+        assert_eq!(
+            Some(vec![Action::Move { from: 0, to: 4 }]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Move","paths":{"global":[{"x":0,"y":0},{"x":1,"y":0},{"x":1,"y":1}]}}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Some(vec![
+                Action::Move { from: 0, to: 1 },
+                Action::Attack {
+                    attacker: 1,
+                    target: 2,
+                    damage: 4
+                }
+            ]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Fire","Move":{"paths":{"global":[{"x":0,"y":0},{"x":1,"y":0}]}},
+                    "Fire":{"combatInfoVision":{"global":{"combatInfo":{
+                        "attacker":{"units_x":1,"units_y":0},
+                        "defender":{"units_x":2,"units_y":0,"units_hit_points":5.5}}}}}}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Some(vec![Action::Build {
+                location: 3,
+                kind: UnitKind::Infantry
+            }]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Build","newUnit":{"global":{"units_name":"Infantry","units_x":0,"units_y":1}}}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Some(vec![Action::EndTurn]),
+            actions_from_message(&game_state, r#"{"action":"End"}"#).unwrap()
+        );
+        assert_eq!(
+            None,
+            actions_from_message(&game_state, r#"{"action":"Chat","message":"gg"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn actions_from_message_decodes_captures() {
+        let game_state = parse_game_state(".0inf c .").unwrap();
+        let capture = |points: i64| {
+            format!(
+                r#"{{"action":"Capt","Move":{{"paths":{{"global":[{{"x":0,"y":0}},{{"x":1,"y":0}}]}}}},
+                    "Capt":{{"buildingInfo":{{"buildings_capture":{},"buildings_x":1,"buildings_y":0}}}}}}"#,
+                points
+            )
+        };
+
+        assert_eq!(
+            Some(vec![Action::Move { from: 0, to: 1 }]),
+            actions_from_message(&game_state, &capture(11)).unwrap()
+        );
+        assert_eq!(
+            Some(vec![
+                Action::Move { from: 0, to: 1 },
+                Action::Capture { location: 1 }
+            ]),
+            actions_from_message(&game_state, &capture(20)).unwrap()
+        );
+    }
+
+    #[test]
+    fn actions_from_message_decodes_transports() {
+        let mut game_state = parse_game_state(".0inf .0apc . .").unwrap();
+        assert_eq!(
+            Some(vec![Action::Load {
+                location: 0,
+                transport: 1
+            }]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Load","Move":{"paths":{"global":[{"x":0,"y":0},{"x":1,"y":0}]}},"Load":{}}"#
+            )
+            .unwrap()
+        );
+
+        game_state.remove_unit(1).unwrap();
+        game_state
+            .place_unit(
+                1,
+                UnitState::new(0, false, UnitKind::Apc).with_cargo(vec![
+                    UnitState::new(0, false, UnitKind::Mech),
+                    UnitState::new(0, false, UnitKind::Infantry),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            Some(vec![Action::Unload {
+                transport: 1,
+                index: 1,
+                destination: 2
+            }]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Unload","Unload":{"unit":{"global":{"units_name":"Infantry","units_x":2,"units_y":0}}}}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Some(vec![
+                Action::Move { from: 1, to: 2 },
+                Action::Unload {
+                    transport: 2,
+                    index: 0,
+                    destination: 3
+                }
+            ]),
+            actions_from_message(
+                &game_state,
+                r#"{"action":"Unload","Move":{"paths":{"global":[{"x":1,"y":0},{"x":2,"y":0}]}},
+                    "Unload":{"unit":{"global":{"units_name":"Mech","units_x":3,"units_y":0}}}}"#
+            )
+            .unwrap()
+        );
+        assert!(actions_from_message(
+            &game_state,
+            r#"{"action":"Unload","Unload":{"unit":{"global":{"units_name":"Tank","units_x":2,"units_y":0}}}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn actions_from_message_errors() {
+        let game_state = parse_game_state(".0inf . .1inf").unwrap();
+
+        assert!(actions_from_message(&game_state, "not json").is_err());
+        assert!(actions_from_message(&game_state, r#"{"action":"Move"}"#).is_err());
+        assert!(actions_from_message(
+            &game_state,
+            r#"{"action":"Move","paths":{"global":[{"x":0,"y":0},{"x":5,"y":0}]}}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+
+/**
+ * A value decoded from PHP's `serialize` format, which AWBW uses for its
+ * replay files.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhpValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /** PHP strings are bytes, these are decoded lossily as UTF-8. */
+    String(String),
+    /** An array's (key, value) pairs in order. */
+    Array(Vec<(PhpValue, PhpValue)>),
+    Object {
+        class: String,
+        /** The object's (property, value) pairs in order. */
+        properties: Vec<(String, PhpValue)>,
+    },
+}
+
+impl PhpValue {
+    /**
+     * Returns the value of `property` if this is an object or the value
+     * stored under the string key `property` if this is an array.
+     */
+    pub fn get(&self, property: &str) -> Option<&PhpValue> {
+        match self {
+            PhpValue::Object { properties, .. } => properties
+                .iter()
+                .find(|(name, _)| name == property)
+                .map(|(_, value)| value),
+            PhpValue::Array(entries) => entries
+                .iter()
+                .find(|(key, _)| matches!(key, PhpValue::String(key) if key == property))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the value as an integer, accepting numeric strings since
+     * AWBW stores many numbers as strings.
+     */
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            PhpValue::Int(value) => Some(*value),
+            PhpValue::String(value) => value.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PhpValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the values of an array in order.
+     */
+    pub fn values(&self) -> Option<Vec<&PhpValue>> {
+        match self {
+            PhpValue::Array(entries) => Some(entries.iter().map(|(_, value)| value).collect()),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Where and why decoding failed.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PhpError {
+    /** Byte offset into the input. */
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for PhpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.reason, self.offset)
+    }
+}
+
+impl std::error::Error for PhpError {}
+
+/**
+ * The most arrays and objects that can be nested inside each other. AWBW
+ * replays nest a handful deep, the limit stops crafted input overflowing
+ * the stack.
+ */
+pub const MAX_DEPTH: usize = 64;
+
+/**
+ * Decodes a single serialized value, which must make up all of `input`.
+ */
+pub fn unserialize(input: &[u8]) -> Result<PhpValue, PhpError> {
+    let mut parser = Parser {
+        input,
+        offset: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    if parser.offset != input.len() {
+        return Err(parser.error("Trailing data"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    offset: usize,
+    /** How many arrays and objects are being read. */
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> PhpError {
+        PhpError {
+            offset: self.offset,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), PhpError> {
+        if self.input.get(self.offset) != Some(&byte) {
+            return Err(self.error(&format!("Expected `{}`", byte as char)));
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    /**
+     * Reads up to (and consumes) `terminator`.
+     */
+    fn until(&mut self, terminator: u8) -> Result<&str, PhpError> {
+        let start = self.offset;
+        let length = self.input[start..]
+            .iter()
+            .position(|byte| *byte == terminator)
+            .ok_or_else(|| self.error(&format!("Missing `{}`", terminator as char)))?;
+        self.offset += length + 1;
+
+        std::str::from_utf8(&self.input[start..start + length]).map_err(|_| PhpError {
+            offset: start,
+            reason: "Invalid UTF-8".to_string(),
+        })
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, terminator: u8) -> Result<T, PhpError> {
+        let start = self.offset;
+        self.until(terminator)?.parse().map_err(|_| PhpError {
+            offset: start,
+            reason: "Invalid number".to_string(),
+        })
+    }
+
+    /**
+     * Reads a `<length>:"<bytes>"` string.
+     */
+    fn string(&mut self) -> Result<String, PhpError> {
+        let length = self.number::<usize>(b':')?;
+        self.expect(b'"')?;
+
+        let end = self
+            .offset
+            .checked_add(length)
+            .filter(|end| *end <= self.input.len())
+            .ok_or_else(|| self.error("String runs past the end"))?;
+        let value = String::from_utf8_lossy(&self.input[self.offset..end]).into_owned();
+        self.offset = end;
+
+        self.expect(b'"')?;
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<PhpValue, PhpError> {
+        let Some(kind) = self.input.get(self.offset).cloned() else {
+            return Err(self.error("Unexpected end"));
+        };
+        self.offset += 1;
+
+        if kind == b'N' {
+            self.expect(b';')?;
+            return Ok(PhpValue::Null);
+        }
+        self.expect(b':')?;
+
+        match kind {
+            b'b' => match self.until(b';')? {
+                "0" => Ok(PhpValue::Bool(false)),
+                "1" => Ok(PhpValue::Bool(true)),
+                _ => Err(self.error("Invalid bool")),
+            },
+            b'i' => Ok(PhpValue::Int(self.number(b';')?)),
+            b'd' => Ok(PhpValue::Float(self.number(b';')?)),
+            b's' => {
+                let value = self.string()?;
+                self.expect(b';')?;
+                Ok(PhpValue::String(value))
+            }
+            b'a' => {
+                let entries = self.entries()?;
+                Ok(PhpValue::Array(entries))
+            }
+            b'O' => {
+                let class = self.string()?;
+                self.expect(b':')?;
+                let properties = self
+                    .entries()?
+                    .into_iter()
+                    .map(|(key, value)| match key {
+                        // Private and protected properties are prefixed
+                        // with the class name or `*` between NUL bytes.
+                        PhpValue::String(name) => Ok((
+                            name.rsplit('\0').next().unwrap_or_default().to_string(),
+                            value,
+                        )),
+                        _ => Err(self.error("Object property is not a string")),
+                    })
+                    .collect::<Result<Vec<(String, PhpValue)>, PhpError>>()?;
+                Ok(PhpValue::Object { class, properties })
+            }
+            _ => {
+                self.offset -= 2;
+                Err(self.error(&format!("Unknown type `{}`", kind as char)))
+            }
+        }
+    }
+
+    /**
+     * Reads a `<count>:{<key><value>...}` list of pairs.
+     */
+    fn entries(&mut self) -> Result<Vec<(PhpValue, PhpValue)>, PhpError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("Nested too deeply"));
+        }
+
+        self.depth += 1;
+        let entries = self.entries_within();
+        self.depth -= 1;
+        entries
+    }
+
+    fn entries_within(&mut self) -> Result<Vec<(PhpValue, PhpValue)>, PhpError> {
+        let count = self.number::<usize>(b':')?;
+        self.expect(b'{')?;
+
+        // Every entry is at least 4 bytes so do not trust huge counts.
+        let mut entries = Vec::with_capacity(count.min(self.input.len() / 4));
+        for _ in 0..count {
+            let key = self.value()?;
+            let value = self.value()?;
+            entries.push((key, value));
+        }
+
+        self.expect(b'}')?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        assert_eq!(Ok(PhpValue::Null), unserialize(b"N;"));
+        assert_eq!(Ok(PhpValue::Bool(true)), unserialize(b"b:1;"));
+        assert_eq!(Ok(PhpValue::Int(-12)), unserialize(b"i:-12;"));
+        assert_eq!(Ok(PhpValue::Float(1.5)), unserialize(b"d:1.5;"));
+        assert_eq!(
+            Ok(PhpValue::String("a\"b;".to_string())),
+            unserialize(b"s:4:\"a\"b;\";")
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let value =
+            unserialize(b"O:4:\"Unit\":2:{s:1:\"x\";i:3;s:5:\"\0*\0hp\";a:1:{i:0;s:2:\"10\";}}")
+                .unwrap();
+
+        assert_eq!(Some(3), value.get("x").and_then(PhpValue::as_int));
+        assert_eq!(
+            Some(vec![&PhpValue::String("10".to_string())]),
+            value.get("hp").and_then(PhpValue::values)
+        );
+        assert_eq!(
+            Some(10),
+            value.get("hp").unwrap().values().unwrap()[0].as_int()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Some(0),
+            unserialize(b"x:1;").err().map(|error| error.offset)
+        );
+        assert!(unserialize(b"s:10:\"short\";").is_err());
+        assert!(unserialize(b"a:1000000000:{}").is_err());
+        assert!(unserialize(b"i:1;i:2;").is_err());
+        assert!(unserialize(b"").is_err());
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| {
+            let mut input = "a:1:{i:0;".repeat(depth);
+            input.push_str("N;");
+            input.push_str(&"}".repeat(depth));
+            input
+        };
+
+        assert!(unserialize(nested(MAX_DEPTH).as_bytes()).is_ok());
+        assert_eq!(
+            Some("Nested too deeply".to_string()),
+            unserialize(nested(MAX_DEPTH + 1).as_bytes())
+                .err()
+                .map(|error| error.reason)
+        );
+        assert!(unserialize(nested(1_000_000).as_bytes()).is_err());
+    }
+}
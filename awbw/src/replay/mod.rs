@@ -0,0 +1,924 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+use common::action::ActionError;
+use common::builder::{BuildError, GameStateBuilder};
+use common::cancel::CancelToken;
+use common::map::{country_from_awbw_id, CountryKind, Map};
+use common::officer::{OfficerKind, PowerKind};
+use common::player::PlayerState;
+use common::settings::GameSettings;
+use common::unit::UnitKind;
+use common::weather::Weather;
+use common::{GameState, UnitState};
+use flate2::read::GzDecoder;
+
+#[cfg(feature = "json")]
+use crate::message::actions_from_message;
+use crate::php::{unserialize, PhpError, PhpValue};
+
+/**
+ * Why a replay could not be read.
+ */
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    /** A turn was not valid PHP serialized data. */
+    Php {
+        turn: usize,
+        error: PhpError,
+    },
+    /** A turn decoded but was missing or had unexpected data. */
+    Format {
+        turn: usize,
+        reason: String,
+    },
+    /** A turn decoded but did not make a consistent state. */
+    Build {
+        turn: usize,
+        error: BuildError,
+    },
+    /** The `action`th action of a turn could not be applied to the state
+     * before it. */
+    Action {
+        turn: usize,
+        action: usize,
+        error: ActionError,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "{}", error),
+            ReplayError::Php { turn, error } => write!(f, "Turn {}: {}", turn, error),
+            ReplayError::Format { turn, reason } => write!(f, "Turn {}: {}", turn, reason),
+            ReplayError::Build { turn, error } => write!(f, "Turn {}: {}", turn, error),
+            ReplayError::Action {
+                turn,
+                action,
+                error,
+            } => write!(f, "Turn {}, action {}: {}", turn, action, error),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(error: std::io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+/**
+ * The states read from a replay.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    /** The state at the start of every turn in order. */
+    pub turns: Vec<GameState>,
+    /** True if reading was cancelled, `turns` then only holds the turns
+     * read so far. */
+    pub cancelled: bool,
+}
+
+/**
+ * Reads the gzipped turn file of a downloaded AWBW replay, which holds one
+ * PHP serialized `awbwGame` per line, into the state at the start of each
+ * turn.
+ *
+ * Replays only record properties and units so the terrain comes from
+ * `map`. Property owners are read from the country of each building.
+ * The states within each turn come from the separate action file, see
+ * `read_actions`.
+ */
+pub fn read_replay(reader: impl Read, map: &Map) -> Result<Replay, ReplayError> {
+    read_replay_with_cancel(reader, map, &CancelToken::new())
+}
+
+/**
+ * Same as `read_replay` but stops once `cancel` is cancelled and returns
+ * the turns read so far.
+ */
+pub fn read_replay_with_cancel(
+    reader: impl Read,
+    map: &Map,
+    cancel: &CancelToken,
+) -> Result<Replay, ReplayError> {
+    let mut turns = Vec::new();
+
+    for line in BufReader::new(GzDecoder::new(reader)).split(b'\n') {
+        if cancel.is_cancelled() {
+            return Ok(Replay {
+                turns,
+                cancelled: true,
+            });
+        }
+
+        let line = line?;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let turn = turns.len();
+        let game =
+            unserialize(line.trim_ascii()).map_err(|error| ReplayError::Php { turn, error })?;
+        turns.push(turn_to_state(turn, &game, map)?);
+    }
+
+    Ok(Replay {
+        turns,
+        cancelled: false,
+    })
+}
+
+/**
+ * Reads the ID of the map a game is played on from the first turn of the
+ * gzipped turn file of its replay, so its terrain can be downloaded before
+ * the turns are read.
+ */
+pub fn read_map_id(reader: impl Read) -> Result<u64, ReplayError> {
+    for line in BufReader::new(GzDecoder::new(reader)).split(b'\n') {
+        let line = line?;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let game =
+            unserialize(line.trim_ascii()).map_err(|error| ReplayError::Php { turn: 0, error })?;
+        let map_id = int_field(0, &game, "maps_id")?;
+        return u64::try_from(map_id).map_err(|_| format_error(0, "Negative `maps_id`"));
+    }
+
+    Err(format_error(0, "No turns"))
+}
+
+/**
+ * Reads the gzipped action file of a downloaded AWBW replay into the state
+ * after every action of every turn in `replay`.
+ *
+ * The file holds a line per turn, `p:<player>;d:<day>;a:` followed by a PHP
+ * serialized array whose third entry lists the turn's actions as JSON,
+ * which are decoded by `actions_from_message`. Entry `i` of the result
+ * starts from `replay.turns[i]` and holds one state per action. Actions
+ * that do not change anything this crate models, such as supplying, keep
+ * the state before them so entries line up with the replay's actions.
+ */
+#[cfg(feature = "json")]
+pub fn read_actions(
+    reader: impl Read,
+    replay: &Replay,
+) -> Result<Vec<Vec<GameState>>, ReplayError> {
+    let mut turns = Vec::new();
+
+    for line in BufReader::new(GzDecoder::new(reader)).split(b'\n') {
+        let line = line?;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let turn = turns.len();
+        let mut game_state = replay
+            .turns
+            .get(turn)
+            .cloned()
+            .ok_or_else(|| format_error(turn, "Actions for a turn the replay does not have"))?;
+        let line = line.trim_ascii();
+        let start = line
+            .windows(3)
+            .position(|window| window == b";a:")
+            .ok_or_else(|| format_error(turn, "Missing `a:`"))?;
+        let actions =
+            unserialize(&line[start + 3..]).map_err(|error| ReplayError::Php { turn, error })?;
+        let actions = actions
+            .values()
+            .and_then(|values| values.get(2).and_then(|actions| actions.values()))
+            .ok_or_else(|| format_error(turn, "Missing list of actions"))?;
+
+        let mut states = Vec::with_capacity(actions.len());
+        for (action, message) in actions.into_iter().enumerate() {
+            let message = message
+                .as_str()
+                .ok_or_else(|| format_error(turn, format!("Action {} is not a string", action)))?;
+            let decoded = actions_from_message(&game_state, message)
+                .map_err(|error| format_error(turn, format!("Action {}: {}", action, error.0)))?;
+            for applied in decoded.iter().flatten() {
+                game_state
+                    .apply_in_place(applied)
+                    .map_err(|error| ReplayError::Action {
+                        turn,
+                        action,
+                        error,
+                    })?;
+            }
+            states.push(game_state.clone());
+        }
+        turns.push(states);
+    }
+
+    Ok(turns)
+}
+
+fn format_error(turn: usize, reason: impl Into<String>) -> ReplayError {
+    ReplayError::Format {
+        turn,
+        reason: reason.into(),
+    }
+}
+
+fn int_field(turn: usize, value: &PhpValue, field: &str) -> Result<i64, ReplayError> {
+    value
+        .get(field)
+        .and_then(PhpValue::as_int)
+        .ok_or_else(|| format_error(turn, format!("Missing number `{}`", field)))
+}
+
+fn str_field<'a>(turn: usize, value: &'a PhpValue, field: &str) -> Result<&'a str, ReplayError> {
+    value
+        .get(field)
+        .and_then(PhpValue::as_str)
+        .ok_or_else(|| format_error(turn, format!("Missing string `{}`", field)))
+}
+
+fn list_field<'a>(
+    turn: usize,
+    value: &'a PhpValue,
+    field: &str,
+) -> Result<Vec<&'a PhpValue>, ReplayError> {
+    value
+        .get(field)
+        .and_then(PhpValue::values)
+        .ok_or_else(|| format_error(turn, format!("Missing list `{}`", field)))
+}
+
+/**
+ * The tile at the `x` and `y` fields of `value`, which must be on `map`.
+ */
+fn location_field(turn: usize, value: &PhpValue, map: &Map) -> Result<usize, ReplayError> {
+    let (width, height) = map.dimensions();
+    let (x, y) = (int_field(turn, value, "x")?, int_field(turn, value, "y")?);
+    usize::try_from(x)
+        .ok()
+        .zip(usize::try_from(y).ok())
+        .filter(|(x, y)| *x < width && *y < height)
+        .map(|(x, y)| y * width + x)
+        .ok_or_else(|| format_error(turn, format!("({}, {}) is off the map", x, y)))
+}
+
+/**
+ * Converts one decoded `awbwGame` into a state on `map`, with its players,
+ * units, property owners, day, active player, weather and match options.
+ */
+fn turn_to_state(turn: usize, game: &PhpValue, map: &Map) -> Result<GameState, ReplayError> {
+    let mut players = list_field(turn, game, "players")?;
+    players.sort_by_key(|player| player.get("order").and_then(PhpValue::as_int));
+
+    let mut builder = GameStateBuilder::new(map);
+    let mut player_indices = BTreeMap::new();
+    let mut countries = Vec::new();
+    let mut teams: Vec<(String, Vec<usize>)> = Vec::new();
+
+    for (index, player) in players.iter().enumerate() {
+        player_indices.insert(int_field(turn, player, "id")?, index);
+
+        let country = int_field(turn, player, "countries_id")?;
+        let country = country_from_id(country)
+            .ok_or_else(|| format_error(turn, format!("Unknown country {}", country)))?;
+        countries.push(country.clone());
+        let officer = int_field(turn, player, "co_id")?;
+        let officer = officer_from_id(officer)
+            .ok_or_else(|| format_error(turn, format!("Unknown officer {}", officer)))?;
+        let power = match player.get("co_power_on").and_then(PhpValue::as_str) {
+            Some("Y") => PowerKind::Normal,
+            Some("S") => PowerKind::Super,
+            _ => PowerKind::None,
+        };
+        // The meter is filled with the funds value of damage, as stored here.
+        let charge = player
+            .get("co_power")
+            .and_then(PhpValue::as_int)
+            .and_then(|charge| u32::try_from(charge).ok())
+            .unwrap_or(0);
+        builder =
+            builder.player_state(PlayerState::new(country, officer, power).with_charge(charge));
+
+        // Players without a team are on their own.
+        let team = match player.get("team") {
+            Some(PhpValue::String(team)) if !team.is_empty() => team.clone(),
+            Some(PhpValue::Int(team)) => team.to_string(),
+            _ => format!("player {}", index),
+        };
+        match teams.iter_mut().find(|(name, _)| *name == team) {
+            Some((_, members)) => members.push(index),
+            None => teams.push((team, vec![index])),
+        }
+    }
+
+    for (_, members) in teams {
+        builder = builder.team(members);
+    }
+
+    let mut units = Vec::new();
+    let mut cargo = BTreeMap::<usize, Vec<UnitState>>::new();
+    for unit in list_field(turn, game, "units")? {
+        let owner = int_field(turn, unit, "players_id")?;
+        let player = *player_indices
+            .get(&owner)
+            .ok_or_else(|| format_error(turn, format!("Unit owned by unknown player {}", owner)))?;
+        let name = str_field(turn, unit, "name")?;
+        let kind = unit_from_name(name)
+            .ok_or_else(|| format_error(turn, format!("Unknown unit `{}`", name)))?;
+        let stealthed = unit.get("sub_dive").and_then(PhpValue::as_str) == Some("Y");
+
+        // Loaded units share their transport's tile.
+        let location = location_field(turn, unit, map)?;
+
+        let mut unit_state = UnitState::new(player, stealthed, kind);
+        match unit.get("hit_points") {
+            // AWBW shows the hit points rounded up.
+            Some(PhpValue::Float(hp)) => unit_state.set_hp(hp.ceil() as u8),
+            Some(hp) => {
+                if let Some(hp) = hp.as_int() {
+                    unit_state.set_hp(hp.clamp(0, u8::MAX as i64) as u8);
+                }
+            }
+            None => {}
+        }
+        if let Some(fuel) = unit.get("fuel").and_then(PhpValue::as_int) {
+            unit_state.set_fuel(fuel.clamp(0, u8::MAX as i64) as u8);
+        }
+        if let Some(ammo) = unit.get("ammo").and_then(PhpValue::as_int) {
+            unit_state.set_ammo(ammo.clamp(0, u8::MAX as i64) as u8);
+        }
+
+        if unit.get("carried").and_then(PhpValue::as_str) == Some("Y") {
+            cargo.entry(location).or_default().push(unit_state);
+        } else {
+            units.push((location, unit_state));
+        }
+    }
+
+    for (location, cargo) in cargo {
+        let (_, transport) = units
+            .iter_mut()
+            .find(|(transport, _)| *transport == location)
+            .ok_or_else(|| {
+                format_error(
+                    turn,
+                    format!("Loaded unit at {} has no transport", location),
+                )
+            })?;
+        *transport = transport.clone().with_cargo(cargo);
+    }
+    for (location, unit) in units {
+        builder = builder.unit(location, unit);
+    }
+
+    // Only owned properties are listed by their owner's country, which
+    // each player has one of.
+    for building in game
+        .get("buildings")
+        .and_then(PhpValue::values)
+        .unwrap_or_default()
+    {
+        let id = int_field(turn, building, "terrain_id")?;
+        let Some(country) = u32::try_from(id).ok().and_then(country_from_awbw_id) else {
+            continue;
+        };
+        let player = countries
+            .iter()
+            .position(|other| *other == country)
+            .ok_or_else(|| {
+                format_error(turn, format!("Building of {:?} has no player", country))
+            })?;
+        builder = builder.owner(location_field(turn, building, map)?, player);
+    }
+
+    if let Some(day) = game.get("day").and_then(PhpValue::as_int) {
+        let day = u32::try_from(day).map_err(|_| format_error(turn, format!("Bad day {}", day)))?;
+        builder = builder.day(day);
+    }
+    if let Some(active) = game.get("turn").and_then(PhpValue::as_int) {
+        let player = *player_indices
+            .get(&active)
+            .ok_or_else(|| format_error(turn, format!("Turn of unknown player {}", active)))?;
+        builder = builder.active_player(player);
+    }
+    if let Some(weather) = game
+        .get("weather_type")
+        .or_else(|| game.get("weather_code"))
+        .and_then(PhpValue::as_str)
+    {
+        builder = builder.weather(match weather {
+            "Clear" | "C" => Weather::Clear,
+            "Rain" | "R" => Weather::Rain,
+            "Snow" | "S" => Weather::Snow,
+            _ => return Err(format_error(turn, format!("Unknown weather `{}`", weather))),
+        });
+    }
+
+    builder
+        .settings(game_settings(game))
+        .build()
+        .map_err(|error| ReplayError::Build { turn, error })
+}
+
+/**
+ * Reads the match options from an `awbwGame`, keeping AWBW's fog league
+ * for any that are missing.
+ */
+fn game_settings(game: &PhpValue) -> GameSettings {
+    let funds = |field: &str| {
+        game.get(field)
+            .and_then(PhpValue::as_int)
+            .and_then(|funds| u32::try_from(funds).ok())
+    };
+
+    let mut settings = GameSettings::fog_league();
+    if let Some(fog) = game.get("fog").and_then(PhpValue::as_str) {
+        settings.fog = fog == "Y";
+    }
+    if let Some(starting_funds) = funds("starting_funds") {
+        settings.starting_funds = starting_funds;
+    }
+    if let Some(funds_per_property) = funds("funds") {
+        settings.funds_per_property = funds_per_property;
+    }
+    settings
+}
+
+/**
+ * Maps AWBW's `countries_id` to a country.
+ */
+pub fn country_from_id(id: i64) -> Option<CountryKind> {
+    Some(match id {
+        1 => CountryKind::OrangeStar,
+        2 => CountryKind::BlueMoon,
+        3 => CountryKind::GreenEarth,
+        4 => CountryKind::YellowComet,
+        5 => CountryKind::BlackHole,
+        6 => CountryKind::RedFire,
+        7 => CountryKind::GreySky,
+        8 => CountryKind::BrownDesert,
+        9 => CountryKind::AmberBlaze,
+        10 => CountryKind::JadeSun,
+        16 => CountryKind::CobaltIce,
+        17 => CountryKind::PinkCosmos,
+        19 => CountryKind::TealGalaxy,
+        20 => CountryKind::PurpleLightning,
+        21 => CountryKind::AcidRain,
+        22 => CountryKind::WhiteNova,
+        23 => CountryKind::AzureAsteroid,
+        24 => CountryKind::NoirEclipse,
+        25 => CountryKind::SilverClaw,
+        _ => return None,
+    })
+}
+
+/**
+ * Maps AWBW's `co_id` to an officer.
+ */
+pub fn officer_from_id(id: i64) -> Option<OfficerKind> {
+    Some(match id {
+        1 => OfficerKind::Andy,
+        2 => OfficerKind::Grit,
+        3 => OfficerKind::Kanbei,
+        5 => OfficerKind::Drake,
+        7 => OfficerKind::Max,
+        8 => OfficerKind::Sami,
+        9 => OfficerKind::Olaf,
+        10 => OfficerKind::Eagle,
+        11 => OfficerKind::Adder,
+        12 => OfficerKind::Hawke,
+        13 => OfficerKind::Sensei,
+        14 => OfficerKind::Jess,
+        15 => OfficerKind::Colin,
+        16 => OfficerKind::Lash,
+        17 => OfficerKind::Hachi,
+        18 => OfficerKind::Sonja,
+        19 => OfficerKind::Sasha,
+        20 => OfficerKind::Grimm,
+        21 => OfficerKind::Koal,
+        22 => OfficerKind::Jake,
+        23 => OfficerKind::Kindle,
+        24 => OfficerKind::Nell,
+        25 => OfficerKind::Flak,
+        26 => OfficerKind::Jugger,
+        27 => OfficerKind::Javier,
+        28 => OfficerKind::Rachel,
+        29 => OfficerKind::Sturm,
+        30 => OfficerKind::VonBolt,
+        _ => return None,
+    })
+}
+
+/**
+ * Maps the unit names AWBW uses to a unit.
+ */
+pub fn unit_from_name(name: &str) -> Option<UnitKind> {
+    Some(match name {
+        "Anti-Air" => UnitKind::AntiAir,
+        "APC" => UnitKind::Apc,
+        "Artillery" => UnitKind::Artillery,
+        "B-Copter" => UnitKind::BattleCopter,
+        "Battleship" => UnitKind::BattleShip,
+        "Black Boat" => UnitKind::BlackBoat,
+        "Black Bomb" => UnitKind::BlackBomb,
+        "Bomber" => UnitKind::Bomber,
+        "Carrier" => UnitKind::Carrier,
+        "Cruiser" => UnitKind::Cruiser,
+        "Fighter" => UnitKind::Fighter,
+        "Flare" => UnitKind::Flare,
+        "Infantry" => UnitKind::Infantry,
+        "Lander" => UnitKind::Lander,
+        "Md.Tank" | "Md. Tank" => UnitKind::MediumTank,
+        "Mech" => UnitKind::Mech,
+        "Mega Tank" => UnitKind::MegaTank,
+        "Missile" => UnitKind::Missile,
+        "Neotank" => UnitKind::NeoTank,
+        "Piperunner" => UnitKind::PipeRunner,
+        "Recon" => UnitKind::Recon,
+        "Rocket" => UnitKind::Rocket,
+        "Stealth" => UnitKind::Stealth,
+        "Sub" => UnitKind::Submarine,
+        "T-Copter" => UnitKind::TransportCopter,
+        "Tank" => UnitKind::Tank,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use common::map::TileKind;
+
+    use super::*;
+
+    fn s(value: &str) -> String {
+        format!("s:{}:\"{}\";", value.len(), value)
+    }
+
+    fn object(class: &str, properties: &[(&str, String)]) -> String {
+        let mut out = format!("O:{}:\"{}\":{}:{{", class.len(), class, properties.len());
+        for (name, value) in properties {
+            out.push_str(&s(name));
+            out.push_str(value);
+        }
+        out.push('}');
+        out
+    }
+
+    fn array(values: &[String]) -> String {
+        let mut out = format!("a:{}:{{", values.len());
+        for (index, value) in values.iter().enumerate() {
+            out.push_str(&format!("i:{};{}", index, value));
+        }
+        out.push('}');
+        out
+    }
+
+    fn player(id: i64, order: i64, country: i64, co: i64, power: &str, charge: i64) -> String {
+        object(
+            "awbwPlayer",
+            &[
+                ("id", format!("i:{};", id)),
+                ("team", s(&id.to_string())),
+                ("countries_id", format!("i:{};", country)),
+                ("co_id", format!("i:{};", co)),
+                ("co_power_on", s(power)),
+                ("co_power", format!("i:{};", charge)),
+                ("order", format!("i:{};", order)),
+            ],
+        )
+    }
+
+    fn unit(owner: i64, name: &str, x: i64, y: i64, dive: &str, carried: &str) -> String {
+        object(
+            "awbwUnit",
+            &[
+                ("players_id", format!("i:{};", owner)),
+                ("name", s(name)),
+                ("x", format!("i:{};", x)),
+                ("y", s(&y.to_string())),
+                ("sub_dive", s(dive)),
+                ("carried", s(carried)),
+            ],
+        )
+    }
+
+    fn building(terrain: i64, x: i64, y: i64) -> String {
+        object(
+            "awbwBuilding",
+            &[
+                ("terrain_id", format!("i:{};", terrain)),
+                ("x", format!("i:{};", x)),
+                ("y", s(&y.to_string())),
+            ],
+        )
+    }
+
+    fn gzip(lines: &[String]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for line in lines {
+            writeln!(encoder, "{}", line).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    fn two_turns() -> Vec<u8> {
+        let players = array(&[
+            player(502, 2, 2, 18, "N", 12000),
+            player(501, 1, 1, 1, "N", 0),
+        ]);
+
+        gzip(&[
+            object(
+                "awbwGame",
+                &[
+                    ("maps_id", "i:77301;".to_string()),
+                    ("day", "i:1;".to_string()),
+                    ("turn", "i:501;".to_string()),
+                    ("weather_type", s("Clear")),
+                    (
+                        "buildings",
+                        array(&[building(38, 0, 1), building(34, 0, 0)]),
+                    ),
+                    ("players", players.clone()),
+                    (
+                        "units",
+                        array(&[
+                            unit(501, "Infantry", 0, 0, "N", "N"),
+                            unit(502, "Sub", 2, 1, "Y", "N"),
+                        ]),
+                    ),
+                ],
+            ),
+            object(
+                "awbwGame",
+                &[
+                    ("day", s("2")),
+                    ("turn", "i:502;".to_string()),
+                    ("weather_code", s("R")),
+                    ("buildings", array(&[building(43, 0, 1)])),
+                    ("fog", s("N")),
+                    ("starting_funds", s("5000")),
+                    ("funds", "i:2000;".to_string()),
+                    ("players", players),
+                    (
+                        "units",
+                        array(&[
+                            unit(501, "APC", 1, 0, "N", "N"),
+                            unit(501, "Infantry", 1, 0, "N", "Y"),
+                            object(
+                                "awbwUnit",
+                                &[
+                                    ("players_id", "i:502;".to_string()),
+                                    ("name", s("Sub")),
+                                    ("x", "i:2;".to_string()),
+                                    ("y", "i:1;".to_string()),
+                                    ("hit_points", "d:4.2;".to_string()),
+                                    ("fuel", "i:30;".to_string()),
+                                    ("ammo", s("5")),
+                                ],
+                            ),
+                        ]),
+                    ),
+                ],
+            ),
+        ])
+    }
+
+    fn map() -> Map {
+        Map::new(
+            vec![
+                TileKind::Plain,
+                TileKind::Road,
+                TileKind::Sea,
+                TileKind::City,
+                TileKind::Plain,
+                TileKind::Sea,
+            ],
+            (3, 2),
+        )
+    }
+
+    #[test]
+    fn read_replay_turns() {
+        let replay = read_replay(two_turns().as_slice(), &map()).unwrap();
+        assert!(!replay.cancelled);
+
+        let first = GameStateBuilder::new(&map())
+            .player(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None)
+            .player_state(
+                PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None)
+                    .with_charge(12000),
+            )
+            .team([0])
+            .team([1])
+            .unit(0, UnitState::new(0, false, UnitKind::Infantry))
+            .unit(5, UnitState::new(1, true, UnitKind::Submarine))
+            .owner(3, 0)
+            .build()
+            .unwrap();
+
+        let second = GameStateBuilder::new(&map())
+            .player(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None)
+            .player_state(
+                PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None)
+                    .with_charge(12000),
+            )
+            .team([0])
+            .team([1])
+            .unit(
+                1,
+                UnitState::new(0, false, UnitKind::Apc).with_cargo(vec![UnitState::new(
+                    0,
+                    false,
+                    UnitKind::Infantry,
+                )]),
+            )
+            .unit(
+                5,
+                UnitState::new(1, false, UnitKind::Submarine)
+                    .with_hp(5)
+                    .with_fuel(30)
+                    .with_ammo(5),
+            )
+            .owner(3, 1)
+            .day(2)
+            .active_player(1)
+            .weather(Weather::Rain)
+            .settings(GameSettings {
+                fog: false,
+                starting_funds: 5000,
+                funds_per_property: 2000,
+                ..GameSettings::fog_league()
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(vec![first, second], replay.turns);
+    }
+
+    #[test]
+    fn read_map_id_from_first_turn() {
+        assert_eq!(77301, read_map_id(two_turns().as_slice()).unwrap());
+        assert!(matches!(
+            read_map_id(gzip(&[object("awbwGame", &[("day", "i:1;".to_string())])]).as_slice()),
+            Err(ReplayError::Format { turn: 0, .. })
+        ));
+        assert!(matches!(
+            read_map_id(gzip(&[]).as_slice()),
+            Err(ReplayError::Format { turn: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn read_replay_cancelled() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let replay = read_replay_with_cancel(two_turns().as_slice(), &map(), &cancel).unwrap();
+        assert!(replay.cancelled);
+        assert!(replay.turns.is_empty());
+    }
+
+    #[test]
+    fn read_replay_errors() {
+        assert!(matches!(
+            read_replay(gzip(&["i:1".to_string()]).as_slice(), &map()),
+            Err(ReplayError::Php { turn: 0, .. })
+        ));
+        assert!(matches!(
+            read_replay(gzip(&["i:1;".to_string()]).as_slice(), &map()),
+            Err(ReplayError::Format { turn: 0, .. })
+        ));
+        for (x, y) in [(-1, 0), (3, 0), (0, 2), (0, i64::MAX)] {
+            let game = object(
+                "awbwGame",
+                &[
+                    ("players", array(&[player(501, 1, 1, 1, "N", 0)])),
+                    ("units", array(&[unit(501, "Infantry", x, y, "N", "N")])),
+                ],
+            );
+            assert!(matches!(
+                read_replay(gzip(&[game]).as_slice(), &map()),
+                Err(ReplayError::Format { turn: 0, .. })
+            ));
+        }
+        for (name, value) in [
+            ("buildings", array(&[building(53, 0, 1)])),
+            ("turn", "i:999;".to_string()),
+            ("weather_type", s("Fog")),
+        ] {
+            let game = object(
+                "awbwGame",
+                &[
+                    (name, value),
+                    ("players", array(&[player(501, 1, 1, 1, "N", 0)])),
+                    ("units", array(&[])),
+                ],
+            );
+            assert!(matches!(
+                read_replay(gzip(&[game]).as_slice(), &map()),
+                Err(ReplayError::Format { turn: 0, .. })
+            ));
+        }
+        assert!(matches!(
+            read_replay(&b"not gzip"[..], &map()),
+            Err(ReplayError::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    fn action_line(player: i64, day: i64, actions: &[&str]) -> String {
+        let actions = actions.iter().map(|action| s(action)).collect::<Vec<_>>();
+        format!(
+            "p:{};d:{};a:{}",
+            player,
+            day,
+            array(&[
+                format!("i:{};", player),
+                format!("i:{};", day),
+                array(&actions)
+            ])
+        )
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn read_actions_per_action() {
+        use common::action::Action;
+
+        let replay = read_replay(two_turns().as_slice(), &map()).unwrap();
+        let actions = gzip(&[
+            action_line(
+                501,
+                1,
+                &[
+                    r#"{"action":"Move","paths":{"global":[{"x":0,"y":0},{"x":0,"y":1}]}}"#,
+                    r#"{"action":"Supply"}"#,
+                    r#"{"action":"End"}"#,
+                ],
+            ),
+            action_line(502, 1, &[]),
+        ]);
+
+        let moved = replay.turns[0]
+            .apply(&Action::Move { from: 0, to: 3 })
+            .unwrap();
+        let ended = moved.apply(&Action::EndTurn).unwrap();
+        assert_eq!(
+            vec![vec![moved.clone(), moved, ended], vec![]],
+            read_actions(actions.as_slice(), &replay).unwrap()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn read_actions_errors() {
+        let replay = read_replay(two_turns().as_slice(), &map()).unwrap();
+        let read = |lines: &[String]| read_actions(gzip(lines).as_slice(), &replay);
+
+        let missing_unit = r#"{"action":"Move","paths":{"global":[{"x":1,"y":1},{"x":0,"y":1}]}}"#;
+        assert!(matches!(
+            read(&[action_line(501, 1, &[r#"{"action":"End"}"#, missing_unit])]),
+            Err(ReplayError::Action {
+                turn: 0,
+                action: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            read(&[action_line(501, 1, &["not json"])]),
+            Err(ReplayError::Format { turn: 0, .. })
+        ));
+        assert!(matches!(
+            read(&["p:501;d:1;".to_string()]),
+            Err(ReplayError::Format { turn: 0, .. })
+        ));
+        assert!(matches!(
+            read(&[
+                action_line(501, 1, &[]),
+                action_line(502, 1, &[]),
+                action_line(501, 2, &[])
+            ]),
+            Err(ReplayError::Format { turn: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn country_ids() {
+        assert_eq!(Some(CountryKind::RedFire), country_from_id(6));
+        assert_eq!(Some(CountryKind::CobaltIce), country_from_id(16));
+        assert_eq!(Some(CountryKind::WhiteNova), country_from_id(22));
+        assert_eq!(Some(CountryKind::SilverClaw), country_from_id(25));
+        assert_eq!(None, country_from_id(11));
+    }
+}
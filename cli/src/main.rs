@@ -0,0 +1,302 @@
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use awbw::client::{Client, ClientError};
+use clap::{Parser, Subcommand};
+use common::error::VisionError;
+use common::map::{from_awbw_text, AwbwMapError, Map};
+use common::render::ascii::{render_ansi, render_ascii};
+use common::vision::VisionConfig;
+use common::GameState;
+
+/**
+ * Works out what every team of an AWBW game can see.
+ */
+#[derive(Debug, Parser)]
+#[command(name = "awbw-vision", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Prints the tiles every team can see in a state saved as JSON
+    Compute {
+        /// Path to the state, in the layout `GameState` serializes to
+        state: PathBuf,
+        /// Print a `CommonVisionReport` as JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Follows a game on AWBW, printing its common vision whenever it changes
+    Watch {
+        /// The game's ID, as in `game.php?games_id=`
+        #[arg(long)]
+        game: u64,
+        /// Path to the game's map as exported from AWBW, instead of
+        /// downloading the map the game is played on
+        #[arg(long)]
+        map: Option<PathBuf>,
+        /// Seconds to wait between downloads
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Download the game once and exit
+        #[arg(long)]
+        once: bool,
+        /// Print a `CommonVisionReport` as one line of JSON per change
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Compute { state, json } => compute(&state, json),
+        Command::Watch {
+            game,
+            map,
+            interval,
+            once,
+            json,
+        } => watch(
+            game,
+            map.as_deref(),
+            Duration::from_secs(interval),
+            once,
+            json,
+        ),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/**
+ * Why a command failed.
+ */
+#[derive(Debug)]
+enum CliError {
+    /** A file could not be read. */
+    Read {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /** A state file did not hold a `GameState`. */
+    State {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    /** A map file was not a valid AWBW export. */
+    Map {
+        path: PathBuf,
+        error: AwbwMapError,
+    },
+    /** The game or its map could not be downloaded from AWBW. */
+    Client {
+        game_id: u64,
+        error: ClientError,
+    },
+    Vision(VisionError),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Read { path, error } => {
+                write!(f, "Could not read {}: {}", path.display(), error)
+            }
+            CliError::State { path, error } => {
+                write!(f, "Could not parse {}: {}", path.display(), error)
+            }
+            CliError::Map { path, error } => {
+                write!(f, "Could not parse {}: {}", path.display(), error)
+            }
+            CliError::Client { game_id, error } => {
+                write!(f, "Could not fetch game {}: {}", game_id, error)
+            }
+            CliError::Vision(error) => write!(f, "Could not compute vision: {}", error),
+            CliError::Json(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+fn read_file(path: &Path) -> Result<String, CliError> {
+    std::fs::read_to_string(path).map_err(|error| CliError::Read {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn compute(path: &Path, json: bool) -> Result<(), CliError> {
+    let text = read_file(path)?;
+    let game_state: GameState = serde_json::from_str(&text).map_err(|error| CliError::State {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    if json {
+        println!("{}", json_report(&game_state)?);
+    } else {
+        print!("{}", report(&game_state, std::io::stdout().is_terminal()));
+    }
+    Ok(())
+}
+
+/**
+ * Downloads game `game_id` every `interval` and prints its common vision
+ * whenever the state has changed. Failed downloads are reported and
+ * retried unless `once` is set.
+ *
+ * The game's map is downloaded once up front unless `map_path` gives an
+ * exported copy to use offline.
+ */
+fn watch(
+    game_id: u64,
+    map_path: Option<&Path>,
+    interval: Duration,
+    once: bool,
+    json: bool,
+) -> Result<(), CliError> {
+    let client = Client::new();
+    let map = match map_path {
+        Some(map_path) => read_map(map_path)?,
+        None => client
+            .game_map(game_id)
+            .map_err(|error| CliError::Client { game_id, error })?,
+    };
+
+    let mut last = None;
+    loop {
+        match client
+            .latest_state(game_id, &map)
+            .map_err(|error| CliError::Client { game_id, error })
+        {
+            Ok(game_state) if last.as_ref() != Some(&game_state) => {
+                if json {
+                    println!("{}", json_report(&game_state)?);
+                } else {
+                    println!(
+                        "Game {}, day {}, player {} to move",
+                        game_id,
+                        game_state.day(),
+                        game_state.active_player()
+                    );
+                    print!("{}", report(&game_state, std::io::stdout().is_terminal()));
+                }
+                last = Some(game_state);
+            }
+            Ok(_) => {}
+            Err(error) if once => return Err(error),
+            Err(error) => eprintln!("{}", error),
+        }
+
+        if once {
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/**
+ * Lists the commonly visible tiles as (x, y) then draws them on the map,
+ * with every other tile shown as `-` or shaded if `color` is set.
+ */
+fn report(game_state: &GameState, color: bool) -> String {
+    let positions = game_state.common_vision_positions();
+
+    let mut text = format!("{} commonly visible tiles\n", positions.len());
+    for position in positions.iter() {
+        text.push_str(&format!("({}, {})\n", position.x, position.y));
+    }
+    text.push('\n');
+    let visible = game_state.common_vision();
+    if color {
+        text.push_str(&render_ansi(game_state, &visible));
+    } else {
+        text.push_str(&render_ascii(game_state, &visible));
+    }
+    text
+}
+
+/**
+ * Reads a map exported from AWBW.
+ */
+fn read_map(path: &Path) -> Result<Map, CliError> {
+    let (tiles, dimensions) = from_awbw_text(&read_file(path)?).map_err(|error| CliError::Map {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    Ok(Map::new(tiles, dimensions))
+}
+
+fn json_report(game_state: &GameState) -> Result<String, CliError> {
+    let report = game_state
+        .common_vision_report(&VisionConfig::default())
+        .map_err(CliError::Vision)?;
+    serde_json::to_string(&report).map_err(CliError::Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use common::ascii::parse_game_state;
+
+    #[test]
+    fn report_lists_and_draws_common_vision() {
+        let game_state = parse_game_state(".0inf f .1inf\n. . .").unwrap();
+
+        assert_eq!(
+            "4 commonly visible tiles\n(0, 0)\n(1, 0)\n(2, 0)\n(1, 1)\n\nIfI\n-.-\n",
+            report(&game_state, false)
+        );
+    }
+
+    #[test]
+    fn json_report_uses_report_layout() {
+        let game_state = parse_game_state(".0inf f .1inf\n. . .").unwrap();
+
+        assert_eq!(
+            r#"{"version":1,"width":3,"height":2,"tiles":[0,1,2,4]}"#,
+            json_report(&game_state).unwrap()
+        );
+    }
+
+    #[test]
+    fn watch_needs_only_the_game() {
+        let cli = Cli::try_parse_from(["awbw-vision", "watch", "--game", "123456"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Watch {
+                game: 123456,
+                map: None,
+                ..
+            }
+        ));
+
+        let cli =
+            Cli::try_parse_from(["awbw-vision", "watch", "--game", "1", "--map", "m.txt"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Watch { map: Some(map), .. } if map == Path::new("m.txt")
+        ));
+    }
+
+    #[test]
+    fn read_map_errors_are_typed() {
+        let path = std::env::temp_dir().join("awbw-vision-missing-map.txt");
+        assert!(matches!(read_map(&path), Err(CliError::Read { .. })));
+    }
+}
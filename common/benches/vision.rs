@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use common::coordinate::Position;
+use common::gen::{random_state, GenConfig};
+use common::GameState;
+
+/**
+ * Fogged two player states from a fixed seed so runs are comparable. The
+ * large map is the size of the biggest ranked maps with a few hundred
+ * units on it.
+ */
+fn states() -> Vec<(&'static str, usize, GameState)> {
+    [
+        ("small", 10, 0.2),
+        ("medium", 25, 0.15),
+        ("large", 48, 0.15),
+    ]
+    .into_iter()
+    .map(|(name, size, unit_density)| {
+        let config = GenConfig {
+            width: size,
+            height: size,
+            unit_density,
+            ..GenConfig::default()
+        };
+        (name, size, random_state(&config, 1))
+    })
+    .collect()
+}
+
+fn neighbors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbors");
+    for (name, size, game_state) in states() {
+        let centre = Position::new(size / 2, size / 2);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &game_state,
+            |b, state| {
+                b.iter(|| {
+                    for distance in 1..=5 {
+                        black_box(state.neighbors_at(black_box(centre), distance));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/**
+ * One pass of every unit's vision, which is what `visible_to_team` does
+ * on top of the private `vision_for_units`.
+ */
+fn vision_for_units(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vision_for_units");
+    for (name, _, game_state) in states() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &game_state,
+            |b, state| b.iter(|| black_box(state.visible_to_team(0))),
+        );
+    }
+    group.finish();
+}
+
+fn common_vision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("common_vision");
+    for (name, _, game_state) in states() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &game_state,
+            |b, state| b.iter(|| black_box(state.common_vision())),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, neighbors, vision_for_units, common_vision);
+criterion_main!(benches);
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use crate::placement::PlacementError;
+use crate::unit::UnitKind;
+use crate::{GameState, UnitState};
+
+/**
+ * A single change to a state, as made by a player or recorded in a replay.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /** Moves the unit at `from` to `to`, the path is not checked. */
+    Move { from: usize, to: usize },
+    /** The unit at `attacker` deals `damage` HP to the unit at `target`. */
+    Attack {
+        attacker: usize,
+        target: usize,
+        damage: u8,
+    },
+    /** The unit at `location` completes the capture of the property it is
+     * on. */
+    Capture { location: usize },
+    /** The owner of the production property at `location` builds a unit
+     * there. */
+    Build { location: usize, kind: UnitKind },
+    /** The unit at `location` boards the transport at `transport`. */
+    Load { location: usize, transport: usize },
+    /** The transport at `transport` drops its `index`th cargo onto
+     * `destination`. */
+    Unload {
+        transport: usize,
+        index: usize,
+        destination: usize,
+    },
+    /** The unit at `location` dives or hides. */
+    Dive { location: usize },
+    /** The unit at `location` surfaces or unhides. */
+    Surface { location: usize },
+    /** The active player ends their turn, see `GameState::end_turn`. */
+    EndTurn,
+}
+
+/**
+ * Why an `Action` could not be applied.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ActionError {
+    /** The action needs a unit where there is none. */
+    NoUnit { location: usize },
+    /** The action breaks the rules of the state it was applied to. */
+    Illegal(Action),
+    /** A moved unit could not be put where it was going. */
+    Placement(PlacementError),
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::NoUnit { location } => write!(f, "There is no unit at {}", location),
+            ActionError::Illegal(action) => write!(f, "{:?} can not be applied", action),
+            ActionError::Placement(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+impl From<PlacementError> for ActionError {
+    fn from(error: PlacementError) -> Self {
+        ActionError::Placement(error)
+    }
+}
+
+impl Action {
+    /**
+     * Returns the location of the unit the action is made with, or None if
+     * it is not made with a unit.
+     */
+    pub fn actor(&self) -> Option<usize> {
+        match self {
+            Action::Move { from, .. } => Some(*from),
+            Action::Attack { attacker, .. } => Some(*attacker),
+            Action::Capture { location }
+            | Action::Load { location, .. }
+            | Action::Dive { location }
+            | Action::Surface { location } => Some(*location),
+            Action::Unload { transport, .. } => Some(*transport),
+            Action::Build { .. } | Action::EndTurn => None,
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the state after `action`, leaving this one unchanged.
+     */
+    pub fn apply(&self, action: &Action) -> Result<GameState, ActionError> {
+        let mut game_state = self.clone();
+        game_state.apply_in_place(action)?;
+        Ok(game_state)
+    }
+
+    /**
+     * Applies `action` to this state.
+     *
+     * Returns an error and changes nothing if the action is not allowed.
+     */
+    pub fn apply_in_place(&mut self, action: &Action) -> Result<(), ActionError> {
+        if let Some(location) = action.actor() {
+            if !self.units.contains_key(&location) {
+                return Err(ActionError::NoUnit { location });
+            }
+        }
+
+        let applied = match action {
+            Action::Move { from, to } => {
+                self.relocate_unit(*from, *to)?;
+                true
+            }
+            Action::Attack {
+                attacker,
+                target,
+                damage,
+            } => self.attack(*attacker, *target, *damage),
+            Action::Capture { location } => self.capture(*location),
+            Action::Build { location, kind } => self.build(*location, kind.clone()),
+            Action::Load {
+                location,
+                transport,
+            } => self.load(*location, *transport),
+            Action::Unload {
+                transport,
+                index,
+                destination,
+            } => self.unload(*transport, *index, *destination),
+            Action::Dive { location } => self.dive(*location),
+            Action::Surface { location } => self.surface(*location),
+            Action::EndTurn => {
+                self.end_turn();
+                true
+            }
+        };
+
+        if applied {
+            Ok(())
+        } else {
+            Err(ActionError::Illegal(action.clone()))
+        }
+    }
+
+    /**
+     * The unit at `attacker` deals `damage` HP to the enemy unit at
+     * `target`, destroying it and its cargo if none is left. The attacker
+     * uses up one ammo if it has any and fires its primary weapon, see
+     * `UnitKind::uses_ammo_against`. Range is not checked.
+     *
+     * Returns false and changes nothing if either unit is missing or they
+     * are on the same team.
+     */
+    pub fn attack(&mut self, attacker: usize, target: usize, damage: u8) -> bool {
+        let (Some(attacking), Some(defending)) =
+            (self.units.get(&attacker), self.units.get(&target))
+        else {
+            return false;
+        };
+        if attacker == target || self.allies_of(attacking.player).contains(&defending.player) {
+            return false;
+        }
+        let hp = defending.hp().saturating_sub(damage);
+        let uses_ammo = attacking.kind.uses_ammo_against(&defending.kind);
+
+        if let Some(unit) = self.units.get_mut(&attacker) {
+            if uses_ammo && unit.ammo() > 0 {
+                unit.set_ammo(unit.ammo() - 1);
+            }
+        }
+
+        if hp == 0 {
+            self.units.remove(&target);
+        } else if let Some(unit) = self.units.get_mut(&target) {
+            unit.set_hp(hp);
+        }
+        true
+    }
+
+    /**
+     * The unit at `location` completes the capture of the property it is
+     * on, handing it to the unit's player.
+     *
+     * Returns false and changes nothing if there is no capturing unit at
+     * `location`, it is not on a property or an ally already owns it.
+     */
+    pub fn capture(&mut self, location: usize) -> bool {
+        let Some(unit) = self.units.get(&location) else {
+            return false;
+        };
+        if !unit.kind.can_capture()
+            || self
+                .owner_of(location)
+                .map(|owner| self.allies_of(unit.player).contains(&owner))
+                .unwrap_or(false)
+        {
+            return false;
+        }
+
+        let player = unit.player;
+        self.set_owner(location, Some(player))
+    }
+
+    /**
+     * The owner of the property at `location` builds a `kind` there. Funds
+     * are not tracked so are not checked.
+     *
+     * Returns false and changes nothing if the tile is occupied, is not an
+     * owned property that builds `kind` or the ruleset has no such unit.
+     */
+    pub fn build(&mut self, location: usize, kind: UnitKind) -> bool {
+        let Some(player) = self.owner_of(location) else {
+            return false;
+        };
+        if self.units.contains_key(&location)
+            || self.map.cells().get(location) != Some(&kind.built_at())
+            || !self.ruleset.has_unit(&kind)
+        {
+            return false;
+        }
+
+        self.units
+            .insert(location, UnitState::new(player, false, kind));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn apply_leaves_the_original() {
+        let game_state = game_state!(".0inf . .1inf");
+
+        let moved = game_state.apply(&Action::Move { from: 0, to: 1 }).unwrap();
+        assert!(game_state.unit(0).is_some());
+        assert!(moved.unit(0).is_none() && moved.unit(1).is_some());
+
+        assert_eq!(
+            Err(ActionError::NoUnit { location: 1 }),
+            game_state.apply(&Action::Dive { location: 1 })
+        );
+        assert_eq!(
+            Err(ActionError::Illegal(Action::Dive { location: 0 })),
+            game_state.apply(&Action::Dive { location: 0 })
+        );
+        assert_eq!(
+            Err(ActionError::Placement(PlacementError::Occupied {
+                location: 2
+            })),
+            game_state.apply(&Action::Move { from: 0, to: 2 })
+        );
+    }
+
+    #[test]
+    fn attack_capture_and_build() {
+        let mut game_state = game_state!(".0inf .1inf b .0inf");
+        game_state.set_owner(2, Some(1));
+
+        assert!(!game_state.attack(0, 0, 5));
+        assert!(game_state.attack(0, 1, 4));
+        assert_eq!(6, game_state.unit(1).unwrap().hp());
+        assert!(game_state.attack(0, 1, 6));
+        assert!(game_state.unit(1).is_none());
+
+        assert!(!game_state.build(2, UnitKind::BattleShip));
+        assert!(game_state.build(2, UnitKind::Infantry));
+        assert_eq!(1, game_state.unit(2).unwrap().player());
+        assert!(!game_state.build(2, UnitKind::Infantry));
+
+        assert!(!game_state.capture(1));
+        game_state.units.remove(&2);
+        assert_eq!(Ok(()), game_state.relocate_unit(3, 2));
+        assert!(game_state.capture(2));
+        assert_eq!(Some(0), game_state.owner_of(2));
+        assert!(!game_state.capture(2));
+    }
+
+    #[test]
+    fn attack_spends_ammo_on_primary_weapon_only() {
+        let mut game_state = game_state!(".0tank .1inf .1tank ~1sub ~0lan");
+
+        // The machine gun needs no ammo, the cannon does.
+        assert!(game_state.attack(0, 1, 1));
+        assert_eq!(9, game_state.unit(0).unwrap().ammo());
+        assert!(game_state.attack(0, 2, 1));
+        assert_eq!(8, game_state.unit(0).unwrap().ammo());
+
+        assert!(game_state.attack(3, 4, 1));
+        assert_eq!(5, game_state.unit(3).unwrap().ammo());
+    }
+}
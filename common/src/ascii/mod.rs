@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::data::{TileData, UnitData};
+use crate::grid::Grid;
+use crate::map::{CountryKind, Map, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::unit::UnitKind;
+use crate::weather::Weather;
+use crate::{GameState, UnitState};
+
+/**
+ * Returns the character a tile is written as in ASCII maps.
+ */
+pub fn tile_symbol(tile: &TileKind) -> char {
+    match tile {
+        TileKind::Plain => '.',
+        TileKind::Mountain => '^',
+        TileKind::Forest => 'f',
+        TileKind::River => 'w',
+        TileKind::Road => '=',
+        TileKind::Bridge => '#',
+        TileKind::Sea => '~',
+        TileKind::Shoal => ',',
+        TileKind::Reef => '%',
+        TileKind::City => 'c',
+        TileKind::Base => 'b',
+        TileKind::Airport => 'a',
+        TileKind::Harbour => 'h',
+        TileKind::HeadQuarters => 'q',
+        TileKind::Pipe => 'p',
+        TileKind::PipeSeam => 'P',
+        TileKind::BrokenPipeSeam => 'r',
+        TileKind::Silo => 's',
+        TileKind::UsedSilo => 'S',
+        TileKind::CommunicationsTower => 't',
+        TileKind::Laboratory => 'l',
+        TileKind::Teleporter => '@',
+        TileKind::Void => 'x',
+    }
+}
+
+/**
+ * Returns the code a unit is written as in ASCII game states.
+ */
+pub fn unit_code(kind: &UnitKind) -> &'static str {
+    match kind {
+        UnitKind::AntiAir => "aa",
+        UnitKind::Apc => "apc",
+        UnitKind::Artillery => "art",
+        UnitKind::BattleCopter => "bcop",
+        UnitKind::BattleShip => "bship",
+        UnitKind::BlackBoat => "bboat",
+        UnitKind::BlackBomb => "bbomb",
+        UnitKind::Bomber => "bomb",
+        UnitKind::Carrier => "car",
+        UnitKind::Cruiser => "cru",
+        UnitKind::Fighter => "fig",
+        UnitKind::Flare => "flare",
+        UnitKind::Infantry => "inf",
+        UnitKind::Lander => "lan",
+        UnitKind::MediumTank => "md",
+        UnitKind::Mech => "mech",
+        UnitKind::MegaTank => "mega",
+        UnitKind::Missile => "mis",
+        UnitKind::NeoTank => "neo",
+        UnitKind::PipeRunner => "pipe",
+        UnitKind::Recon => "rec",
+        UnitKind::Rocket => "rock",
+        UnitKind::Stealth => "stl",
+        UnitKind::Submarine => "sub",
+        UnitKind::TransportCopter => "tcop",
+        UnitKind::Tank => "tank",
+    }
+}
+
+/**
+ * Parses a map written one row per line with whitespace between tiles.
+ *
+ * Blank lines are skipped. See `tile_symbol` for the character of each tile.
+ */
+pub fn parse_map(text: &str) -> Result<Map, String> {
+    let state = parse_game_state(text)?;
+    if !state.units.is_empty() {
+        return Err("Map contains units".to_string());
+    }
+
+    Ok(state.terrain())
+}
+
+/**
+ * Parses a game state written one row per line with whitespace between
+ * tiles.
+ *
+ * Each tile is its `tile_symbol` optionally followed by the owning player's
+ * number, the `unit_code` of the unit on it and a trailing `*` if the unit
+ * is stealthed, e.g. `f1inf` is an infantry owned by player 1 in a forest.
+ *
+ * An optional first line `teams: 0,1 2` groups players into teams, by
+ * default every player is on their own team. Players are given Andy with no
+ * power active and a distinct country.
+ *
+ * ```text
+ * teams: 0 1
+ * .0inf .     f
+ * ~     ~     .1sub*
+ * ```
+ */
+pub fn parse_game_state(text: &str) -> Result<GameState, String> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .peekable();
+
+    let teams = match lines.peek().and_then(|line| line.strip_prefix("teams:")) {
+        Some(teams) => {
+            lines.next();
+            Some(parse_teams(teams)?)
+        }
+        None => None,
+    };
+
+    let mut map = Vec::new();
+    let mut units = BTreeMap::new();
+    let mut width = None;
+    let mut height = 0;
+
+    for (row, line) in lines.enumerate() {
+        let tokens = line.split_whitespace().collect::<Vec<&str>>();
+        match width {
+            None => width = Some(tokens.len()),
+            Some(width) if width != tokens.len() => {
+                return Err(format!(
+                    "Row {} has {} tiles but expected {}",
+                    row,
+                    tokens.len(),
+                    width
+                ));
+            }
+            Some(_) => {}
+        }
+
+        for token in tokens {
+            let (tile, unit) = parse_tile(token)?;
+            if let Some(unit) = unit {
+                units.insert(map.len(), unit);
+            }
+            map.push(tile);
+        }
+        height += 1;
+    }
+
+    let player_count = units
+        .values()
+        .map(|unit: &UnitState| unit.player + 1)
+        .chain(teams.iter().flatten().flatten().map(|player| player + 1))
+        .max()
+        .unwrap_or(0);
+
+    let teams = teams.unwrap_or_else(|| {
+        (0..player_count)
+            .map(|player| [player].into_iter().collect())
+            .collect()
+    });
+
+    let countries = [
+        CountryKind::OrangeStar,
+        CountryKind::BlueMoon,
+        CountryKind::GreenEarth,
+        CountryKind::YellowComet,
+        CountryKind::BlackHole,
+    ];
+
+    let game_state = GameState {
+        // Every row was checked to be as wide as the first.
+        map: Grid::new(map, (width.unwrap_or(0), height)).expect("Rows do not fill the map"),
+        units,
+        players: (0..player_count)
+            .map(|player| {
+                PlayerState::new(
+                    countries[player % countries.len()].clone(),
+                    OfficerKind::Andy,
+                    PowerKind::None,
+                )
+            })
+            .collect(),
+        teams,
+        weather: Weather::Clear,
+        settings: GameSettings::fog_league(),
+        owners: BTreeMap::new(),
+        reveals: Vec::new(),
+        ruleset: Ruleset::Awbw,
+        day: 1,
+        active_player: 0,
+    };
+    game_state
+        .validate()
+        .map_err(|problems| problems[0].to_string())?;
+    Ok(game_state)
+}
+
+fn parse_teams(text: &str) -> Result<Vec<HashSet<usize>>, String> {
+    text.split_whitespace()
+        .map(|team| {
+            team.split(',')
+                .map(|player| {
+                    player
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid player `{}` in teams", player))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn parse_tile(token: &str) -> Result<(TileKind, Option<UnitState>), String> {
+    let mut chars = token.chars();
+    let symbol = chars.next().expect("Tokens are never empty");
+    let tile = TileKind::ALL
+        .iter()
+        .find(|tile| tile_symbol(tile) == symbol)
+        .ok_or_else(|| format!("Unknown tile `{}` in `{}`", symbol, token))?
+        .clone();
+
+    let rest = chars.as_str();
+    if rest.is_empty() {
+        return Ok((tile, None));
+    }
+
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let player = rest[..digits]
+        .parse::<usize>()
+        .map_err(|_| format!("Missing player in `{}`", token))?;
+
+    let (code, stealthed) = match rest[digits..].strip_suffix('*') {
+        Some(code) => (code, true),
+        None => (&rest[digits..], false),
+    };
+
+    let kind = UnitKind::ALL
+        .iter()
+        .find(|kind| unit_code(kind) == code)
+        .ok_or_else(|| format!("Unknown unit `{}` in `{}`", code, token))?
+        .clone();
+
+    Ok((tile, Some(UnitState::new(player, stealthed, kind))))
+}
+
+/**
+ * Builds a `Map` from an ASCII string, panicking if it is invalid.
+ *
+ * See `ascii::parse_map` for the format.
+ */
+#[macro_export]
+macro_rules! map {
+    ($text:expr) => {
+        $crate::ascii::parse_map($text).expect("Invalid ASCII map")
+    };
+}
+
+/**
+ * Builds a `GameState` from an ASCII string, panicking if it is invalid.
+ *
+ * See `ascii::parse_game_state` for the format.
+ */
+#[macro_export]
+macro_rules! game_state {
+    ($text:expr) => {
+        $crate::ascii::parse_game_state($text).expect("Invalid ASCII game state")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_round_trip() {
+        for tile in TileKind::ALL {
+            let (parsed, _) = parse_tile(&tile_symbol(tile).to_string()).unwrap();
+            assert_eq!(*tile, parsed);
+        }
+
+        for kind in UnitKind::ALL {
+            let (_, unit) = parse_tile(&format!(".0{}", unit_code(kind))).unwrap();
+            assert_eq!(Some(UnitState::new(0, false, kind.clone())), unit);
+        }
+    }
+
+    #[test]
+    fn parse_game_state_units_and_teams() {
+        let state = crate::game_state!(
+            "
+            teams: 0,2 1
+            .0inf . f
+            ~ ~ ~1sub*
+            . .2rec ^
+            "
+        );
+
+        assert_eq!((3, 3), state.map.dimensions());
+        assert_eq!(TileKind::Forest, state.map[2]);
+        assert_eq!(TileKind::Sea, state.map[3]);
+        assert_eq!(
+            Some(&UnitState::new(1, true, UnitKind::Submarine)),
+            state.units.get(&5)
+        );
+        assert_eq!(3, state.players.len());
+        assert_eq!(Some(0), state.team_of(2));
+        assert_eq!(Some(1), state.team_of(1));
+    }
+
+    #[test]
+    fn parse_game_state_default_teams() {
+        let state = crate::game_state!(".0inf .1inf");
+        assert_eq!(2, state.teams.len());
+        assert_eq!(Some(1), state.team_of(1));
+    }
+
+    #[test]
+    fn parse_map_errors() {
+        assert!(parse_map(". .\n.").is_err());
+        assert!(parse_map(". ?").is_err());
+        assert!(parse_map(".0inf").is_err());
+        assert!(parse_game_state(".inf").is_err());
+        assert!(parse_game_state(".0xyz").is_err());
+        assert_eq!(
+            Map::new(vec![TileKind::Sea, TileKind::Reef], (2, 1)),
+            crate::map!("~ %")
+        );
+    }
+
+    #[test]
+    fn parse_game_state_vision() {
+        let state = crate::game_state!(
+            "
+            .0inf . f1inf
+            "
+        );
+
+        assert!(!state.common_vision().contains(&2));
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::data::UnitData;
+use crate::map::{CountryKind, Map, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::settings::GameSettings;
+use crate::unit::UnitKind;
+use crate::vision::VisionConfig;
+use crate::{GameState, UnitState};
+
+/**
+ * How many days ahead the balance report looks.
+ */
+pub const BALANCE_HORIZON_DAYS: u32 = 20;
+
+/**
+ * How fair a map is to each of its players.
+ *
+ * Tiles do not track an owner yet so each HeadQuarters, in map order, is
+ * taken to be the start of one player and every other property to start
+ * neutral. Players are compared by what an infantry leaving their
+ * HeadQuarters on day 1 could do.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceReport {
+    /** Location of each player's HeadQuarters. */
+    pub headquarters: Vec<usize>,
+    /** For each player how many tiles an infantry on their HeadQuarters
+     * sees. */
+    pub vision: Vec<usize>,
+    /** The smallest entry of `vision` divided by the largest. 1.0 means
+     * every player sees equally much. */
+    pub vision_parity: f64,
+    /** For each day and then each player the income they would have if
+     * they captured every property they reach strictly first. */
+    pub income_curve: Vec<Vec<u32>>,
+    /** Average over every day of the smallest player's income divided by
+     * the largest's. */
+    pub income_parity: f64,
+    /** For each player the days an infantry needs to reach each property
+     * (other than HeadQuarters), closest first. Unreachable properties are
+     * omitted. */
+    pub property_distances: Vec<Vec<u32>>,
+    /** For each player how many chokepoints they reach strictly first. */
+    pub chokepoint_control: Vec<usize>,
+    /** Chokepoints no single player reaches first. */
+    pub contested_chokepoints: usize,
+    /** The earliest day two players' infantry could meet, or None if they
+     * never do within the horizon. */
+    pub first_contact_day: Option<u32>,
+}
+
+impl Map {
+    /**
+     * Summarizes how fair the map is to each player, see `BalanceReport`.
+     */
+    pub fn balance_report(&self) -> BalanceReport {
+        let headquarters = self
+            .tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileKind::HeadQuarters)
+            .map(|(location, _)| location)
+            .collect::<Vec<usize>>();
+
+        let countries = [
+            CountryKind::OrangeStar,
+            CountryKind::BlueMoon,
+            CountryKind::GreenEarth,
+            CountryKind::YellowComet,
+        ];
+
+        let mut state = GameState::from_map(self);
+        for (player, location) in headquarters.iter().enumerate() {
+            state.players.push(PlayerState::new(
+                countries[player % countries.len()].clone(),
+                OfficerKind::Andy,
+                PowerKind::None,
+            ));
+            state
+                .teams
+                .push([player].into_iter().collect::<HashSet<usize>>());
+            state
+                .units
+                .insert(*location, UnitState::new(player, false, UnitKind::Infantry));
+        }
+
+        let config = VisionConfig::default();
+        let vision = (0..headquarters.len())
+            .map(|team| state.visible_to_team_with_config(team, &config).len())
+            .collect::<Vec<usize>>();
+
+        let turns = headquarters
+            .iter()
+            .map(|location| {
+                state.turns_to_reach(
+                    *location,
+                    UnitKind::Infantry.movement_kind(),
+                    UnitKind::Infantry.movement_points(),
+                    BALANCE_HORIZON_DAYS,
+                )
+            })
+            .collect::<Vec<HashMap<usize, u32>>>();
+
+        let properties = self
+            .tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.is_property() && **tile != TileKind::HeadQuarters)
+            .map(|(location, _)| location)
+            .collect::<Vec<usize>>();
+
+        let property_distances = turns
+            .iter()
+            .map(|turns| {
+                let mut days = properties
+                    .iter()
+                    .filter_map(|property| turns.get(property).cloned())
+                    .collect::<Vec<u32>>();
+                days.sort();
+                days
+            })
+            .collect::<Vec<Vec<u32>>>();
+
+        // The day each player would finish capturing each property they
+        // reach strictly first, a capture taking two days on the property.
+        let mut captures = vec![Vec::new(); headquarters.len()];
+        for property in properties.iter() {
+            if let Some((player, turn)) = strictly_first(&turns, *property) {
+                captures[player].push(std::cmp::max(turn, 1) + 1);
+            }
+        }
+
+        let funds_per_property = GameSettings::standard().funds_per_property;
+        let income_curve = (1..=BALANCE_HORIZON_DAYS)
+            .map(|day| {
+                captures
+                    .iter()
+                    .map(|days| {
+                        let owned = 1 + days.iter().filter(|captured| **captured <= day).count();
+                        owned as u32 * funds_per_property
+                    })
+                    .collect::<Vec<u32>>()
+            })
+            .collect::<Vec<Vec<u32>>>();
+
+        let mut chokepoint_control = vec![0; headquarters.len()];
+        let mut contested_chokepoints = 0;
+        for chokepoint in self.chokepoints() {
+            match strictly_first(&turns, chokepoint) {
+                Some((player, _)) => chokepoint_control[player] += 1,
+                None => contested_chokepoints += 1,
+            }
+        }
+
+        let income_parities = income_curve
+            .iter()
+            .map(|incomes| ratio(incomes.iter().map(|income| *income as usize)))
+            .collect::<Vec<f64>>();
+
+        BalanceReport {
+            vision_parity: ratio(vision.iter().cloned()),
+            vision,
+            income_parity: if income_parities.is_empty() {
+                1.0
+            } else {
+                income_parities.iter().sum::<f64>() / income_parities.len() as f64
+            },
+            income_curve,
+            property_distances,
+            chokepoint_control,
+            contested_chokepoints,
+            first_contact_day: state
+                .time_to_contact(BALANCE_HORIZON_DAYS)
+                .first_contact_day,
+            headquarters,
+        }
+    }
+}
+
+/**
+ * Returns the player that reaches `tile` strictly before every other
+ * player and on which turn.
+ */
+fn strictly_first(turns: &[HashMap<usize, u32>], tile: usize) -> Option<(usize, u32)> {
+    let mut arrivals = turns
+        .iter()
+        .enumerate()
+        .filter_map(|(player, turns)| turns.get(&tile).map(|turn| (*turn, player)))
+        .collect::<Vec<(u32, usize)>>();
+    arrivals.sort();
+
+    match (arrivals.first(), arrivals.get(1)) {
+        (Some((first, player)), Some((second, _))) if first < second => Some((*player, *first)),
+        (Some((first, player)), None) => Some((*player, *first)),
+        _ => None,
+    }
+}
+
+/**
+ * Returns the smallest value divided by the largest, or 1.0 if there are
+ * none or the largest is 0.
+ */
+fn ratio(values: impl Iterator<Item = usize>) -> f64 {
+    let values = values.collect::<Vec<usize>>();
+    match (values.iter().min(), values.iter().max()) {
+        (Some(min), Some(max)) if *max > 0 => *min as f64 / *max as f64,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn symmetric_map_is_balanced() {
+        let report = crate::map!("q . c . . . . c . q").balance_report();
+
+        assert_eq!(vec![0, 9], report.headquarters);
+        assert_eq!(1.0, report.vision_parity);
+        assert_eq!(1.0, report.income_parity);
+        assert_eq!(vec![vec![1, 3], vec![1, 3]], report.property_distances);
+        assert_eq!(report.chokepoint_control[0], report.chokepoint_control[1]);
+        assert_eq!(Some(2), report.first_contact_day);
+    }
+
+    #[test]
+    fn lopsided_map_favours_near_player() {
+        let report = crate::map!("q c . . . . . . c q c").balance_report();
+
+        assert_eq!(
+            vec![vec![1, 3, 4], vec![1, 1, 3]],
+            report.property_distances
+        );
+        assert!(report.income_parity < 1.0);
+
+        let last_day = report.income_curve.last().unwrap();
+        assert_eq!(vec![2000, 3000], *last_day);
+    }
+}
@@ -0,0 +1,379 @@
+use core::fmt;
+
+use crate::map::{CountryKind, Map};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::prelude::*;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::teams::Teams;
+use crate::weather::Weather;
+use crate::{GameState, UnitState};
+
+/**
+ * Why a `GameStateBuilder` could not build a state.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum BuildError {
+    /** The number of tiles does not match the (width, height). */
+    DimensionsMismatch {
+        tiles: usize,
+        dimensions: (usize, usize),
+    },
+    /** A unit is not on the map. */
+    UnitOutOfBounds { location: usize },
+    /** Two units were placed on the same tile. */
+    DuplicateUnit { location: usize },
+    /** A unit is owned by a player that was never added. */
+    UnknownPlayer { location: usize, player: usize },
+    /** A tile that is not a property was given an owner. */
+    NotAProperty { location: usize },
+    /** A property is owned by a player that was never added. */
+    UnknownOwner { location: usize, player: usize },
+    /** A team lists a player that was never added. */
+    UnknownTeamMember { team: usize, player: usize },
+    /** A player is on more than one team. */
+    PlayerOnMultipleTeams { player: usize },
+    /** A player is on no team. */
+    NoTeam { player: usize },
+    /** A player owns more than one HQ. */
+    DuplicateHeadQuarters { player: usize },
+    /** A team has nobody on it, so no tile is seen by every team. */
+    EmptyTeam { team: usize },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::DimensionsMismatch { tiles, dimensions } => write!(
+                f,
+                "{} tiles do not fill a {}x{} map",
+                tiles, dimensions.0, dimensions.1
+            ),
+            BuildError::UnitOutOfBounds { location } => {
+                write!(f, "Unit at {} is not on the map", location)
+            }
+            BuildError::DuplicateUnit { location } => {
+                write!(f, "More than one unit at {}", location)
+            }
+            BuildError::UnknownPlayer { location, player } => {
+                write!(
+                    f,
+                    "Unit at {} is owned by unknown player {}",
+                    location, player
+                )
+            }
+            BuildError::NotAProperty { location } => {
+                write!(
+                    f,
+                    "Tile at {} is not a property so can not be owned",
+                    location
+                )
+            }
+            BuildError::UnknownOwner { location, player } => {
+                write!(
+                    f,
+                    "Property at {} is owned by unknown player {}",
+                    location, player
+                )
+            }
+            BuildError::UnknownTeamMember { team, player } => {
+                write!(f, "Team {} lists unknown player {}", team, player)
+            }
+            BuildError::PlayerOnMultipleTeams { player } => {
+                write!(f, "Player {} is on more than one team", player)
+            }
+            BuildError::NoTeam { player } => write!(f, "Player {} is on no team", player),
+            BuildError::DuplicateHeadQuarters { player } => {
+                write!(f, "Player {} owns more than one HQ", player)
+            }
+            BuildError::EmptyTeam { team } => write!(f, "Team {} has no players", team),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+/**
+ * Builds a `GameState`, checking it is consistent before handing it out.
+ *
+ * Players are numbered from 0 in the order they are added.
+ */
+#[derive(Debug, Clone)]
+pub struct GameStateBuilder {
+    map: Map,
+    units: Vec<(usize, UnitState)>,
+    owners: Vec<(usize, usize)>,
+    players: Vec<PlayerState>,
+    teams: Vec<HashSet<usize>>,
+    weather: Weather,
+    settings: GameSettings,
+    ruleset: Ruleset,
+    day: u32,
+    active_player: usize,
+}
+
+impl GameStateBuilder {
+    /**
+     * Starts a state on `map` with no players, teams or units.
+     */
+    pub fn new(map: &Map) -> GameStateBuilder {
+        GameStateBuilder {
+            map: map.clone(),
+            units: Vec::new(),
+            owners: Vec::new(),
+            players: Vec::new(),
+            teams: Vec::new(),
+            weather: Weather::Clear,
+            settings: GameSettings::fog_league(),
+            ruleset: Ruleset::Awbw,
+            day: 1,
+            active_player: 0,
+        }
+    }
+
+    /**
+     * Adds the next player.
+     */
+    pub fn player(self, country: CountryKind, officer: OfficerKind, power: PowerKind) -> Self {
+        self.player_state(PlayerState::new(country, officer, power))
+    }
+
+    /**
+     * Adds the next player along with their power meter.
+     */
+    pub fn player_state(mut self, player: PlayerState) -> Self {
+        self.players.push(player);
+        self
+    }
+
+    /**
+     * Adds a team made up of `players`.
+     */
+    pub fn team(mut self, players: impl IntoIterator<Item = usize>) -> Self {
+        self.teams.push(players.into_iter().collect());
+        self
+    }
+
+    /**
+     * Replaces every team added so far with `teams`.
+     */
+    pub fn teams(mut self, teams: Teams) -> Self {
+        self.teams = teams.into();
+        self
+    }
+
+    /**
+     * Places `unit` at `location`.
+     */
+    pub fn unit(mut self, location: usize, unit: UnitState) -> Self {
+        self.units.push((location, unit));
+        self
+    }
+
+    /**
+     * Hands the property at `location` to `player`.
+     */
+    pub fn owner(mut self, location: usize, player: usize) -> Self {
+        self.owners.push((location, player));
+        self
+    }
+
+    /**
+     * Sets the weather, which is clear unless changed.
+     */
+    pub fn weather(mut self, weather: Weather) -> Self {
+        self.weather = weather;
+        self
+    }
+
+    /**
+     * Sets the match options, which are AWBW's fog league unless changed.
+     */
+    pub fn settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /**
+     * Sets the rules the state follows, which are AWBW's unless changed.
+     */
+    pub fn ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    /**
+     * Sets the day, which is 1 unless changed.
+     */
+    pub fn day(mut self, day: u32) -> Self {
+        self.day = day;
+        self
+    }
+
+    /**
+     * Sets whose turn it is, which is player 0's unless changed.
+     */
+    pub fn active_player(mut self, player: usize) -> Self {
+        self.active_player = player;
+        self
+    }
+
+    /**
+     * Builds the state, failing if the map's tiles do not fill its
+     * dimensions, if two units were placed on the same tile or with the
+     * first problem `GameState::validate` finds.
+     */
+    pub fn build(self) -> Result<GameState, BuildError> {
+        let map = self
+            .map
+            .to_grid()
+            .ok_or_else(|| BuildError::DimensionsMismatch {
+                tiles: self.map.tiles().len(),
+                dimensions: self.map.dimensions(),
+            })?;
+
+        let mut units = BTreeMap::new();
+        for (location, unit) in self.units {
+            if units.insert(location, unit).is_some() {
+                return Err(BuildError::DuplicateUnit { location });
+            }
+        }
+
+        let game_state = GameState {
+            map,
+            units,
+            owners: self.owners.into_iter().collect(),
+            players: self.players,
+            teams: self.teams,
+            weather: self.weather,
+            settings: self.settings,
+            reveals: Vec::new(),
+            ruleset: self.ruleset,
+            day: self.day,
+            active_player: self.active_player,
+        };
+        game_state
+            .validate()
+            .map_err(|mut problems| problems.remove(0))?;
+        Ok(game_state)
+    }
+}
+
+impl GameState {
+    /**
+     * Starts building a state on `map`, see `GameStateBuilder`.
+     */
+    pub fn builder(map: &Map) -> GameStateBuilder {
+        GameStateBuilder::new(map)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::map::TileKind;
+    use crate::unit::UnitKind;
+
+    fn two_players() -> GameStateBuilder {
+        GameState::builder(&Map::new(vec![TileKind::Plain; 6], (3, 2)))
+            .player(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None)
+            .player(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None)
+            .team([0])
+            .team([1])
+    }
+
+    #[test]
+    fn build_valid() {
+        let state = two_players()
+            .unit(0, UnitState::new(0, false, UnitKind::Infantry))
+            .unit(5, UnitState::new(1, false, UnitKind::Recon))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            crate::game_state!(
+                "
+                .0inf . .
+                .     . .1rec
+                "
+            )
+            .common_vision(),
+            state.common_vision()
+        );
+        assert_eq!(2, state.units.len());
+    }
+
+    #[test]
+    fn build_errors() {
+        assert_eq!(
+            Err(BuildError::DimensionsMismatch {
+                tiles: 6,
+                dimensions: (2, 2)
+            }),
+            GameState::builder(&Map::new(vec![TileKind::Plain; 6], (2, 2))).build()
+        );
+        assert_eq!(
+            Err(BuildError::UnitOutOfBounds { location: 6 }),
+            two_players()
+                .unit(6, UnitState::new(0, false, UnitKind::Tank))
+                .build()
+        );
+        assert_eq!(
+            Err(BuildError::DuplicateUnit { location: 1 }),
+            two_players()
+                .unit(1, UnitState::new(0, false, UnitKind::Tank))
+                .unit(1, UnitState::new(1, false, UnitKind::Tank))
+                .build()
+        );
+        assert_eq!(
+            Err(BuildError::UnknownPlayer {
+                location: 1,
+                player: 2
+            }),
+            two_players()
+                .unit(1, UnitState::new(2, false, UnitKind::Tank))
+                .build()
+        );
+        assert_eq!(
+            Err(BuildError::NotAProperty { location: 1 }),
+            two_players().owner(1, 0).build()
+        );
+        assert_eq!(
+            Err(BuildError::UnknownOwner {
+                location: 0,
+                player: 2
+            }),
+            GameState::builder(&Map::new(vec![TileKind::City], (1, 1)))
+                .owner(0, 2)
+                .build()
+        );
+        assert_eq!(
+            Err(BuildError::UnknownTeamMember { team: 2, player: 3 }),
+            two_players().team([3]).build()
+        );
+        assert_eq!(
+            Err(BuildError::PlayerOnMultipleTeams { player: 0 }),
+            two_players().team([0]).build()
+        );
+        assert_eq!(
+            Err(BuildError::EmptyTeam { team: 2 }),
+            two_players().team([]).build()
+        );
+    }
+
+    #[test]
+    fn teams_replace_earlier_teams() {
+        let state = two_players()
+            .teams(Teams::new().team([0, 1]))
+            .build()
+            .unwrap();
+        assert_eq!(Teams::new().team([0, 1]), state.teams());
+
+        assert_eq!(
+            Err(BuildError::NoTeam { player: 1 }),
+            two_players().teams(Teams::ffa(1)).build()
+        );
+    }
+}
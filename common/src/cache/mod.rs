@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::vision::VisionConfig;
+use crate::zobrist::ZobristHash;
+use crate::GameState;
+
+/**
+ * How often a `VisionCache` had the answer already.
+ */
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /** States dropped to stay within the cache's limit. */
+    pub evictions: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    game_state: GameState,
+    common: Option<HashSet<usize>>,
+    teams: BTreeMap<usize, HashSet<usize>>,
+}
+
+/**
+ * Remembers the common and per team vision of states already seen, keyed
+ * by their `ZobristHash`, for replay analysers that keep coming back to
+ * the same states through undo or what-ifs.
+ *
+ * Each state is stored alongside its results and compared on lookup, so
+ * states that differ only in what the hash leaves out, such as teams or
+ * weather, never share results. Every result is computed under the
+ * cache's `VisionConfig`.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct VisionCache {
+    config: VisionConfig,
+    entries: HashMap<ZobristHash, Vec<Entry>>,
+    /** Hash of every entry, oldest first. */
+    order: VecDeque<ZobristHash>,
+    /** The most states kept at once, None for no limit. */
+    max_entries: Option<usize>,
+    stats: CacheStats,
+}
+
+impl VisionCache {
+    pub fn new(config: VisionConfig) -> VisionCache {
+        VisionCache {
+            config,
+            ..VisionCache::default()
+        }
+    }
+
+    /**
+     * Keeps at most `max_entries` states, dropping the oldest first. The
+     * state being looked up is always kept so the limit is at least 1.
+     */
+    pub fn with_max_entries(mut self, max_entries: usize) -> VisionCache {
+        self.max_entries = Some(max_entries);
+        self.evict();
+        self
+    }
+
+    pub fn config(&self) -> &VisionConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /**
+     * Returns how many states are stored.
+     */
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /**
+     * Forgets every state, keeping the stats.
+     */
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /**
+     * Same as `GameState::common_vision_with_config`, computed at most once
+     * per state.
+     */
+    pub fn common_vision(&mut self, game_state: &GameState) -> HashSet<usize> {
+        self.common_vision_hashed(game_state, game_state.zobrist_hash())
+    }
+
+    /**
+     * Same as `common_vision` with `hash` already known to be
+     * `game_state`'s, e.g. kept up to date by `GameState::apply_hashed`.
+     */
+    pub fn common_vision_hashed(
+        &mut self,
+        game_state: &GameState,
+        hash: ZobristHash,
+    ) -> HashSet<usize> {
+        if let Some(common) = self.entry(game_state, hash).common.clone() {
+            self.stats.hits += 1;
+            return common;
+        }
+
+        let common = game_state.common_vision_with_config(&self.config);
+        self.entry(game_state, hash).common = Some(common.clone());
+        self.stats.misses += 1;
+        common
+    }
+
+    /**
+     * Same as `GameState::visible_to_team_with_config`, computed at most
+     * once per state and team.
+     */
+    pub fn team_vision(&mut self, game_state: &GameState, team: usize) -> HashSet<usize> {
+        self.team_vision_hashed(game_state, game_state.zobrist_hash(), team)
+    }
+
+    /**
+     * Same as `team_vision` with `hash` already known to be `game_state`'s.
+     */
+    pub fn team_vision_hashed(
+        &mut self,
+        game_state: &GameState,
+        hash: ZobristHash,
+        team: usize,
+    ) -> HashSet<usize> {
+        if let Some(vision) = self.entry(game_state, hash).teams.get(&team).cloned() {
+            self.stats.hits += 1;
+            return vision;
+        }
+
+        let vision = game_state.visible_to_team_with_config(team, &self.config);
+        self.entry(game_state, hash)
+            .teams
+            .insert(team, vision.clone());
+        self.stats.misses += 1;
+        vision
+    }
+
+    /**
+     * Returns the entry for `game_state`, adding an empty one if it has not
+     * been seen.
+     */
+    fn entry(&mut self, game_state: &GameState, hash: ZobristHash) -> &mut Entry {
+        let known = self
+            .entries
+            .get(&hash)
+            .map(|bucket| bucket.iter().any(|entry| entry.game_state == *game_state))
+            .unwrap_or(false);
+
+        if !known {
+            self.entries.entry(hash).or_default().push(Entry {
+                game_state: game_state.clone(),
+                common: None,
+                teams: BTreeMap::new(),
+            });
+            self.order.push_back(hash);
+            self.evict();
+        }
+
+        self.entries
+            .get_mut(&hash)
+            .and_then(|bucket| {
+                bucket
+                    .iter_mut()
+                    .find(|entry| entry.game_state == *game_state)
+            })
+            .expect("entry was just added")
+    }
+
+    fn evict(&mut self) {
+        while self
+            .max_entries
+            .is_some_and(|max| self.order.len() > max.max(1))
+        {
+            let Some(hash) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(bucket) = self.entries.get_mut(&hash) {
+                bucket.remove(0);
+                if bucket.is_empty() {
+                    self.entries.remove(&hash);
+                }
+            }
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::action::Action;
+    use crate::game_state;
+
+    #[test]
+    fn repeated_states_hit() {
+        let game_state = game_state!(".0inf f . .1inf\n. . . .");
+        let mut cache = VisionCache::default();
+
+        let common = cache.common_vision(&game_state);
+        assert_eq!(game_state.common_vision(), common);
+        assert_eq!(common, cache.common_vision(&game_state.clone()));
+        assert_eq!(
+            game_state.visible_to_team(1),
+            cache.team_vision(&game_state, 1)
+        );
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                evictions: 0
+            },
+            cache.stats()
+        );
+        assert_eq!(1, cache.len());
+    }
+
+    #[test]
+    fn states_outside_the_hash_do_not_share() {
+        let game_state = game_state!(".0inf . . .1inf\n. . . .");
+        let mut fogless = game_state.clone();
+        fogless.settings.fog = false;
+        assert_eq!(game_state.zobrist_hash(), fogless.zobrist_hash());
+
+        let mut cache = VisionCache::default();
+        assert_eq!(game_state.common_vision(), cache.common_vision(&game_state));
+        assert_eq!(fogless.common_vision(), cache.common_vision(&fogless));
+        assert_eq!(2, cache.len());
+        assert_eq!(0, cache.stats().hits);
+    }
+
+    #[test]
+    fn undo_with_hashed_lookups() {
+        let start = game_state!(".0inf . . .1inf\n. . . .");
+        let mut game_state = start.clone();
+        let mut hash = game_state.zobrist_hash();
+        let mut cache = VisionCache::default();
+
+        cache.common_vision_hashed(&game_state, hash);
+        game_state
+            .apply_hashed(&Action::Move { from: 0, to: 1 }, &mut hash)
+            .unwrap();
+        cache.common_vision_hashed(&game_state, hash);
+        game_state
+            .apply_hashed(&Action::Move { from: 1, to: 0 }, &mut hash)
+            .unwrap();
+
+        assert_eq!(
+            start.common_vision(),
+            cache.common_vision_hashed(&game_state, hash)
+        );
+        assert_eq!(1, cache.stats().hits);
+    }
+
+    #[test]
+    fn oldest_states_are_evicted() {
+        let states = [
+            game_state!(".0inf . .1inf"),
+            game_state!(". .0inf .1inf"),
+            game_state!(".0inf .1inf ."),
+        ];
+        let mut cache = VisionCache::default().with_max_entries(2);
+        for game_state in states.iter() {
+            cache.common_vision(game_state);
+        }
+        assert_eq!(2, cache.len());
+        assert_eq!(1, cache.stats().evictions);
+
+        cache.common_vision(&states[2]);
+        cache.common_vision(&states[0]);
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 4,
+                evictions: 2
+            },
+            cache.stats()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/**
+ * Lets a caller stop a long analysis early, either by calling `cancel` from
+ * another thread or once a deadline passes.
+ *
+ * Clones share the same flag so one clone can be handed to the analysis and
+ * another kept to cancel it.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /**
+     * Returns a token that is only cancelled by calling `cancel`.
+     */
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /**
+     * Returns a token that is also cancelled once `deadline` has passed.
+     */
+    pub fn with_deadline(deadline: Instant) -> CancelToken {
+        CancelToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /**
+     * Returns a token that is also cancelled once `timeout` has elapsed
+     * from now.
+     */
+    pub fn with_timeout(timeout: Duration) -> CancelToken {
+        CancelToken::with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self
+                .deadline
+                .map(|deadline| Instant::now() >= deadline)
+                .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_is_shared() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn deadline_passes() {
+        assert!(CancelToken::with_deadline(Instant::now()).is_cancelled());
+        assert!(!CancelToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    }
+}
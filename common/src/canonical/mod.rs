@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::builder::BuildError;
+use crate::grid::Grid;
+use crate::map::{CountryKind, TileKind};
+use crate::player::PlayerState;
+use crate::reveal::Reveal;
+use crate::{GameState, UnitState};
+
+/**
+ * One of the eight ways a rectangle can be rotated / reflected onto itself.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Transform {
+    transpose: bool,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl Transform {
+    fn all() -> Vec<Transform> {
+        let mut transforms = Vec::with_capacity(8);
+        for transpose in [false, true] {
+            for flip_x in [false, true] {
+                for flip_y in [false, true] {
+                    transforms.push(Transform {
+                        transpose,
+                        flip_x,
+                        flip_y,
+                    });
+                }
+            }
+        }
+        transforms
+    }
+
+    fn dimensions(&self, (width, height): (usize, usize)) -> (usize, usize) {
+        if self.transpose {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn apply(&self, location: usize, (width, height): (usize, usize)) -> usize {
+        let (mut x, mut y) = (location % width, location / width);
+        let (mut new_width, mut new_height) = (width, height);
+
+        if self.transpose {
+            std::mem::swap(&mut x, &mut y);
+            std::mem::swap(&mut new_width, &mut new_height);
+        }
+        if self.flip_x {
+            x = new_width - 1 - x;
+        }
+        if self.flip_y {
+            y = new_height - 1 - y;
+        }
+
+        y * new_width + x
+    }
+}
+
+impl GameState {
+    /**
+     * Returns a value that orders states so the smallest one can be picked
+     * as the canonical form.
+     */
+    fn canonical_key(&self) -> Vec<usize> {
+        let mut key = vec![self.map.dimensions().0, self.map.dimensions().1];
+        key.extend(self.map.cells().iter().map(|tile| tile.clone() as usize));
+        for (location, unit) in self.units.iter() {
+            key.extend([
+                *location,
+                unit.player,
+                unit.stealthed as usize,
+                unit.kind.clone() as usize,
+                unit.hp as usize,
+                unit.fuel() as usize,
+                unit.ammo() as usize,
+                unit.cargo.len(),
+            ]);
+            for cargo in unit.cargo.iter() {
+                key.extend([
+                    cargo.player,
+                    cargo.kind.clone() as usize,
+                    cargo.hp as usize,
+                    cargo.fuel() as usize,
+                    cargo.ammo() as usize,
+                ]);
+            }
+        }
+        for (location, owner) in self.owners.iter() {
+            key.extend([*location, *owner]);
+        }
+        for reveal in self.reveals.iter() {
+            key.extend([
+                reveal.player.map_or(0, |player| player + 1),
+                reveal.center,
+                reveal.radius,
+            ]);
+        }
+        key.push(self.active_player);
+        for player in self.players.iter() {
+            key.extend([
+                player.country.clone() as usize,
+                player.officer.clone() as usize,
+                player.power.clone() as usize,
+                player.charge as usize,
+                player.powers_used as usize,
+            ]);
+        }
+        for team in self.teams.iter() {
+            let mut players = team.iter().cloned().collect::<Vec<usize>>();
+            players.sort();
+            key.push(players.len());
+            key.extend(players);
+        }
+        key
+    }
+
+    /**
+     * Moves every tile, unit, owned property and revealed area according to
+     * `transform` then renumbers the players in the order their units
+     * appear.
+     *
+     * The state must have no `structural_problems`.
+     */
+    fn transformed(&self, transform: Transform) -> GameState {
+        let dimensions = transform.dimensions(self.map.dimensions());
+
+        let mut map = Grid::filled(TileKind::Void, dimensions);
+        for (location, tile) in self.map.cells().iter().enumerate() {
+            map[transform.apply(location, self.map.dimensions())] = tile.clone();
+        }
+
+        let units = self
+            .units
+            .iter()
+            .map(|(location, unit)| {
+                (
+                    transform.apply(*location, self.map.dimensions()),
+                    unit.clone(),
+                )
+            })
+            .collect::<BTreeMap<usize, UnitState>>();
+
+        // Players are ordered by their first unit, those without units by
+        // the properties they own, then their officer and power.
+        let mut order = Vec::with_capacity(self.players.len());
+        for unit in units.values() {
+            if !order.contains(&unit.player) {
+                order.push(unit.player);
+            }
+        }
+        let mut without_units = (0..self.players.len())
+            .filter(|player| !order.contains(player))
+            .collect::<Vec<usize>>();
+        without_units.sort_by_key(|player| {
+            let mut properties = self
+                .owners
+                .iter()
+                .filter(|(_, owner)| *owner == player)
+                .map(|(location, _)| transform.apply(*location, self.map.dimensions()))
+                .collect::<Vec<usize>>();
+            properties.sort();
+            let state = &self.players[*player];
+            (
+                properties,
+                state.officer.clone() as usize,
+                state.power.clone() as usize,
+            )
+        });
+        order.extend(without_units);
+        let renumber = order
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old, new))
+            .collect::<HashMap<usize, usize>>();
+
+        // Colours are cosmetic so they are handed out in a fixed order.
+        let mut countries = self
+            .players
+            .iter()
+            .map(|player| player.country.clone())
+            .collect::<Vec<CountryKind>>();
+        countries.sort_by_key(|country| country.clone() as usize);
+
+        let players = order
+            .iter()
+            .zip(countries)
+            .filter_map(|(old, country)| {
+                self.players.get(*old).map(|player| PlayerState {
+                    country,
+                    ..player.clone()
+                })
+            })
+            .collect();
+
+        let units = units
+            .into_iter()
+            .map(|(location, mut unit)| {
+                unit.player = renumber.get(&unit.player).cloned().unwrap_or(unit.player);
+                for cargo in unit.cargo.iter_mut() {
+                    cargo.player = renumber.get(&cargo.player).cloned().unwrap_or(cargo.player);
+                }
+                (location, unit)
+            })
+            .collect();
+
+        let owners = self
+            .owners
+            .iter()
+            .map(|(location, owner)| {
+                (
+                    transform.apply(*location, self.map.dimensions()),
+                    renumber.get(owner).cloned().unwrap_or(*owner),
+                )
+            })
+            .collect();
+
+        let reveals = self
+            .reveals
+            .iter()
+            .map(|reveal| Reveal {
+                player: reveal
+                    .player
+                    .map(|player| renumber.get(&player).cloned().unwrap_or(player)),
+                center: transform.apply(reveal.center, self.map.dimensions()),
+                radius: reveal.radius,
+            })
+            .collect();
+
+        let mut teams = self
+            .teams
+            .iter()
+            .map(|team| {
+                team.iter()
+                    .map(|player| renumber.get(player).cloned().unwrap_or(*player))
+                    .collect::<HashSet<usize>>()
+            })
+            .collect::<Vec<HashSet<usize>>>();
+        teams.sort_by_key(|team| {
+            let mut players = team.iter().cloned().collect::<Vec<usize>>();
+            players.sort();
+            players
+        });
+
+        GameState {
+            map,
+            units,
+            players,
+            teams,
+            weather: self.weather.clone(),
+            settings: self.settings.clone(),
+            owners,
+            reveals,
+            ruleset: self.ruleset.clone(),
+            day: self.day,
+            active_player: renumber
+                .get(&self.active_player)
+                .cloned()
+                .unwrap_or(self.active_player),
+        }
+    }
+
+    /**
+     * Returns the canonical form of the state so that states which only
+     * differ by player numbering, team ordering, colours or by being
+     * rotated / mirrored compare (and hash) equal.
+     *
+     * Returns the `structural_problems` of the state instead if it has any,
+     * since e.g. a unit of a player that does not exist cannot be
+     * renumbered.
+     */
+    pub fn canonicalize(&self) -> Result<GameState, Vec<BuildError>> {
+        let problems = self.structural_problems();
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        Ok(Transform::all()
+            .into_iter()
+            .map(|transform| self.transformed(transform))
+            .min_by_key(|state| state.canonical_key())
+            .expect("There is always at least one transform"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::BuildError;
+    use crate::game_state;
+    use crate::officer::{OfficerKind, PowerKind};
+
+    #[test]
+    fn canonicalize_mirrored_colour_swap() {
+        let mut game_state = game_state!("f0inf . .1tank");
+        game_state.players[1].officer = OfficerKind::Olaf;
+
+        let mut mirrored = game_state!("teams: 1 0\n.0tank\n.\nf1inf");
+        mirrored.players[0].officer = OfficerKind::Olaf;
+        mirrored.active_player = 1;
+
+        assert_eq!(game_state.canonicalize(), mirrored.canonicalize());
+        assert_eq!(
+            game_state.canonicalize(),
+            game_state.canonicalize().unwrap().canonicalize()
+        );
+    }
+
+    #[test]
+    fn canonicalize_players_without_units() {
+        let mut game_state = game_state!("teams: 0 1 2\nc0inf c c");
+        game_state.set_owner(2, Some(1));
+
+        let mut renumbered = game_state.clone();
+        renumbered.owners.clear();
+        renumbered.set_owner(2, Some(2));
+        assert_eq!(game_state.canonicalize(), renumbered.canonicalize());
+
+        game_state.owners.clear();
+        game_state.players[1].officer = OfficerKind::Olaf;
+        let mut renumbered = game_state.clone();
+        renumbered.players.swap(1, 2);
+        assert_eq!(game_state.canonicalize(), renumbered.canonicalize());
+
+        game_state.players[1].officer = OfficerKind::Andy;
+        game_state.players[1].power = PowerKind::Normal;
+        let mut renumbered = game_state.clone();
+        renumbered.players.swap(1, 2);
+        assert_eq!(game_state.canonicalize(), renumbered.canonicalize());
+    }
+
+    #[test]
+    fn canonicalize_rejects_invalid_states() {
+        let mut game_state = game_state!(".0inf . .1inf");
+        // Player 2 does not exist.
+        game_state.units.get_mut(&0).unwrap().player = 2;
+        assert_eq!(
+            Err(vec![BuildError::UnknownPlayer {
+                location: 0,
+                player: 2
+            }]),
+            game_state.canonicalize()
+        );
+    }
+}
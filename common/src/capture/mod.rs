@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use crate::data::UnitData;
+use crate::GameState;
+
+/**
+ * The result of racing for a single property.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CaptureRace {
+    /** Location of the property. */
+    pub property: usize,
+    /** For each team the earliest day (starting from 1) it could complete
+     * the capture, or None if it cannot within the horizon. */
+    pub capture_days: Vec<Option<u32>>,
+    /** The team that captures first, or None if teams are tied. */
+    pub winner: Option<usize>,
+    /** True if a unit from another team could attack the property before
+     * the earliest capture completes. */
+    pub interruptible: bool,
+}
+
+impl GameState {
+    /**
+     * Returns the earliest day the capturing unit at `location` could
+     * complete a capture of `property`, either by walking or by boarding an
+     * allied transport on the first day.
+     *
+     * A full HP unit needs two days on the property to capture it.
+     */
+    fn earliest_capture_day(&self, location: usize, property: usize, max_days: u32) -> Option<u32> {
+        let unit = self.units.get(&location)?;
+        let walking = self.turns_to_reach(
+            location,
+            unit.kind.movement_kind(),
+            unit.kind.movement_points(),
+            max_days,
+        );
+
+        let mut best = walking
+            .get(&property)
+            .map(|turn| std::cmp::max(*turn, 1) + 1);
+
+        let allies = self.allies_of(unit.player);
+        for (transport_location, transport) in self.units.iter() {
+            if !transport.kind.can_carry(&unit.kind) || !allies.contains(&transport.player) {
+                continue;
+            }
+
+            if walking
+                .get(transport_location)
+                .map(|turn| *turn > 1)
+                .unwrap_or(true)
+            {
+                // Can not board this transport on the first day.
+                continue;
+            }
+
+            let driving = self.turns_to_reach(
+                *transport_location,
+                transport.kind.movement_kind(),
+                transport.kind.movement_points(),
+                max_days,
+            );
+
+            // The capture starts the day after the transport drops the unit.
+            let transported = self
+                .neighbors(property, 1)
+                .into_iter()
+                .filter(|tile| *tile != property)
+                .filter_map(|tile| driving.get(&tile))
+                .map(|turn| std::cmp::max(*turn, 1) + 2)
+                .min();
+
+            best = match (best, transported) {
+                (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        best.filter(|day| *day <= max_days)
+    }
+
+    /**
+     * For each property that more than one team could capture within
+     * `max_days` works out who gets there first and whether that capture
+     * could be interrupted.
+     *
+     * HeadQuarters are never raced for and a team does not race for a
+     * property it already owns.
+     */
+    pub fn capture_races(&self, max_days: u32) -> Vec<CaptureRace> {
+        let player_to_team_map = self
+            .teams
+            .iter()
+            .enumerate()
+            .flat_map(|(team, players)| players.iter().map(move |player| (*player, team)))
+            .collect::<HashMap<usize, usize>>();
+
+        let mut races = Vec::new();
+        for (property, tile) in self.map.cells().iter().enumerate() {
+            if !tile.is_property() || *tile == crate::map::TileKind::HeadQuarters {
+                continue;
+            }
+
+            let owning_team = self
+                .owner_of(property)
+                .and_then(|owner| self.team_of(owner));
+
+            let mut capture_days = vec![None; self.teams.len()];
+            for (location, unit) in self.units.iter() {
+                if !unit.kind.can_capture() {
+                    continue;
+                }
+
+                let Some(team) = player_to_team_map.get(&unit.player) else {
+                    continue;
+                };
+                if owning_team == Some(*team) {
+                    continue;
+                }
+
+                let Some(day) = self.earliest_capture_day(*location, property, max_days) else {
+                    continue;
+                };
+
+                capture_days[*team] = match capture_days[*team] {
+                    Some(best) if best <= day => Some(best),
+                    _ => Some(day),
+                };
+            }
+
+            if capture_days.iter().filter(|day| day.is_some()).count() < 2 {
+                continue;
+            }
+
+            let Some(earliest) = capture_days.iter().flatten().min().cloned() else {
+                continue;
+            };
+
+            let earliest_teams = capture_days
+                .iter()
+                .enumerate()
+                .filter(|(_, day)| **day == Some(earliest))
+                .map(|(team, _)| team)
+                .collect::<Vec<usize>>();
+
+            let winner = match earliest_teams.as_slice() {
+                [team] => Some(*team),
+                _ => None,
+            };
+
+            let interruptible = earliest_teams.iter().any(|team| {
+                self.units.iter().any(|(location, unit)| {
+                    player_to_team_map.get(&unit.player) != Some(team)
+                        && self
+                            .earliest_attack_day(*location, property, earliest)
+                            .is_some()
+                })
+            });
+
+            races.push(CaptureRace {
+                property,
+                capture_days,
+                winner,
+                interruptible,
+            });
+        }
+
+        races
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state;
+
+    use super::CaptureRace;
+
+    #[test]
+    fn capture_race_closest_wins() {
+        let game_state = game_state!(". . . .0inf . . c . . . . .1inf");
+
+        let races = game_state.capture_races(10);
+        assert_eq!(1, races.len());
+        assert_eq!(vec![Some(2), Some(3)], races[0].capture_days);
+        assert_eq!(Some(0), races[0].winner);
+        assert!(races[0].interruptible);
+    }
+
+    #[test]
+    fn capture_race_transport() {
+        let game_state = game_state!(".0mech .0apc . . . . c . . . . .1inf");
+
+        let races = game_state.capture_races(10);
+        assert_eq!(1, races.len());
+        // Walking the mech would take until day 4.
+        assert_eq!(vec![Some(3), Some(3)], races[0].capture_days);
+        assert_eq!(None, races[0].winner);
+    }
+
+    #[test]
+    fn capture_race_skips_owner() {
+        let mut game_state = game_state!(". . . .0inf . . c . . . . .1inf");
+        game_state.set_owner(6, Some(0));
+
+        assert_eq!(Vec::<CaptureRace>::new(), game_state.capture_races(10));
+    }
+}
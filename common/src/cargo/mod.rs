@@ -0,0 +1,132 @@
+use crate::data::{TileData, UnitData};
+use crate::GameState;
+
+impl GameState {
+    /**
+     * Moves the unit at `location` into the transport at `transport`.
+     *
+     * Returns false and changes nothing if either unit is missing, they
+     * have different owners or the transport can not carry the unit or is
+     * full.
+     */
+    pub fn load(&mut self, location: usize, transport: usize) -> bool {
+        if location == transport {
+            return false;
+        }
+
+        let (Some(unit), Some(carrier)) = (self.units.get(&location), self.units.get(&transport))
+        else {
+            return false;
+        };
+
+        if unit.player != carrier.player
+            || !carrier.kind.can_carry(&unit.kind)
+            || carrier.cargo.len() >= carrier.kind.cargo_capacity()
+        {
+            return false;
+        }
+
+        let unit = self
+            .units
+            .remove(&location)
+            .expect("Unit was not at its own location");
+        self.units
+            .get_mut(&transport)
+            .expect("Transport was not at its own location")
+            .cargo
+            .push(unit);
+        true
+    }
+
+    /**
+     * Drops the `index`th unit carried by the transport at `transport` onto
+     * the empty neighbouring tile `destination`.
+     *
+     * Returns false and changes nothing if there is no such unit, the
+     * destination is not next to the transport, is occupied or the unit
+     * could not move onto it.
+     */
+    pub fn unload(&mut self, transport: usize, index: usize, destination: usize) -> bool {
+        let Some(carrier) = self.units.get(&transport) else {
+            return false;
+        };
+        let Some(unit) = carrier.cargo.get(index) else {
+            return false;
+        };
+
+        if destination == transport
+            || self.units.contains_key(&destination)
+            || !self
+                .checked_neighbors(transport, 1)
+                .map(|tiles| tiles.contains(&destination))
+                .unwrap_or(false)
+            || self.map[destination]
+                .movement_cost(unit.kind.movement_kind())
+                .is_none()
+        {
+            return false;
+        }
+
+        let unit = self
+            .units
+            .get_mut(&transport)
+            .expect("Transport was not at its own location")
+            .cargo
+            .remove(index);
+        self.units.insert(destination, unit);
+        true
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::game_state;
+    use crate::unit::UnitKind;
+    use crate::UnitState;
+
+    #[test]
+    fn load_and_unload() {
+        let mut game_state = game_state!(
+            "
+            .0inf .0apc .0tank
+            .1inf ~     .
+            "
+        );
+
+        // Wrong owner, can not carry tanks and nothing to load.
+        assert!(!game_state.load(3, 1));
+        assert!(!game_state.load(2, 1));
+        assert!(!game_state.load(4, 1));
+
+        assert!(game_state.load(0, 1));
+        assert_eq!(None, game_state.unit(0));
+        assert_eq!(
+            &[UnitState::new(0, false, UnitKind::Infantry)],
+            game_state.unit(1).unwrap().cargo()
+        );
+        // Full.
+        assert!(!game_state.load(3, 1));
+
+        // Not adjacent, occupied, impassable and no such cargo.
+        assert!(!game_state.unload(1, 0, 5));
+        assert!(!game_state.unload(1, 0, 2));
+        assert!(!game_state.unload(1, 0, 4));
+        assert!(!game_state.unload(1, 1, 0));
+
+        assert!(game_state.unload(1, 0, 0));
+        assert!(game_state.unit(1).unwrap().cargo().is_empty());
+        assert_eq!(
+            Some(&UnitState::new(0, false, UnitKind::Infantry)),
+            game_state.unit(0)
+        );
+    }
+
+    #[test]
+    fn cargo_is_not_seen() {
+        let mut game_state = game_state!(".0inf .0apc . . . .1inf");
+        let before = game_state.visible_to_team(0);
+
+        assert!(game_state.load(0, 1));
+        assert_eq!(before, game_state.visible_to_team(0));
+    }
+}
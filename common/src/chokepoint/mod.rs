@@ -0,0 +1,131 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::data::TileData;
+use crate::map::Map;
+use crate::unit::MovementKind;
+use crate::GameState;
+
+impl Map {
+    /**
+     * Returns every tile that, if blocked, would cut off one part of the
+     * land from another for tanks (treads). Tiles are in ascending order.
+     */
+    pub fn chokepoints(&self) -> Vec<usize> {
+        GameState::from_map(self).articulation_points(MovementKind::Treads)
+    }
+}
+
+impl GameState {
+    /**
+     * Finds the articulation points of the graph of tiles a unit moving in
+     * the given way can enter.
+     */
+    fn articulation_points(&self, movement: MovementKind) -> Vec<usize> {
+        let passable = |tile: usize| {
+            self.map
+                .cells()
+                .get(tile)
+                .map(|kind| kind.movement_cost(movement).is_some())
+                .unwrap_or(false)
+        };
+
+        let adjacent = |tile: usize| {
+            let mut tiles = self
+                .movement_neighbors(tile)
+                .into_iter()
+                .filter(|neighbor| passable(*neighbor))
+                .collect::<Vec<usize>>();
+            tiles.sort();
+            tiles
+        };
+
+        let mut discovered: HashMap<usize, usize> = HashMap::new();
+        let mut low: HashMap<usize, usize> = HashMap::new();
+        let mut points = BTreeSet::new();
+
+        for root in 0..self.map.len() {
+            if !passable(root) || discovered.contains_key(&root) {
+                continue;
+            }
+
+            discovered.insert(root, discovered.len());
+            low.insert(root, discovered[&root]);
+            let mut root_children = 0;
+
+            // (tile, parent, neighbors, next neighbor index)
+            let mut stack = vec![(root, None, adjacent(root), 0)];
+
+            while let Some((tile, parent, neighbors, index)) = stack.last_mut() {
+                let tile = *tile;
+                let parent = *parent;
+
+                if *index < neighbors.len() {
+                    let neighbor = neighbors[*index];
+                    *index += 1;
+
+                    if let Some(order) = discovered.get(&neighbor) {
+                        if Some(neighbor) != parent {
+                            let order = *order;
+                            let tile_low = low.get_mut(&tile).expect("Tile was not discovered");
+                            *tile_low = std::cmp::min(*tile_low, order);
+                        }
+                        continue;
+                    }
+
+                    discovered.insert(neighbor, discovered.len());
+                    low.insert(neighbor, discovered[&neighbor]);
+                    if tile == root {
+                        root_children += 1;
+                    }
+                    stack.push((neighbor, Some(tile), adjacent(neighbor), 0));
+                    continue;
+                }
+
+                stack.pop();
+                let Some(parent) = parent else {
+                    continue;
+                };
+
+                let tile_low = low[&tile];
+                let parent_low = low.get_mut(&parent).expect("Parent was not discovered");
+                *parent_low = std::cmp::min(*parent_low, tile_low);
+
+                if parent != root && tile_low >= discovered[&parent] {
+                    points.insert(parent);
+                }
+            }
+
+            if root_children > 1 {
+                points.insert(root);
+            }
+        }
+
+        points.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::{Map, TileKind};
+
+    #[test]
+    fn chokepoints_bridge() {
+        // . . ~ . .
+        // . . = . .
+        // . . ~ . .
+        let mut tiles = vec![TileKind::Plain; 15];
+        tiles[2] = TileKind::Sea;
+        tiles[7] = TileKind::Bridge;
+        tiles[12] = TileKind::Sea;
+
+        assert_eq!(vec![6, 7, 8], Map::new(tiles, (5, 3)).chokepoints());
+    }
+
+    #[test]
+    fn chokepoints_open_field() {
+        assert_eq!(
+            Vec::<usize>::new(),
+            Map::new(vec![TileKind::Plain; 9], (3, 3)).chokepoints()
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::data::UnitData;
+use crate::GameState;
+
+/**
+ * When each team's forces could first reach every tile.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ContactMap {
+    /** For each tile and then each team the earliest day a unit of that
+     * team could stand on the tile. Day 0 is where units currently are. */
+    pub arrival_days: Vec<Vec<Option<u32>>>,
+    /** Tiles where two teams' areas of control meet in ascending order. A
+     * team controls a tile if it can reach it strictly before every other
+     * team. */
+    pub contact_line: Vec<usize>,
+    /** The earliest day a second team could reach any tile on the contact
+     * line, or None if teams never meet. */
+    pub first_contact_day: Option<u32>,
+}
+
+impl ContactMap {
+    /**
+     * Returns the team that reaches `tile` strictly first, if any.
+     */
+    pub fn controller(&self, tile: usize) -> Option<usize> {
+        let days = self.arrival_days.get(tile)?;
+        let earliest = days.iter().flatten().min()?;
+
+        let mut teams = days
+            .iter()
+            .enumerate()
+            .filter(|(_, day)| **day == Some(*earliest));
+
+        match (teams.next(), teams.next()) {
+            (Some((team, _)), None) => Some(team),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the day a second team could reach `tile`, if any.
+     */
+    fn second_arrival(&self, tile: usize) -> Option<u32> {
+        let mut days = self.arrival_days[tile]
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<u32>>();
+        days.sort();
+        days.get(1).cloned()
+    }
+}
+
+impl GameState {
+    /**
+     * Works out how soon each team could reach every tile, looking at most
+     * `max_days` ahead, and where the teams would first meet.
+     */
+    pub fn time_to_contact(&self, max_days: u32) -> ContactMap {
+        let mut arrival_days = vec![vec![None; self.teams.len()]; self.map.len()];
+
+        for (location, unit) in self.units.iter() {
+            let Some(team) = self.team_of(unit.player) else {
+                continue;
+            };
+
+            let turns: HashMap<usize, u32> = self.turns_to_reach(
+                *location,
+                unit.kind.movement_kind(),
+                unit.kind.movement_points(),
+                max_days,
+            );
+
+            for (tile, day) in turns {
+                let Some(days) = arrival_days.get_mut(tile) else {
+                    continue;
+                };
+
+                days[team] = match days[team] {
+                    Some(best) if best <= day => Some(best),
+                    _ => Some(day),
+                };
+            }
+        }
+
+        let mut contact_map = ContactMap {
+            arrival_days,
+            contact_line: Vec::new(),
+            first_contact_day: None,
+        };
+
+        for tile in 0..self.map.len() {
+            let Some(second) = contact_map.second_arrival(tile) else {
+                continue;
+            };
+
+            let on_line = match contact_map.controller(tile) {
+                // Tied between teams.
+                None => true,
+                Some(team) => self.neighbors(tile, 1).into_iter().any(|neighbor| {
+                    contact_map
+                        .controller(neighbor)
+                        .map(|other| other != team)
+                        .unwrap_or(false)
+                }),
+            };
+
+            if on_line {
+                contact_map.contact_line.push(tile);
+                contact_map.first_contact_day = Some(
+                    contact_map
+                        .first_contact_day
+                        .map_or(second, |day| std::cmp::min(day, second)),
+                );
+            }
+        }
+
+        contact_map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn time_to_contact_line() {
+        let game_state = crate::game_state!(".0inf . . . . . . . . . . .1inf");
+
+        let contact_map = game_state.time_to_contact(10);
+
+        assert_eq!(vec![Some(0), Some(4)], contact_map.arrival_days[0]);
+        assert_eq!(vec![Some(2), Some(2)], contact_map.arrival_days[5]);
+        assert_eq!(Some(0), contact_map.controller(3));
+        assert_eq!(None, contact_map.controller(5));
+        assert_eq!(vec![5, 6], contact_map.contact_line);
+        assert_eq!(Some(2), contact_map.first_contact_day);
+    }
+}
@@ -0,0 +1,407 @@
+use core::cmp::Ordering;
+
+use crate::map::TileKind;
+use crate::prelude::*;
+use crate::{GameState, UnitState};
+
+/**
+ * A tile on the map: `x` is the column and `y` is the row, both counted
+ * from 0 at the top left.
+ *
+ * Positions are ordered row by row, the same order as indices.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Position {
+    pub fn new(x: usize, y: usize) -> Position {
+        Position { x, y }
+    }
+
+    /**
+     * Converts a row-major index into the map to a position.
+     *
+     * Returns None if the index is not on a map of the given
+     * (width, height).
+     */
+    pub fn from_index(index: usize, (width, height): (usize, usize)) -> Option<Position> {
+        if width == 0 || index >= width * height {
+            return None;
+        }
+
+        Some(Position {
+            x: index % width,
+            y: index / width,
+        })
+    }
+
+    /**
+     * Converts the position into a row-major index into the map.
+     *
+     * Returns None if the position is not on a map of the given
+     * (width, height).
+     */
+    pub fn to_index(self, (width, height): (usize, usize)) -> Option<usize> {
+        if self.x >= width || self.y >= height {
+            return None;
+        }
+
+        Some(self.y * width + self.x)
+    }
+}
+
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the index of `position`, or None if it is not on the map.
+     */
+    pub fn index_of(&self, position: Position) -> Option<usize> {
+        position.to_index(self.map.dimensions())
+    }
+
+    /**
+     * Returns the position of `index`, or None if it is not on the map.
+     */
+    pub fn position_of(&self, index: usize) -> Option<Position> {
+        Position::from_index(index, self.map.dimensions())
+    }
+
+    /**
+     * Returns the unit at `index`, if any.
+     */
+    pub fn unit(&self, index: usize) -> Option<&UnitState> {
+        self.units.get(&index)
+    }
+
+    /**
+     * Returns the unit at `position`, if any.
+     */
+    pub fn unit_at(&self, position: Position) -> Option<&UnitState> {
+        self.unit(self.index_of(position)?)
+    }
+
+    /**
+     * Returns every position within `distance` tiles of `position`. Empty
+     * if `position` is not on the map.
+     */
+    pub fn neighbors_at(&self, position: Position, distance: usize) -> HashSet<Position> {
+        let Some(index) = self.index_of(position) else {
+            return HashSet::new();
+        };
+
+        self.neighbors(index, distance)
+            .into_iter()
+            .filter_map(|tile| self.position_of(tile))
+            .collect()
+    }
+
+    /**
+     * Returns the terrain at `position`, or None if it is not on the map.
+     */
+    pub fn tile_at(&self, position: Position) -> Option<&TileKind> {
+        self.map.cells().get(self.index_of(position)?)
+    }
+
+    /**
+     * Changes the terrain at `position` to `tile`, such as when a pipe seam
+     * is destroyed, and returns what was there. Returns None and changes
+     * nothing if `position` is not on the map.
+     *
+     * Whoever owned the tile loses it if `tile` is not a property. A unit
+     * on the tile is left where it is even if it could not be there, see
+     * `GameState::validate`.
+     */
+    pub fn set_tile(&mut self, position: Position, tile: TileKind) -> Option<TileKind> {
+        let index = self.index_of(position)?;
+        let cell = self.map.cells_mut().get_mut(index)?;
+
+        if !tile.is_property() {
+            self.owners.remove(&index);
+        }
+        Some(core::mem::replace(cell, tile))
+    }
+
+    /**
+     * Returns every tile of the map with its position, row by row.
+     */
+    pub fn tiles(&self) -> impl Iterator<Item = (Position, &TileKind)> + '_ {
+        self.map.iter()
+    }
+
+    /**
+     * Returns every unit on the map with its position, row by row. Units
+     * being carried are left out, see `UnitState::cargo`.
+     */
+    pub fn units(&self) -> impl Iterator<Item = (Position, &UnitState)> + '_ {
+        self.units
+            .iter()
+            .map_while(|(index, unit)| Some((self.position_of(*index)?, unit)))
+    }
+
+    /**
+     * Returns the tiles of row `y` from left to right, none if there is no
+     * such row.
+     */
+    pub fn row(&self, y: usize) -> impl Iterator<Item = (Position, &TileKind)> + '_ {
+        let width = self.map.dimensions().0;
+        let start = if y < self.map.dimensions().1 {
+            y.saturating_mul(width)
+        } else {
+            self.map.len()
+        };
+
+        self.tiles().skip(start).take(width)
+    }
+
+    /**
+     * Same as `common_vision` but as positions in row by row order.
+     */
+    pub fn common_vision_positions(&self) -> BTreeSet<Position> {
+        self.common_vision()
+            .into_iter()
+            .filter_map(|tile| self.position_of(tile))
+            .collect()
+    }
+}
+
+/**
+ * A tile as addressed by the AWBW site API, which counts columns and rows
+ * from 0 at the top left exactly like `Position`.
+ */
+pub type AwbwCoordinate = Position;
+
+/**
+ * A tile as addressed by the AWBW Move Planner: `x` is the column and `y`
+ * is the row, both counted from 1 at the top left.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct MovePlannerCoordinate {
+    pub x: usize,
+    pub y: usize,
+}
+
+/**
+ * Converts a row-major index into the map to AWBW API coordinates.
+ *
+ * Returns None if the index is not on a map of the given (width, height).
+ */
+pub fn index_to_awbw(index: usize, dimensions: (usize, usize)) -> Option<AwbwCoordinate> {
+    Position::from_index(index, dimensions)
+}
+
+/**
+ * Converts AWBW API coordinates into a row-major index into the map.
+ *
+ * Returns None if the coordinates are not on a map of the given
+ * (width, height).
+ */
+pub fn awbw_to_index(coordinate: AwbwCoordinate, dimensions: (usize, usize)) -> Option<usize> {
+    coordinate.to_index(dimensions)
+}
+
+/**
+ * Converts a row-major index into the map to Move Planner coordinates.
+ *
+ * Returns None if the index is not on a map of the given (width, height).
+ */
+pub fn index_to_move_planner(
+    index: usize,
+    dimensions: (usize, usize),
+) -> Option<MovePlannerCoordinate> {
+    index_to_awbw(index, dimensions).map(MovePlannerCoordinate::from)
+}
+
+/**
+ * Converts Move Planner coordinates into a row-major index into the map.
+ *
+ * Returns None if the coordinates are not on a map of the given
+ * (width, height).
+ */
+pub fn move_planner_to_index(
+    coordinate: MovePlannerCoordinate,
+    dimensions: (usize, usize),
+) -> Option<usize> {
+    awbw_to_index(AwbwCoordinate::try_from(coordinate).ok()?, dimensions)
+}
+
+impl From<AwbwCoordinate> for MovePlannerCoordinate {
+    fn from(coordinate: AwbwCoordinate) -> Self {
+        MovePlannerCoordinate {
+            x: coordinate.x + 1,
+            y: coordinate.y + 1,
+        }
+    }
+}
+
+impl TryFrom<MovePlannerCoordinate> for AwbwCoordinate {
+    type Error = MovePlannerCoordinate;
+
+    /**
+     * Fails if either coordinate is 0 which the Move Planner never uses.
+     */
+    fn try_from(coordinate: MovePlannerCoordinate) -> Result<Self, Self::Error> {
+        match (coordinate.x.checked_sub(1), coordinate.y.checked_sub(1)) {
+            (Some(x), Some(y)) => Ok(AwbwCoordinate { x, y }),
+            _ => Err(coordinate),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_round_trip() {
+        let dimensions = (3, 2);
+        for index in 0..6 {
+            let position = Position::from_index(index, dimensions).unwrap();
+            assert_eq!(Some(index), position.to_index(dimensions));
+        }
+
+        assert_eq!(None, Position::from_index(6, dimensions));
+        assert_eq!(None, Position::new(3, 0).to_index(dimensions));
+        assert!(Position::new(2, 0) < Position::new(0, 1));
+    }
+
+    #[test]
+    fn game_state_positions() {
+        let game_state = crate::game_state!(
+            "
+            .0inf . .
+            .     . .1inf
+            "
+        );
+
+        assert_eq!(
+            Some(&UnitState::new(1, false, crate::unit::UnitKind::Infantry)),
+            game_state.unit_at(Position::new(2, 1))
+        );
+        assert_eq!(None, game_state.unit_at(Position::new(5, 5)));
+        assert_eq!(
+            [
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(0, 1)
+            ]
+            .into_iter()
+            .collect::<HashSet<Position>>(),
+            game_state.neighbors_at(Position::new(0, 0), 1)
+        );
+        assert!(game_state.neighbors_at(Position::new(0, 2), 1).is_empty());
+
+        assert_eq!(
+            Some((Position::new(1, 1), &TileKind::Plain)),
+            game_state.tiles().nth(4)
+        );
+        assert_eq!(6, game_state.tiles().count());
+        assert_eq!(
+            vec![Position::new(0, 0), Position::new(2, 1)],
+            game_state
+                .units()
+                .map(|(position, _)| position)
+                .collect::<Vec<Position>>()
+        );
+        assert_eq!(
+            vec![
+                Position::new(0, 1),
+                Position::new(1, 1),
+                Position::new(2, 1)
+            ],
+            game_state
+                .row(1)
+                .map(|(position, _)| position)
+                .collect::<Vec<Position>>()
+        );
+        assert_eq!(0, game_state.row(2).count());
+    }
+
+    #[test]
+    fn tiles_by_position() {
+        let mut game_state = crate::game_state!(". P c\n. . .");
+        game_state.set_owner(2, Some(0));
+
+        assert_eq!(
+            Some(&TileKind::PipeSeam),
+            game_state.tile_at(Position::new(1, 0))
+        );
+        assert_eq!(None, game_state.tile_at(Position::new(3, 0)));
+
+        assert_eq!(
+            Some(TileKind::PipeSeam),
+            game_state.set_tile(Position::new(1, 0), TileKind::BrokenPipeSeam)
+        );
+        assert_eq!(
+            Some(&TileKind::BrokenPipeSeam),
+            game_state.tile_at(Position::new(1, 0))
+        );
+        assert_eq!(
+            None,
+            game_state.set_tile(Position::new(0, 2), TileKind::Sea)
+        );
+
+        game_state.set_tile(Position::new(2, 0), TileKind::Base);
+        assert_eq!(Some(0), game_state.owner_of(2));
+        game_state.set_tile(Position::new(2, 0), TileKind::Plain);
+        assert_eq!(None, game_state.owner_of(2));
+        assert_eq!(
+            game_state
+                .common_vision()
+                .into_iter()
+                .map(|tile| game_state.position_of(tile).unwrap())
+                .collect::<BTreeSet<Position>>(),
+            game_state.common_vision_positions()
+        );
+    }
+
+    #[test]
+    fn awbw_round_trip() {
+        let dimensions = (3, 2);
+        for index in 0..6 {
+            let coordinate = index_to_awbw(index, dimensions).unwrap();
+            assert_eq!(Some(index), awbw_to_index(coordinate, dimensions));
+        }
+
+        assert_eq!(
+            Some(AwbwCoordinate { x: 2, y: 1 }),
+            index_to_awbw(5, dimensions)
+        );
+        assert_eq!(None, index_to_awbw(6, dimensions));
+        assert_eq!(
+            None,
+            awbw_to_index(AwbwCoordinate { x: 3, y: 0 }, dimensions)
+        );
+    }
+
+    #[test]
+    fn move_planner_round_trip() {
+        let dimensions = (3, 2);
+        for index in 0..6 {
+            let coordinate = index_to_move_planner(index, dimensions).unwrap();
+            assert_eq!(Some(index), move_planner_to_index(coordinate, dimensions));
+        }
+
+        assert_eq!(
+            Some(MovePlannerCoordinate { x: 1, y: 1 }),
+            index_to_move_planner(0, dimensions)
+        );
+        assert_eq!(
+            None,
+            move_planner_to_index(MovePlannerCoordinate { x: 0, y: 1 }, dimensions)
+        );
+    }
+}
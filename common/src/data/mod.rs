@@ -0,0 +1,259 @@
+use crate::officer::PowerKind;
+use crate::unit::{MovementKind, UnitKind};
+
+/**
+ * Every table of per-unit data.
+ *
+ * Implementations match on every variant without a wildcard so adding a
+ * new `UnitKind` fails to compile until every table covers it, and the
+ * tests below fail until it is added to `ALL`.
+ */
+pub trait UnitData: Sized + 'static {
+    /** Every variant in declaration order. */
+    const ALL: &'static [Self];
+
+    /** How many tiles away the unit can see. */
+    fn vision(&self) -> u8;
+    fn movement_kind(&self) -> MovementKind;
+    fn movement_points(&self) -> u8;
+    /** The funds needed to build the unit. */
+    fn cost(&self) -> u32;
+    /**
+     * The (minimum, maximum) distance the unit can attack at, or None if it
+     * cannot attack at all.
+     *
+     * Units with a minimum range above 1 are indirect and cannot move and
+     * fire on the same turn.
+     */
+    fn attack_range(&self) -> Option<(u8, u8)>;
+    /** The fuel the unit is built with. */
+    fn max_fuel(&self) -> u8;
+    /** The fuel burnt at the start of each of its owner's turns. */
+    fn daily_fuel(&self, stealthed: bool) -> u8;
+    /** The ammo the unit is built with, 0 if its weapons need none. */
+    fn max_ammo(&self) -> u8;
+}
+
+/**
+ * Every table of per-terrain data.
+ */
+pub trait TileData: Sized + 'static {
+    /** Every variant in declaration order. */
+    const ALL: &'static [Self];
+
+    /** True if units on the tile can only be seen from adjacent tiles. */
+    fn hides_units(&self) -> bool;
+    /**
+     * The movement points needed to enter the tile, or None if units that
+     * move this way cannot enter it.
+     */
+    fn movement_cost(&self, movement: MovementKind) -> Option<u8>;
+    /** Defense stars the tile gives to ground units. */
+    fn defense(&self) -> u8;
+}
+
+/**
+ * Every table of per-officer data.
+ */
+pub trait OfficerData: Sized + 'static {
+    /** Every variant in declaration order. */
+    const ALL: &'static [Self];
+
+    /** Extra vision the officer gives their units. */
+    fn vision_bonus(&self, power: &PowerKind) -> u8;
+    /** True if the officer's units can see into tiles that hide units. */
+    fn reveals_hidden(&self, power: &PowerKind) -> bool;
+    /** True if the officer has `power`, every officer has no power. */
+    fn has_power(&self, power: &PowerKind) -> bool;
+    /**
+     * Stars of charge the power meter needs before `power` can be
+     * activated, 0 for no power or one the officer does not have.
+     */
+    fn power_stars(&self, power: &PowerKind) -> u8;
+    /** Change to the maximum range the officer's indirect `kind` units
+     * fire at, negative for officers that shorten it. */
+    fn range_bonus(&self, power: &PowerKind, kind: &UnitKind) -> i8;
+    /** Extra funds each income property of the officer's brings in. */
+    fn income_bonus(&self) -> u32;
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::prelude::HashSet;
+    use core::fmt::Debug;
+    use core::hash::Hash;
+
+    use super::*;
+    use crate::map::TileKind;
+    use crate::officer::OfficerKind;
+    use crate::unit::UnitKind;
+
+    /**
+     * Checks `ALL` lists every variant exactly once by comparing it against
+     * an exhaustive match, which stops compiling when a variant is added.
+     */
+    fn check_all<T: Debug + Eq + Hash>(all: &[T], index_of: impl Fn(&T) -> usize) {
+        assert_eq!(
+            all.len(),
+            all.iter().collect::<HashSet<&T>>().len(),
+            "ALL has duplicates"
+        );
+        for (index, variant) in all.iter().enumerate() {
+            assert_eq!(index, index_of(variant), "{:?} is out of order", variant);
+        }
+    }
+
+    macro_rules! exhaustive_index {
+        ($kind:ident { $($variant:ident),* $(,)? }) => {
+            |kind: &$kind| {
+                let variants: &[$kind] = &[$($kind::$variant),*];
+                match kind {
+                    $($kind::$variant)|* => variants
+                        .iter()
+                        .position(|variant| variant == kind)
+                        .expect("Variant was listed"),
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn unit_data_complete() {
+        check_all(
+            UnitKind::ALL,
+            exhaustive_index!(UnitKind {
+                AntiAir,
+                Apc,
+                Artillery,
+                BattleCopter,
+                BattleShip,
+                BlackBoat,
+                BlackBomb,
+                Bomber,
+                Carrier,
+                Cruiser,
+                Fighter,
+                Flare,
+                Infantry,
+                Lander,
+                MediumTank,
+                Mech,
+                MegaTank,
+                Missile,
+                NeoTank,
+                PipeRunner,
+                Recon,
+                Rocket,
+                Stealth,
+                Submarine,
+                TransportCopter,
+                Tank,
+            }),
+        );
+
+        for kind in UnitKind::ALL {
+            assert!(kind.vision() > 0, "{:?} has no vision", kind);
+            assert!(kind.movement_points() > 0, "{:?} can not move", kind);
+            assert!(kind.cost() > 0, "{:?} has no cost", kind);
+            assert!(
+                TileKind::ALL
+                    .iter()
+                    .any(|tile| tile.movement_cost(kind.movement_kind()).is_some()),
+                "{:?} can not enter any tile",
+                kind
+            );
+            if let Some((min, max)) = kind.attack_range() {
+                assert!(0 < min && min <= max, "{:?} has a bad range", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn tile_data_complete() {
+        check_all(
+            TileKind::ALL,
+            exhaustive_index!(TileKind {
+                Plain,
+                Mountain,
+                Forest,
+                River,
+                Road,
+                Bridge,
+                Sea,
+                Shoal,
+                Reef,
+                City,
+                Base,
+                Airport,
+                Harbour,
+                HeadQuarters,
+                Pipe,
+                PipeSeam,
+                BrokenPipeSeam,
+                Silo,
+                UsedSilo,
+                CommunicationsTower,
+                Laboratory,
+                Teleporter,
+                Void,
+            }),
+        );
+
+        for tile in TileKind::ALL {
+            assert!(tile.defense() <= 4, "{:?} has too much defense", tile);
+        }
+    }
+
+    #[test]
+    fn officer_data_complete() {
+        check_all(
+            OfficerKind::ALL,
+            exhaustive_index!(OfficerKind {
+                Andy,
+                Hachi,
+                Jake,
+                Max,
+                Nell,
+                Rachel,
+                Sami,
+                Colin,
+                Grit,
+                Olaf,
+                Sasha,
+                Drake,
+                Eagle,
+                Javier,
+                Jess,
+                Grimm,
+                Kanbei,
+                Sensei,
+                Sonja,
+                Adder,
+                Flak,
+                Hawke,
+                Jugger,
+                Kindle,
+                Koal,
+                Lash,
+                Sturm,
+                VonBolt,
+            }),
+        );
+
+        for officer in OfficerKind::ALL {
+            for power in [PowerKind::None, PowerKind::Normal, PowerKind::Super] {
+                assert!(officer.vision_bonus(&power) <= 2);
+            }
+            assert!(officer.has_power(&PowerKind::None));
+            assert!(officer.has_power(&PowerKind::Super));
+        }
+    }
+
+    #[test]
+    fn sonja_day_to_day_vision() {
+        // AWBW gives Sonja +1 vision even without a power.
+        assert_eq!(1, OfficerKind::Sonja.vision_bonus(&PowerKind::None));
+        assert!(!OfficerKind::Sonja.reveals_hidden(&PowerKind::None));
+        assert_eq!(2, OfficerKind::Sonja.vision_bonus(&PowerKind::Normal));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::builder::BuildError;
+use crate::mask::VisionMask;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * Why a vision question could not be answered.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum VisionError {
+    /** The state is not consistent, for example after deserializing one
+     * that was edited by hand. */
+    InvalidState(BuildError),
+    /** The team asked about does not exist. */
+    UnknownTeam { team: usize },
+    /** The common vision fixed point did not settle within its bound. */
+    FixpointBound { iterations: usize },
+}
+
+impl fmt::Display for VisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisionError::InvalidState(error) => write!(f, "Invalid state: {}", error),
+            VisionError::UnknownTeam { team } => write!(f, "There is no team {}", team),
+            VisionError::FixpointBound { iterations } => write!(
+                f,
+                "Common vision did not settle after {} passes",
+                iterations
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VisionError {}
+
+impl From<BuildError> for VisionError {
+    fn from(error: BuildError) -> Self {
+        VisionError::InvalidState(error)
+    }
+}
+
+impl GameState {
+    /**
+     * Computes the same result as `common_vision_with_config` but reports an
+     * inconsistent state or a fixed point that did not settle instead of
+     * returning an empty set.
+     */
+    pub fn try_common_vision_with_config(
+        &self,
+        config: &VisionConfig,
+    ) -> Result<HashSet<usize>, VisionError> {
+        self.check_structure()?;
+
+        let (vision, diagnostics) = self.common_vision_with_diagnostics(config);
+        if diagnostics.hit_bound {
+            return Err(VisionError::FixpointBound {
+                iterations: diagnostics.iterations,
+            });
+        }
+
+        Ok(vision)
+    }
+
+    /**
+     * Computes the same result as `visible_to_team_with_config` but reports
+     * an inconsistent state or unknown team instead of returning an empty
+     * set.
+     */
+    pub fn try_visible_to_team_with_config(
+        &self,
+        team: usize,
+        config: &VisionConfig,
+    ) -> Result<HashSet<usize>, VisionError> {
+        self.check_structure()?;
+        if team >= self.teams.len() {
+            return Err(VisionError::UnknownTeam { team });
+        }
+
+        Ok(self.visible_to_team_with_config(team, config))
+    }
+
+    /**
+     * Computes the same result as `try_common_vision_with_config` as a
+     * `VisionMask`.
+     */
+    pub fn try_common_vision_mask_with_config(
+        &self,
+        config: &VisionConfig,
+    ) -> Result<VisionMask, VisionError> {
+        self.check_structure()?;
+        if !self.settings.fog {
+            return Ok(VisionMask::full(self.map.len()));
+        }
+
+        match self.common_vision_fixpoint(config) {
+            (Some(fixpoint), _) => Ok(fixpoint.visible_mask(self.map.len())),
+            (None, diagnostics) => Err(VisionError::FixpointBound {
+                iterations: diagnostics.iterations,
+            }),
+        }
+    }
+
+    /**
+     * Computes the same result as `try_visible_to_team_with_config` as a
+     * `VisionMask`.
+     */
+    pub fn try_visible_to_team_mask(
+        &self,
+        team: usize,
+        config: &VisionConfig,
+    ) -> Result<VisionMask, VisionError> {
+        self.check_structure()?;
+        if team >= self.teams.len() {
+            return Err(VisionError::UnknownTeam { team });
+        }
+
+        Ok(self.visible_to_team_mask(team, config))
+    }
+
+    /**
+     * Returns the first of the problems `GameStateBuilder::build` would have
+     * rejected that a state can still have, such as after deserializing.
+     */
+    pub(crate) fn check_structure(&self) -> Result<(), BuildError> {
+        match self.structural_problems().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::unit::UnitKind;
+    use crate::{game_state, UnitState};
+
+    #[test]
+    fn try_common_vision_reports_errors() {
+        let mut game_state = game_state!(".0inf . .1inf");
+        let config = VisionConfig::default();
+        assert_eq!(
+            Ok(game_state.common_vision()),
+            game_state.try_common_vision_with_config(&config)
+        );
+
+        game_state
+            .units
+            .insert(3, UnitState::new(0, false, UnitKind::Infantry));
+        assert_eq!(
+            Err(VisionError::InvalidState(BuildError::UnitOutOfBounds {
+                location: 3
+            })),
+            game_state.try_common_vision_with_config(&config)
+        );
+    }
+
+    #[test]
+    fn try_visible_to_team_reports_errors() {
+        let mut game_state = game_state!(".0inf . .1inf");
+        let config = VisionConfig::default();
+        assert_eq!(
+            Err(VisionError::UnknownTeam { team: 2 }),
+            game_state.try_visible_to_team_with_config(2, &config)
+        );
+
+        game_state.teams[1].insert(3);
+        assert_eq!(
+            Err(VisionError::InvalidState(BuildError::UnknownTeamMember {
+                team: 1,
+                player: 3,
+            })),
+            game_state.try_visible_to_team_with_config(0, &config)
+        );
+    }
+
+    #[test]
+    fn try_masks_match_sets() {
+        let mut game_state = game_state!(".0inf f .1inf\n. . .");
+        let config = VisionConfig::default();
+        assert_eq!(
+            Ok(game_state.common_vision_mask()),
+            game_state.try_common_vision_mask_with_config(&config)
+        );
+        assert_eq!(
+            Ok(game_state.visible_to_team_mask(1, &config)),
+            game_state.try_visible_to_team_mask(1, &config)
+        );
+        assert_eq!(
+            Err(VisionError::UnknownTeam { team: 2 }),
+            game_state.try_visible_to_team_mask(2, &config)
+        );
+
+        game_state
+            .units
+            .insert(6, UnitState::new(0, false, UnitKind::Infantry));
+        assert_eq!(
+            Err(VisionError::InvalidState(BuildError::UnitOutOfBounds {
+                location: 6
+            })),
+            game_state.try_common_vision_mask_with_config(&config)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashSet};
+
+use arbitrary::{Result, Unstructured};
+
+use crate::action::Action;
+use crate::data::{OfficerData, UnitData};
+use crate::grid::Grid;
+use crate::incremental::Incremental;
+use crate::map::{CountryKind, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::reveal::Reveal;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::unit::{UnitKind, MAX_HP};
+use crate::vision::VisionConfig;
+use crate::weather::Weather;
+use crate::{GameState, TileData, UnitState};
+
+/**
+ * Builds a small but otherwise arbitrary state, which is valid in that
+ * every unit is on the map and every player exists, though units may be
+ * on terrain they could never reach.
+ */
+pub fn arbitrary_state(u: &mut Unstructured) -> Result<GameState> {
+    let width = u.int_in_range(1..=12usize)?;
+    let height = u.int_in_range(1..=12usize)?;
+
+    let mut map = Vec::with_capacity(width * height);
+    for _ in 0..width * height {
+        map.push(u.choose(TileKind::ALL)?.clone());
+    }
+
+    let player_count = u.int_in_range(1..=4usize)?;
+    let mut players = Vec::with_capacity(player_count);
+    let mut teams = vec![HashSet::new(); player_count];
+    for player in 0..player_count {
+        let power = u.choose(&[PowerKind::None, PowerKind::Normal, PowerKind::Super])?;
+        players.push(
+            PlayerState::new(
+                CountryKind::OrangeStar,
+                u.choose(OfficerKind::ALL)?.clone(),
+                power.clone(),
+            )
+            .with_charge(u.arbitrary()?),
+        );
+        teams[u.int_in_range(0..=player)?].insert(player);
+    }
+    teams.retain(|team| !team.is_empty());
+
+    let mut units = BTreeMap::new();
+    for _ in 0..u.int_in_range(0..=16usize)? {
+        units.insert(
+            u.int_in_range(0..=map.len() - 1)?,
+            UnitState::new(
+                u.int_in_range(0..=player_count - 1)?,
+                u.arbitrary()?,
+                u.choose(UnitKind::ALL)?.clone(),
+            )
+            .with_hp(u.int_in_range(1..=MAX_HP)?)
+            .with_fuel(u.arbitrary()?)
+            .with_ammo(u.arbitrary()?),
+        );
+    }
+
+    let mut owners = BTreeMap::new();
+    for (location, tile) in map.iter().enumerate() {
+        if tile.is_property() && u.arbitrary()? {
+            owners.insert(location, u.int_in_range(0..=player_count - 1)?);
+        }
+    }
+
+    let mut reveals = Vec::new();
+    for _ in 0..u.int_in_range(0..=2usize)? {
+        reveals.push(Reveal {
+            player: match u.arbitrary()? {
+                true => Some(u.int_in_range(0..=player_count - 1)?),
+                false => None,
+            },
+            center: u.int_in_range(0..=map.len() - 1)?,
+            radius: u.int_in_range(0..=3usize)?,
+        });
+    }
+
+    Ok(GameState {
+        map: Grid::new(map, (width, height)).expect("Tiles do not fill the map"),
+        units,
+        players,
+        teams,
+        weather: Weather::Clear,
+        settings: GameSettings {
+            fog: u.ratio(7u8, 8u8)?,
+            ..GameSettings::fog_league()
+        },
+        owners,
+        reveals,
+        ruleset: u.choose(Ruleset::ALL)?.clone(),
+        day: u.int_in_range(1..=99)?,
+        active_player: u.int_in_range(0..=player_count - 1)?,
+    })
+}
+
+/**
+ * Feeds arbitrary bytes to the text importers, which must reject bad input
+ * rather than panic, and analyzes whatever they accept.
+ */
+pub fn fuzz_importers(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = crate::map::from_awbw_text(text);
+    let _ = crate::ascii::parse_map(text);
+    if let Ok(state) = crate::ascii::parse_game_state(text) {
+        let _ = state.lint();
+        let _ = state.common_vision();
+    }
+}
+
+/**
+ * Checks the invariants of the vision computations on an arbitrary state.
+ */
+pub fn fuzz_common_vision(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    let Ok(state) = arbitrary_state(&mut u) else {
+        return;
+    };
+
+    let config = VisionConfig::default();
+    let (common, diagnostics) = state.common_vision_with_diagnostics(&config);
+    assert!(!diagnostics.hit_bound, "Fixpoint hit its bound");
+
+    for team in 0..state.teams.len() {
+        assert!(
+            common.is_subset(&state.visible_to_team_with_config(team, &config)),
+            "Common vision is not seen by team {}",
+            team
+        );
+    }
+
+    assert_eq!(common, state.common_vision_chunked(&config, 3, None));
+    assert_eq!(Ok(common), state.common_vision_task(&config).finish());
+}
+
+/**
+ * Applies arbitrary actions to an arbitrary state, which must either
+ * succeed or leave the state unchanged.
+ */
+pub fn fuzz_apply(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    let Ok(mut state) = arbitrary_state(&mut u) else {
+        return;
+    };
+
+    let tiles = state.map.len() - 1;
+    while !u.is_empty() {
+        let Ok(action) = arbitrary_action(&mut u, tiles) else {
+            return;
+        };
+        let before = state.clone();
+        if state.apply_in_place(&action).is_err() {
+            assert_eq!(before, state, "Rejected {:?} changed the state", action);
+        }
+    }
+}
+
+fn arbitrary_action(u: &mut Unstructured, tiles: usize) -> Result<Action> {
+    let mut location = || u.int_in_range(0..=tiles);
+    let (a, b, c) = (location()?, location()?, location()?);
+
+    Ok(match u.int_in_range(0..=8u8)? {
+        0 => Action::Move { from: a, to: b },
+        1 => Action::Attack {
+            attacker: a,
+            target: b,
+            damage: u.int_in_range(0..=MAX_HP)?,
+        },
+        2 => Action::Capture { location: a },
+        3 => Action::Build {
+            location: a,
+            kind: u.choose(UnitKind::ALL)?.clone(),
+        },
+        4 => Action::Load {
+            location: a,
+            transport: b,
+        },
+        5 => Action::Unload {
+            transport: a,
+            index: u.int_in_range(0..=1)?,
+            destination: c,
+        },
+        6 => Action::Dive { location: a },
+        7 => Action::EndTurn,
+        _ => Action::Surface { location: a },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzz_entry_points_smoke() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        for length in 0..64 {
+            let data = (0..length * 16)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed as u8
+                })
+                .collect::<Vec<u8>>();
+
+            fuzz_importers(&data);
+            fuzz_common_vision(&data);
+            fuzz_apply(&data);
+        }
+
+        fuzz_importers(b"teams: 0 1\n.0inf f1tank*\n~ .2sub");
+    }
+}
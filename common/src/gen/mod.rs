@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use crate::data::{TileData, UnitData};
+use crate::map::{CountryKind, Map, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::teams::Teams;
+use crate::unit::UnitKind;
+use crate::weather::Weather;
+use crate::{GameState, UnitState};
+
+/**
+ * What `random_state` generates.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenConfig {
+    pub width: usize,
+    pub height: usize,
+    /** At least 1, each player is on their own team. */
+    pub players: usize,
+    /** Chance of each tile holding a unit, from 0 to 1. */
+    pub unit_density: f64,
+    /** Chance of each tile being a Forest, from 0 to 1. */
+    pub forest_ratio: f64,
+    pub fog: bool,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        GenConfig {
+            width: 20,
+            height: 20,
+            players: 2,
+            unit_density: 0.1,
+            forest_ratio: 0.1,
+            fog: true,
+        }
+    }
+}
+
+/**
+ * SplitMix64, small and good enough for generating test data. The same
+ * seed always gives the same sequence.
+ */
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /**
+     * Returns a number in `0..bound`, or 0 if `bound` is 0.
+     */
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /**
+     * Returns true with probability `chance`, clamped to 0 and 1.
+     */
+    pub fn chance(&mut self, chance: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < chance
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len()))
+    }
+}
+
+/** Terrain other than Forest, Plains repeated so they are most common. */
+const TERRAIN: [TileKind; 12] = [
+    TileKind::Plain,
+    TileKind::Plain,
+    TileKind::Plain,
+    TileKind::Plain,
+    TileKind::Plain,
+    TileKind::Mountain,
+    TileKind::Road,
+    TileKind::River,
+    TileKind::Sea,
+    TileKind::Shoal,
+    TileKind::Reef,
+    TileKind::City,
+];
+
+/**
+ * Returns a random map of `config`'s size.
+ */
+pub fn random_map(config: &GenConfig, seed: u64) -> Map {
+    random_map_with(config, &mut Rng::new(seed))
+}
+
+fn random_map_with(config: &GenConfig, rng: &mut Rng) -> Map {
+    let tiles = (0..config.width * config.height)
+        .map(|_| {
+            if rng.chance(config.forest_ratio) {
+                TileKind::Forest
+            } else {
+                rng.choose(&TERRAIN).cloned().unwrap_or(TileKind::Plain)
+            }
+        })
+        .collect();
+    Map::new(tiles, (config.width, config.height))
+}
+
+/**
+ * Returns a random state which `GameState::validate` accepts. Units are
+ * only placed on terrain they could move onto and are only stealthed if
+ * they can be.
+ */
+pub fn random_state(config: &GenConfig, seed: u64) -> GameState {
+    let mut rng = Rng::new(seed);
+    let map = random_map_with(config, &mut rng);
+    let players = config.players.max(1);
+
+    let mut units = BTreeMap::new();
+    for (location, tile) in map.tiles().iter().enumerate() {
+        if !rng.chance(config.unit_density) {
+            continue;
+        }
+
+        let kinds = UnitKind::ALL
+            .iter()
+            .filter(|kind| tile.movement_cost(kind.movement_kind()).is_some())
+            .collect::<Vec<&UnitKind>>();
+        if let Some(kind) = rng.choose(&kinds) {
+            let stealthed = kind.can_stealth() && rng.chance(0.5);
+            units.insert(
+                location,
+                UnitState::new(rng.below(players), stealthed, (*kind).clone()),
+            );
+        }
+    }
+
+    let countries = [
+        CountryKind::OrangeStar,
+        CountryKind::BlueMoon,
+        CountryKind::GreenEarth,
+        CountryKind::YellowComet,
+        CountryKind::BlackHole,
+    ];
+
+    let mut game_state = GameState::from_map(&map);
+    game_state.units = units;
+    game_state.players = (0..players)
+        .map(|player| {
+            PlayerState::new(
+                countries[player % countries.len()].clone(),
+                OfficerKind::Andy,
+                PowerKind::None,
+            )
+        })
+        .collect();
+    game_state.teams = Teams::ffa(players).into();
+    game_state.weather = Weather::Clear;
+    game_state.settings = GameSettings {
+        fog: config.fog,
+        ..GameSettings::fog_league()
+    };
+    game_state.ruleset = Ruleset::Awbw;
+    game_state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_states_are_valid_and_seeded() {
+        let config = GenConfig {
+            players: 3,
+            unit_density: 0.3,
+            ..GenConfig::default()
+        };
+
+        for seed in 0..20 {
+            let game_state = random_state(&config, seed);
+            assert_eq!(Ok(()), game_state.validate(), "seed {}", seed);
+            assert_eq!(3, game_state.teams.len());
+            assert_eq!(game_state, random_state(&config, seed));
+        }
+        assert_ne!(random_state(&config, 1), random_state(&config, 2));
+    }
+
+    #[test]
+    fn config_shapes_output() {
+        let forest = random_map(
+            &GenConfig {
+                width: 5,
+                height: 4,
+                forest_ratio: 1.0,
+                ..GenConfig::default()
+            },
+            7,
+        );
+        assert_eq!((5, 4), forest.dimensions());
+        assert!(forest.tiles().iter().all(|tile| *tile == TileKind::Forest));
+
+        let empty = random_state(
+            &GenConfig {
+                unit_density: 0.0,
+                ..GenConfig::default()
+            },
+            7,
+        );
+        assert!(empty.units.is_empty());
+    }
+}
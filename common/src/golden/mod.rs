@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * A scenario whose recorded vision differs from what it computes now.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GoldenMismatch {
+    pub name: String,
+    /** The recorded output. */
+    pub expected: String,
+    /** The output computed now. */
+    pub actual: String,
+}
+
+impl GoldenMismatch {
+    /**
+     * Returns the lines that differ, prefixed with `-` for recorded lines
+     * and `+` for computed ones.
+     */
+    pub fn diff(&self) -> Vec<String> {
+        let expected = self.expected.lines().collect::<Vec<&str>>();
+        let actual = self.actual.lines().collect::<Vec<&str>>();
+
+        let mut lines = Vec::new();
+        for index in 0..std::cmp::max(expected.len(), actual.len()) {
+            let (before, after) = (expected.get(index), actual.get(index));
+            if before == after {
+                continue;
+            }
+            if let Some(before) = before {
+                lines.push(format!("-{}", before));
+            }
+            if let Some(after) = after {
+                lines.push(format!("+{}", after));
+            }
+        }
+        lines
+    }
+}
+
+/**
+ * A directory of recorded vision outputs, one `<name>.golden` file per
+ * scenario, used to catch unintended behaviour changes.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GoldenCorpus {
+    directory: PathBuf,
+    update: bool,
+}
+
+impl GoldenCorpus {
+    /**
+     * Returns a corpus stored in `directory`. Outputs are re-recorded
+     * instead of compared if the `UPDATE_GOLDEN` environment variable is
+     * set.
+     */
+    pub fn new(directory: impl Into<PathBuf>) -> GoldenCorpus {
+        GoldenCorpus {
+            directory: directory.into(),
+            update: std::env::var_os("UPDATE_GOLDEN").is_some(),
+        }
+    }
+
+    /**
+     * If `update` is true then every checked scenario is re-recorded
+     * instead of compared.
+     */
+    pub fn with_update(mut self, update: bool) -> GoldenCorpus {
+        self.update = update;
+        self
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /**
+     * Compares the vision of `state` to what was recorded for `name`,
+     * recording it if nothing was.
+     */
+    pub fn check(
+        &self,
+        name: &str,
+        state: &GameState,
+        config: &VisionConfig,
+    ) -> std::io::Result<Result<(), GoldenMismatch>> {
+        let path = self.directory.join(format!("{}.golden", name));
+        let actual = render_vision(state, config);
+
+        if !self.update {
+            match std::fs::read_to_string(&path) {
+                Ok(expected) if expected == actual => return Ok(Ok(())),
+                Ok(expected) => {
+                    return Ok(Err(GoldenMismatch {
+                        name: name.to_string(),
+                        expected,
+                        actual,
+                    }))
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        std::fs::create_dir_all(&self.directory)?;
+        std::fs::write(path, actual)?;
+        Ok(Ok(()))
+    }
+
+    /**
+     * Checks every (name, state) and returns the mismatches.
+     */
+    pub fn check_all<'a>(
+        &self,
+        scenarios: impl IntoIterator<Item = (&'a str, &'a GameState)>,
+        config: &VisionConfig,
+    ) -> std::io::Result<Vec<GoldenMismatch>> {
+        let mut mismatches = Vec::new();
+        for (name, state) in scenarios {
+            if let Err(mismatch) = self.check(name, state, config)? {
+                mismatches.push(mismatch);
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+/**
+ * Renders the vision of every team and the common vision of `state` as
+ * stable text, one line per set of tiles in ascending order.
+ */
+pub fn render_vision(state: &GameState, config: &VisionConfig) -> String {
+    fn line(label: &str, tiles: HashSet<usize>) -> String {
+        let mut tiles = tiles.into_iter().collect::<Vec<usize>>();
+        tiles.sort();
+
+        let mut line = label.to_string();
+        line.push(':');
+        for tile in tiles {
+            line.push_str(&format!(" {}", tile));
+        }
+        line.push('\n');
+        line
+    }
+
+    let mut output = String::new();
+    for team in 0..state.teams.len() {
+        output.push_str(&line(
+            &format!("team {}", team),
+            state.visible_to_team_with_config(team, config),
+        ));
+    }
+    output.push_str(&line("common", state.common_vision_with_config(config)));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{duel, free_for_all, two_versus_two};
+
+    #[test]
+    fn corpus_matches() {
+        let corpus = GoldenCorpus::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("golden"));
+        let (duel, two_versus_two, free_for_all) = (duel(), two_versus_two(), free_for_all());
+
+        let mismatches = corpus
+            .check_all(
+                [
+                    ("duel", &duel),
+                    ("two_versus_two", &two_versus_two),
+                    ("free_for_all", &free_for_all),
+                ],
+                &VisionConfig::default(),
+            )
+            .unwrap();
+
+        for mismatch in mismatches.iter() {
+            eprintln!("{}:\n{}", mismatch.name, mismatch.diff().join("\n"));
+        }
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn check_records_then_compares() {
+        let directory = std::env::temp_dir().join(format!("golden-{}", std::process::id()));
+        let corpus = GoldenCorpus::new(&directory).with_update(false);
+        let config = VisionConfig::default();
+
+        let state = duel();
+        assert_eq!(Ok(()), corpus.check("duel", &state, &config).unwrap());
+        assert_eq!(Ok(()), corpus.check("duel", &state, &config).unwrap());
+
+        let mismatch = corpus
+            .check("duel", &two_versus_two(), &config)
+            .unwrap()
+            .unwrap_err();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!("duel", mismatch.name);
+        assert!(mismatch.diff().iter().any(|line| line.starts_with('-')));
+        assert!(mismatch.diff().iter().any(|line| line.starts_with('+')));
+    }
+
+    #[test]
+    fn render_vision_sorted() {
+        let state = crate::game_state!(".0inf . . . .1inf");
+        assert_eq!(
+            "team 0: 0 1 2\nteam 1: 2 3 4\ncommon:\n",
+            render_vision(&state, &VisionConfig::default())
+        );
+    }
+}
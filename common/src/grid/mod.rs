@@ -0,0 +1,226 @@
+use core::ops::{Index, IndexMut};
+
+use crate::coordinate::Position;
+use crate::prelude::*;
+
+/**
+ * A rectangle of cells stored row by row from the top left, always holding
+ * exactly width * height of them.
+ *
+ * Used for anything with one value per tile, such as terrain, vision or
+ * heatmaps, so the width never has to be carried around separately.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /**
+     * Returns a grid of (width, height) holding `cells` row by row, or None
+     * if there are not width * height of them.
+     */
+    pub fn new(cells: Vec<T>, (width, height): (usize, usize)) -> Option<Grid<T>> {
+        if width.checked_mul(height) != Some(cells.len()) {
+            return None;
+        }
+
+        Some(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /**
+     * Returns a grid of (width, height) with each cell set to
+     * `cell(position)`.
+     */
+    pub fn from_fn(
+        (width, height): (usize, usize),
+        mut cell: impl FnMut(Position) -> T,
+    ) -> Grid<T> {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Position::new(x, y)))
+            .map(&mut cell)
+            .collect();
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /**
+     * Returns (width, height).
+     */
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /**
+     * Returns every cell row by row, so a cell's index here is its tile
+     * index.
+     */
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    /**
+     * Returns how many cells there are, width * height.
+     */
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /**
+     * Returns the cell at `position`, or None if it is off the grid.
+     */
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.cells.get(position.to_index(self.dimensions())?)
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        let index = position.to_index(self.dimensions())?;
+        self.cells.get_mut(index)
+    }
+
+    /**
+     * Returns each row from the top, each from left to right.
+     */
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // A grid with no columns still has its rows, they are just empty.
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    /**
+     * Returns every (position, cell) row by row.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| (Position::new(index % self.width, index / self.width), cell))
+    }
+
+    /**
+     * Returns a grid of the same size with `f` applied to every cell.
+     */
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /**
+     * Returns a grid of (width, height) with every cell set to `cell`.
+     */
+    pub fn filled(cell: T, (width, height): (usize, usize)) -> Grid<T> {
+        Grid {
+            cells: vec![cell; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+/**
+ * Panics if `position` is off the grid, see `Grid::get`.
+ */
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).expect("position is off the grid")
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position).expect("position is off the grid")
+    }
+}
+
+/**
+ * Indexes by row-major index, the same as `cells`. Panics if the index is
+ * off the grid.
+ */
+impl<T> Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.cells[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.cells[index]
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::map::{Map, TileKind};
+
+    #[test]
+    fn new_checks_dimensions() {
+        assert!(Grid::new(vec![0; 6], (3, 2)).is_some());
+        assert!(Grid::new(vec![0; 5], (3, 2)).is_none());
+        assert!(Grid::new(vec![0; 1], (usize::MAX, 2)).is_none());
+    }
+
+    #[test]
+    fn positions_and_rows() {
+        let mut grid = Grid::from_fn((3, 2), |position| position.y * 10 + position.x);
+        assert_eq!(&[0, 1, 2, 10, 11, 12], grid.cells());
+        assert_eq!(12, grid[Position::new(2, 1)]);
+        assert_eq!(None, grid.get(Position::new(3, 0)));
+
+        grid[Position::new(0, 1)] = 99;
+        assert_eq!(
+            vec![&[0, 1, 2][..], &[99, 11, 12][..]],
+            grid.rows().collect::<Vec<&[usize]>>()
+        );
+        assert_eq!(Some((Position::new(1, 1), &11)), grid.iter().nth(4));
+        assert_eq!(Grid::filled(true, (3, 2)), grid.map(|cell| *cell < 100));
+        assert_eq!(0, Grid::<u8>::filled(0, (0, 2)).rows().flatten().count());
+    }
+
+    #[test]
+    fn map_round_trip() {
+        let grid = Grid::filled(TileKind::Forest, (2, 3));
+        let map = Map::from(grid.clone());
+        assert_eq!((2, 3), map.dimensions());
+        assert_eq!(Some(grid), map.to_grid());
+        assert_eq!(None, Map::new(vec![TileKind::Plain], (2, 2)).to_grid());
+    }
+}
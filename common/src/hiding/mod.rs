@@ -0,0 +1,369 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::data::{TileData, UnitData};
+use crate::map::TileKind;
+use crate::unit::UnitKind;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * Who can see one of a player's units.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Exposure {
+    /** Location of the exposed unit. */
+    pub location: usize,
+    /** Each (team, observer location) that can see the unit, in ascending
+     * order. */
+    pub observers: Vec<(usize, usize)>,
+}
+
+impl Exposure {
+    /**
+     * Returns every team that can see the unit.
+     */
+    pub fn teams(&self) -> BTreeSet<usize> {
+        self.observers.iter().map(|(team, _)| *team).collect()
+    }
+}
+
+/**
+ * Where the enemy units a team cannot see could be.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HiddenUnits {
+    /** How many of the known enemy units the team cannot see. */
+    pub unseen: usize,
+    /** Every tile an unseen unit could be on, empty if `unseen` is 0. */
+    pub candidates: HashSet<usize>,
+}
+
+impl GameState {
+    /**
+     * Returns where the enemies of `team` it cannot see under the rules
+     * given by `config` could be, given that they have `known_enemies`
+     * units in total (such as from the player list of a live game).
+     *
+     * Candidates are the tiles hidden from the team, whether out of its
+     * vision or in forests and reefs it cannot see into. If `kinds` is
+     * given only tiles at least one of those units could stand on are
+     * kept.
+     */
+    pub fn hidden_unit_candidates(
+        &self,
+        team: usize,
+        known_enemies: usize,
+        kinds: Option<&[UnitKind]>,
+        config: &VisionConfig,
+    ) -> HiddenUnits {
+        if team >= self.teams.len() {
+            return HiddenUnits {
+                unseen: 0,
+                candidates: HashSet::new(),
+            };
+        }
+
+        let visible = self.visible_to_team_with_config(team, config);
+        let seen = self
+            .units
+            .iter()
+            .filter(|(location, unit)| {
+                self.team_of(unit.player) != Some(team) && visible.contains(location)
+            })
+            .count();
+        let unseen = known_enemies.saturating_sub(seen);
+        if unseen == 0 {
+            return HiddenUnits {
+                unseen,
+                candidates: HashSet::new(),
+            };
+        }
+
+        let candidates = self
+            .map
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(location, tile)| {
+                **tile != TileKind::Void
+                    && !visible.contains(location)
+                    && kinds
+                        .map(|kinds| {
+                            kinds
+                                .iter()
+                                .any(|kind| tile.movement_cost(kind.movement_kind()).is_some())
+                        })
+                        .unwrap_or(true)
+            })
+            .map(|(location, _)| location)
+            .collect();
+
+        HiddenUnits { unseen, candidates }
+    }
+
+    /**
+     * Returns every tile the unit at `location` could move to this turn
+     * where no enemy unit would see it.
+     */
+    pub fn hiding_spots_for(&self, location: usize) -> HashSet<usize> {
+        self.hiding_spots_for_with_config(location, &VisionConfig::default())
+    }
+
+    /**
+     * Returns every tile the unit at `location` could move to this turn
+     * where no enemy team would see it under the rules given by `config`.
+     *
+     * Enemy units, owned properties and revealed areas all count, and with
+     * fog of war off there is nowhere to hide.
+     */
+    pub fn hiding_spots_for_with_config(
+        &self,
+        location: usize,
+        config: &VisionConfig,
+    ) -> HashSet<usize> {
+        let Some(unit) = self.units.get(&location) else {
+            return HashSet::new();
+        };
+        let own_team = self.team_of(unit.player);
+
+        let mut what_if = self.clone();
+        let moved = what_if
+            .units
+            .remove(&location)
+            .expect("Unit was not at its own location");
+
+        // Destinations are never occupied by another unit so the moved unit
+        // can be put on each in turn and taken off again.
+        let mut spots = HashSet::new();
+        for destination in self.reachable_tiles(location).into_keys() {
+            what_if.units.insert(destination, moved.clone());
+            let seen = (0..self.teams.len())
+                .filter(|team| Some(*team) != own_team)
+                .any(|team| what_if.unit_visible_to_with_config(destination, team, config));
+            what_if.units.remove(&destination);
+
+            if !seen {
+                spots.insert(destination);
+            }
+        }
+        spots
+    }
+
+    /**
+     * Returns every tile the unit at `location` could move to this turn
+     * that is hidden from enemies and out of range of every enemy attack
+     * next turn.
+     */
+    pub fn safe_tiles_for(&self, location: usize) -> HashSet<usize> {
+        let Some(unit) = self.units.get(&location) else {
+            return HashSet::new();
+        };
+
+        let allies = self.allies_of(unit.player);
+        let threats = self.threat_counts(
+            self.units
+                .iter()
+                .filter(|(_, other)| !allies.contains(&other.player))
+                .map(|(enemy_location, _)| *enemy_location),
+        );
+
+        self.hiding_spots_for(location)
+            .into_iter()
+            .filter(|tile| !threats.contains_key(tile))
+            .collect()
+    }
+}
+
+impl GameState {
+    /**
+     * For each of `player`'s units lists which enemy teams can see it and
+     * from where.
+     */
+    pub fn exposure_report(&self, player: usize) -> Vec<Exposure> {
+        self.exposure_report_with_config(player, &VisionConfig::default())
+    }
+
+    /**
+     * For each of `player`'s units lists which enemy teams can see it and
+     * from where under the rules given by `config`.
+     *
+     * A unit is exposed exactly when `unit_visible_to_with_config` is true
+     * for an enemy team. Enemy units are listed at their location while
+     * owned properties, revealed areas and fog of war being off are listed
+     * with the exposed unit's own location.
+     */
+    pub fn exposure_report_with_config(
+        &self,
+        player: usize,
+        config: &VisionConfig,
+    ) -> Vec<Exposure> {
+        let own_team = self.team_of(player);
+        let enemy_teams = (0..self.teams.len())
+            .filter(|team| Some(*team) != own_team)
+            .collect::<Vec<usize>>();
+        let fixed_vision = self
+            .fixed_vision(config)
+            .into_iter()
+            .collect::<HashSet<(usize, usize)>>();
+
+        self.units
+            .iter()
+            .filter(|(_, unit)| unit.player == player)
+            .map(|(location, _)| {
+                let mut observers = Vec::new();
+                if !self.settings.fog {
+                    observers.extend(enemy_teams.iter().map(|team| (*team, *location)));
+                } else {
+                    for (watcher, observer) in self.watchers_of_with_config(*location, config) {
+                        if let Some(team) = self.team_of(watcher) {
+                            if enemy_teams.contains(&team) {
+                                observers.push((team, observer));
+                            }
+                        }
+                    }
+                    observers.extend(
+                        enemy_teams
+                            .iter()
+                            .filter(|team| fixed_vision.contains(&(*location, **team)))
+                            .map(|team| (*team, *location)),
+                    );
+                }
+                observers.sort();
+                observers.dedup();
+
+                Exposure {
+                    location: *location,
+                    observers,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use crate::reveal::Reveal;
+    use crate::unit::UnitKind;
+    use crate::GameState;
+
+    use super::Exposure;
+
+    fn make_state() -> GameState {
+        // The Apc at 7 spots tile 6 while the artillery at 8 covers tiles 5
+        // and 6.
+        crate::game_state!("=0rec = = = = = = =1apc =1art =")
+    }
+
+    #[test]
+    fn hiding_spots_for_unspotted() {
+        assert_eq!(
+            [0, 1, 2, 3, 4, 5].into_iter().collect::<HashSet<usize>>(),
+            make_state().hiding_spots_for(0)
+        );
+    }
+
+    #[test]
+    fn safe_tiles_for_excludes_threats() {
+        assert_eq!(
+            [0, 1, 2, 3, 4].into_iter().collect::<HashSet<usize>>(),
+            make_state().safe_tiles_for(0)
+        );
+    }
+
+    #[test]
+    fn exposure_report_lists_observers() {
+        assert_eq!(
+            vec![Exposure {
+                location: 0,
+                observers: vec![],
+            }],
+            make_state().exposure_report(0)
+        );
+
+        let mut game_state = make_state();
+        let recon = game_state.units.remove(&0).unwrap();
+        game_state.units.insert(6, recon);
+
+        let report = game_state.exposure_report(0);
+        assert_eq!(vec![(1, 7)], report[0].observers);
+        assert_eq!(
+            [1].into_iter().collect::<BTreeSet<usize>>(),
+            report[0].teams()
+        );
+    }
+
+    #[test]
+    fn exposure_report_fixed_vision_and_fog_off() {
+        let mut game_state = crate::game_state!(".0inf c . . . . . . . .1inf");
+        assert!(game_state.exposure_report(0)[0].observers.is_empty());
+
+        game_state.add_reveal(Reveal {
+            player: Some(1),
+            center: 0,
+            radius: 0,
+        });
+        assert_eq!(vec![(1, 0)], game_state.exposure_report(0)[0].observers);
+
+        let mut game_state = crate::game_state!("c0inf . . . . .1inf");
+        game_state.set_owner(0, Some(1));
+        assert_eq!(vec![(1, 0)], game_state.exposure_report(0)[0].observers);
+
+        let mut game_state = crate::game_state!(".0inf . . . . . . . . .1inf");
+        game_state.settings.fog = false;
+        assert_eq!(vec![(1, 0)], game_state.exposure_report(0)[0].observers);
+        assert!(game_state.unit_visible_to(0, 1));
+    }
+
+    #[test]
+    fn hiding_spots_for_fixed_vision_and_fog_off() {
+        let mut game_state = crate::game_state!(".0inf . c . . . . . . .1inf");
+        assert_eq!(
+            [0, 1, 2, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state.hiding_spots_for(0)
+        );
+
+        // The enemy sees its own city.
+        game_state.set_owner(2, Some(1));
+        assert_eq!(
+            [0, 1, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state.hiding_spots_for(0)
+        );
+
+        game_state.add_reveal(Reveal {
+            player: Some(1),
+            center: 1,
+            radius: 2,
+        });
+        assert!(game_state.hiding_spots_for(0).is_empty());
+        assert!(game_state.safe_tiles_for(0).is_empty());
+
+        let mut game_state = crate::game_state!(".0inf . . . . . . . . .1inf");
+        game_state.settings.fog = false;
+        assert!(game_state.hiding_spots_for(0).is_empty());
+    }
+
+    #[test]
+    fn hidden_unit_candidates_in_fog_and_forests() {
+        let game_state = crate::game_state!(".0inf . f ~ ~ .1inf");
+        let config = crate::vision::VisionConfig::default();
+
+        let hidden = game_state.hidden_unit_candidates(0, 2, None, &config);
+        assert_eq!(2, hidden.unseen);
+        assert_eq!(
+            [2, 3, 4, 5].into_iter().collect::<HashSet<usize>>(),
+            hidden.candidates
+        );
+
+        let hidden = game_state.hidden_unit_candidates(0, 2, Some(&[UnitKind::Infantry]), &config);
+        assert_eq!(
+            [2, 5].into_iter().collect::<HashSet<usize>>(),
+            hidden.candidates
+        );
+
+        let hidden = game_state.hidden_unit_candidates(0, 0, None, &config);
+        assert_eq!(0, hidden.unseen);
+        assert!(hidden.candidates.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::data::OfficerData;
+use crate::map::TileKind;
+use crate::player::PlayerState;
+use crate::GameState;
+
+impl GameState {
+    /**
+     * Returns the funds `player` gains at the start of each of their turns:
+     * `funds_per_property` from the settings for each property they own,
+     * plus their officer's bonus. 0 if there is no such player.
+     *
+     * Communications Towers and Laboratories bring in nothing. Officers
+     * such as Colin and Kanbei change what units cost rather than income so
+     * are not accounted for here.
+     */
+    pub fn income(&self, player: usize) -> u32 {
+        let Some(PlayerState { officer, .. }) = self.players.get(player) else {
+            return 0;
+        };
+
+        let properties = self
+            .properties_of(player)
+            .into_iter()
+            .filter(|location| {
+                !matches!(
+                    self.map.cells().get(*location),
+                    Some(TileKind::CommunicationsTower | TileKind::Laboratory)
+                )
+            })
+            .count() as u32;
+
+        properties * (self.settings.funds_per_property + officer.income_bonus())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::builder::GameStateBuilder;
+    use crate::map::{CountryKind, Map, TileKind};
+    use crate::officer::{OfficerKind, PowerKind};
+
+    #[test]
+    fn income_counts_properties_and_officer() {
+        let game_state = GameStateBuilder::new(&Map::new(
+            vec![
+                TileKind::City,
+                TileKind::Base,
+                TileKind::CommunicationsTower,
+                TileKind::City,
+            ],
+            (4, 1),
+        ))
+        .player(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None)
+        .player(CountryKind::BlueMoon, OfficerKind::Sasha, PowerKind::None)
+        .team([0])
+        .team([1])
+        .owner(0, 0)
+        .owner(1, 0)
+        .owner(2, 0)
+        .owner(3, 1)
+        .build()
+        .unwrap();
+
+        assert_eq!(2000, game_state.income(0));
+        assert_eq!(1100, game_state.income(1));
+        assert_eq!(0, game_state.income(2));
+    }
+}
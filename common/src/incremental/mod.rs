@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::error::VisionError;
+use crate::vision::{Fixpoint, VisionConfig, VisionGrid};
+use crate::GameState;
+
+/**
+ * The result of driving an incremental computation one step.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Step<T> {
+    /** More work remains, call `poll` again. */
+    Pending,
+    Ready(T),
+}
+
+/**
+ * A long computation that can be advanced a little at a time so callers
+ * such as render loops can spread it across frames.
+ */
+pub trait Incremental {
+    type Output;
+
+    /**
+     * Does at most `budget` units of work (at least one) and returns the
+     * result once it is finished. Polling after the result was returned
+     * returns it again.
+     */
+    fn poll(&mut self, budget: usize) -> Step<Self::Output>;
+
+    /**
+     * Runs the computation to the end.
+     */
+    fn finish(mut self) -> Self::Output
+    where
+        Self: Sized,
+    {
+        loop {
+            if let Step::Ready(output) = self.poll(usize::MAX) {
+                return output;
+            }
+        }
+    }
+}
+
+/**
+ * Computes `try_common_vision_with_config` one unit's vision per unit of
+ * work, running the same `Fixpoint` pass as the whole computation.
+ */
+#[derive(Debug, Clone)]
+pub struct CommonVisionTask<'a> {
+    game_state: &'a GameState,
+    config: VisionConfig,
+    fixed_vision: Vec<(usize, usize)>,
+    fixpoint: Fixpoint,
+    iteration: usize,
+    /** Units still to be processed in the current pass. */
+    pending: Vec<usize>,
+    /** Which teams have seen each tile in the current pass. */
+    vision: VisionGrid,
+    result: Option<Result<HashSet<usize>, VisionError>>,
+}
+
+impl<'a> CommonVisionTask<'a> {
+    fn new(game_state: &'a GameState, config: &VisionConfig) -> CommonVisionTask<'a> {
+        let mut task = CommonVisionTask {
+            game_state,
+            config: config.clone(),
+            fixed_vision: game_state.fixed_vision(config),
+            fixpoint: Fixpoint::default(),
+            iteration: 0,
+            pending: Vec::new(),
+            vision: VisionGrid::new(game_state.map.dimensions(), game_state.teams.len()),
+            result: None,
+        };
+        if let Err(error) = game_state.check_structure() {
+            task.result = Some(Err(error.into()));
+        } else if game_state.settings.fog {
+            task.start_pass();
+        } else {
+            task.result = Some(Ok((0..game_state.map.len()).collect()));
+        }
+        task
+    }
+
+    fn start_pass(&mut self) {
+        if self.iteration == self.game_state.fixpoint_bound() {
+            // Algorithm is deterministic but avoid unbounded loops.
+            self.result = Some(Err(VisionError::FixpointBound {
+                iterations: self.iteration,
+            }));
+            return;
+        }
+
+        self.pending = self
+            .game_state
+            .units
+            .keys()
+            .rev()
+            .copied()
+            .filter(|location| self.fixpoint.counts(*location))
+            .collect();
+        self.vision.clear();
+        for (location, team) in self.fixed_vision.iter() {
+            self.vision.insert(*location, *team);
+        }
+    }
+
+    fn finish_pass(&mut self) {
+        self.iteration += 1;
+        let (removed_tiles, removed_units) =
+            self.fixpoint.hide_unseen(self.game_state, &self.vision);
+
+        if removed_tiles.is_empty() && removed_units.is_empty() {
+            self.result = Some(Ok(self.fixpoint.visible_tiles(self.game_state.map.len())));
+        } else {
+            self.start_pass();
+        }
+    }
+}
+
+impl Incremental for CommonVisionTask<'_> {
+    type Output = Result<HashSet<usize>, VisionError>;
+
+    fn poll(&mut self, budget: usize) -> Step<Result<HashSet<usize>, VisionError>> {
+        let mut budget = std::cmp::max(budget, 1);
+
+        while budget > 0 {
+            if let Some(result) = &self.result {
+                return Step::Ready(result.clone());
+            }
+            budget -= 1;
+
+            let Some(location) = self.pending.pop() else {
+                self.finish_pass();
+                continue;
+            };
+
+            let Some((player, tiles)) = self.game_state.vision_from_tiles(location, &self.config)
+            else {
+                continue;
+            };
+
+            let Some(team) = self.game_state.team_of(player) else {
+                continue;
+            };
+
+            for tile in tiles {
+                self.vision.insert(tile, team);
+            }
+        }
+
+        match &self.result {
+            Some(result) => Step::Ready(result.clone()),
+            None => Step::Pending,
+        }
+    }
+}
+
+/**
+ * Computes how many attackers threaten each tile one attacker per unit of
+ * work.
+ */
+#[derive(Debug, Clone)]
+pub struct ThreatTask<'a> {
+    game_state: &'a GameState,
+    /** Attackers still to be processed. */
+    pending: Vec<usize>,
+    counts: HashMap<usize, usize>,
+}
+
+impl Incremental for ThreatTask<'_> {
+    type Output = HashMap<usize, usize>;
+
+    fn poll(&mut self, budget: usize) -> Step<HashMap<usize, usize>> {
+        for _ in 0..std::cmp::max(budget, 1) {
+            let Some(attacker) = self.pending.pop() else {
+                break;
+            };
+
+            for tile in self.game_state.attackable_tiles(attacker) {
+                *self.counts.entry(tile).or_insert(0) += 1;
+            }
+        }
+
+        if self.pending.is_empty() {
+            Step::Ready(self.counts.clone())
+        } else {
+            Step::Pending
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns a task that computes `try_common_vision_with_config` a little
+     * at a time.
+     */
+    pub fn common_vision_task(&self, config: &VisionConfig) -> CommonVisionTask<'_> {
+        CommonVisionTask::new(self, config)
+    }
+
+    /**
+     * Returns a task that computes, for each tile, how many of the units at
+     * `attackers` could attack it next turn. Tiles no attacker can reach are
+     * omitted.
+     */
+    pub fn threat_task(&self, attackers: impl IntoIterator<Item = usize>) -> ThreatTask<'_> {
+        let mut pending = attackers.into_iter().collect::<Vec<usize>>();
+        pending.reverse();
+
+        ThreatTask {
+            game_state: self,
+            pending,
+            counts: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::builder::BuildError;
+    use crate::testing::two_versus_two;
+    use crate::unit::UnitKind;
+    use crate::UnitState;
+
+    #[test]
+    fn common_vision_task_matches() {
+        let game_state = crate::game_state!(
+            "
+            .0rec .   f   .   .1inf
+            .     f0inf . .   .
+            .1rec .   .   f   .0tank
+            "
+        );
+        let config = VisionConfig::default();
+
+        let mut task = game_state.common_vision_task(&config);
+        let mut polls = 1;
+        let result = loop {
+            match task.poll(1) {
+                Step::Ready(result) => break result,
+                Step::Pending => polls += 1,
+            }
+        };
+
+        assert!(polls > game_state.units.len());
+        assert_eq!(Ok(game_state.common_vision_with_config(&config)), result);
+        assert_eq!(Step::Ready(result), task.poll(1));
+    }
+
+    #[test]
+    fn common_vision_task_finish() {
+        let game_state = two_versus_two();
+        let config = VisionConfig::default();
+
+        assert_eq!(
+            Ok(game_state.common_vision_with_config(&config)),
+            game_state.common_vision_task(&config).finish()
+        );
+    }
+
+    #[test]
+    fn common_vision_task_rejects_invalid_states() {
+        let mut game_state = crate::game_state!(".0inf . .1inf");
+        game_state
+            .units
+            .insert(4, UnitState::new(0, false, UnitKind::Infantry));
+
+        let mut task = game_state.common_vision_task(&VisionConfig::default());
+        assert_eq!(
+            Step::Ready(Err(VisionError::InvalidState(
+                BuildError::UnitOutOfBounds { location: 4 }
+            ))),
+            task.poll(1)
+        );
+    }
+
+    #[test]
+    fn threat_task_matches() {
+        let game_state = two_versus_two();
+        let attackers = game_state.units.keys().copied().collect::<Vec<usize>>();
+
+        let mut task = game_state.threat_task(attackers.clone());
+        assert_eq!(Step::Pending, task.poll(1));
+        assert_eq!(game_state.threat_counts(attackers), task.finish());
+    }
+}
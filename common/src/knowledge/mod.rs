@@ -0,0 +1,440 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::vision::VisionConfig;
+use crate::{GameState, UnitState};
+
+/**
+ * A single sighting of an enemy unit.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Sighting {
+    /** The unit as it was when it was last seen. */
+    pub unit: UnitState,
+    /** The day the unit was last seen on. */
+    pub day: u32,
+}
+
+impl Sighting {
+    /**
+     * Returns how many days have passed since the unit was last seen.
+     */
+    pub fn staleness(&self, today: u32) -> u32 {
+        today.saturating_sub(self.day)
+    }
+}
+
+/**
+ * What a single player can see of the game under fog.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlayerView {
+    /** Index into players of who this view belongs to. */
+    pub player: usize,
+    /** The player and everyone they share vision with. */
+    pub allies: HashSet<usize>,
+    /** All of the tiles the player (or an ally) can see. */
+    pub visible_tiles: HashSet<usize>,
+    /** Every unit the player knows is currently on the map. */
+    pub units: BTreeMap<usize, UnitState>,
+    /** Enemy units that were seen previously but are no longer visible. */
+    pub ghosts: BTreeMap<usize, Sighting>,
+}
+
+impl PlayerView {
+    /**
+     * Adds the remembered enemy units from `knowledge` that are not on a
+     * currently visible tile.
+     */
+    pub fn include_ghosts(&mut self, knowledge: &Knowledge) {
+        for (location, sighting) in knowledge.sightings() {
+            if self.visible_tiles.contains(location) {
+                continue;
+            }
+
+            self.ghosts.insert(*location, sighting.clone());
+        }
+    }
+}
+
+/**
+ * Memory of where a player has last seen each enemy unit.
+ *
+ * Units do not carry an identity so a sighting is only forgotten once its
+ * tile is seen again, not when the same unit shows up elsewhere.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Knowledge {
+    /** Keyed by the location the unit was last seen at. */
+    last_seen: BTreeMap<usize, Sighting>,
+}
+
+impl Knowledge {
+    pub fn new() -> Knowledge {
+        Knowledge::default()
+    }
+
+    /**
+     * Updates the memory with everything visible in `view` on `day`.
+     */
+    pub fn observe(&mut self, view: &PlayerView, day: u32) {
+        self.last_seen
+            .retain(|location, _| !view.visible_tiles.contains(location));
+
+        for (location, unit) in view.units.iter() {
+            if view.allies.contains(&unit.player) {
+                continue;
+            }
+
+            self.last_seen.insert(
+                *location,
+                Sighting {
+                    unit: unit.clone(),
+                    day,
+                },
+            );
+        }
+    }
+
+    /**
+     * Returns every remembered enemy unit keyed by where it was last seen.
+     */
+    pub fn sightings(&self) -> &BTreeMap<usize, Sighting> {
+        &self.last_seen
+    }
+
+    /**
+     * Forgets any sighting older than `max_staleness` days.
+     */
+    pub fn forget_older_than(&mut self, today: u32, max_staleness: u32) {
+        self.last_seen
+            .retain(|_, sighting| sighting.staleness(today) <= max_staleness);
+    }
+}
+
+/**
+ * Memory of where each team last saw each enemy unit, built up by feeding
+ * it the states of a game in order, such as the turns of a replay.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FogMemory {
+    config: VisionConfig,
+    /** Indexed by team. */
+    teams: Vec<Knowledge>,
+}
+
+impl FogMemory {
+    pub fn new(config: &VisionConfig) -> FogMemory {
+        FogMemory {
+            config: config.clone(),
+            teams: Vec::new(),
+        }
+    }
+
+    /**
+     * Updates every team's memory with what it can see in `game_state`, on
+     * the state's day.
+     */
+    pub fn observe(&mut self, game_state: &GameState) {
+        if self.teams.len() < game_state.teams.len() {
+            self.teams
+                .resize_with(game_state.teams.len(), Knowledge::default);
+        }
+
+        for (team, players) in game_state.teams.iter().enumerate() {
+            let Some(player) = players.iter().min() else {
+                continue;
+            };
+
+            let view = game_state.player_view_with_config(*player, &self.config);
+            self.teams[team].observe(&view, game_state.day());
+        }
+    }
+
+    /**
+     * Returns what `team` remembers, or None if no state observed so far had
+     * such a team.
+     */
+    pub fn team(&self, team: usize) -> Option<&Knowledge> {
+        self.teams.get(team)
+    }
+
+    /**
+     * Forgets any sighting older than `max_staleness` days for every team.
+     */
+    pub fn forget_older_than(&mut self, today: u32, max_staleness: u32) {
+        for knowledge in self.teams.iter_mut() {
+            knowledge.forget_older_than(today, max_staleness);
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns every player that shares vision with `player` (including
+     * themself).
+     */
+    pub(crate) fn allies_of(&self, player: usize) -> HashSet<usize> {
+        self.teams
+            .iter()
+            .find(|team| team.contains(&player))
+            .cloned()
+            .unwrap_or_else(|| [player].into_iter().collect())
+    }
+
+    /**
+     * Projects the game onto what `player` can see using the default
+     * `VisionConfig`.
+     */
+    pub fn player_view(&self, player: usize) -> PlayerView {
+        self.player_view_with_config(player, &VisionConfig::default())
+    }
+
+    /**
+     * Projects the game onto what `player` can see under the rules given by
+     * `config`. Vision is shared with the rest of the player's team, so the
+     * tiles are the same as `visible_to_team_with_config` for their team,
+     * and none if they are on no team.
+     */
+    pub fn player_view_with_config(&self, player: usize, config: &VisionConfig) -> PlayerView {
+        let allies = self.allies_of(player);
+        let visible_tiles = self
+            .team_of(player)
+            .map(|team| self.visible_to_team_with_config(team, config))
+            .unwrap_or_default();
+
+        let units = self
+            .units
+            .iter()
+            .filter(|(location, unit)| {
+                allies.contains(&unit.player) || visible_tiles.contains(location)
+            })
+            .map(|(location, unit)| (*location, unit.clone()))
+            .collect();
+
+        PlayerView {
+            player,
+            allies,
+            visible_tiles,
+            units,
+            ghosts: BTreeMap::new(),
+        }
+    }
+
+    /**
+     * Returns every tile `player` or a teammate can currently see using the
+     * default `VisionConfig`.
+     */
+    pub fn visible_to_player(&self, player: usize) -> HashSet<usize> {
+        self.visible_to_player_with_config(player, &VisionConfig::default())
+    }
+
+    /**
+     * Returns every tile `player` or a teammate can currently see under the
+     * rules given by `config`.
+     */
+    pub fn visible_to_player_with_config(
+        &self,
+        player: usize,
+        config: &VisionConfig,
+    ) -> HashSet<usize> {
+        self.player_view_with_config(player, config).visible_tiles
+    }
+
+    /**
+     * Returns every unit `player` knows is on the map: their team's own
+     * units and every other unit on a tile they can see, using the default
+     * `VisionConfig`.
+     */
+    pub fn units_visible_to_player(&self, player: usize) -> BTreeMap<usize, UnitState> {
+        self.units_visible_to_player_with_config(player, &VisionConfig::default())
+    }
+
+    /**
+     * Returns every unit `player` knows is on the map under the rules given
+     * by `config`.
+     */
+    pub fn units_visible_to_player_with_config(
+        &self,
+        player: usize,
+        config: &VisionConfig,
+    ) -> BTreeMap<usize, UnitState> {
+        self.player_view_with_config(player, config).units
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::reveal::Reveal;
+    use crate::unit::UnitKind;
+
+    #[test]
+    fn player_view_filters_units() {
+        let view = game_state!(".0inf . .1tank . . . . .").player_view(0);
+        assert_eq!(vec![0, 2], view.units.keys().cloned().collect::<Vec<_>>());
+
+        let view = game_state!(".0inf . . . . .1tank . .").player_view(0);
+        assert_eq!(vec![0], view.units.keys().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn visible_to_player_shares_with_team() {
+        let game_state = game_state!(
+            "
+            teams: 0,1 2
+            .0inf . . . . . .1inf . . . .2inf
+            "
+        );
+
+        assert_eq!(
+            [0, 1, 2, 4, 5, 6, 7, 8]
+                .into_iter()
+                .collect::<HashSet<usize>>(),
+            game_state.visible_to_player(0)
+        );
+        assert_eq!(
+            game_state.visible_to_player(0),
+            game_state.visible_to_player(1)
+        );
+        assert_eq!(
+            vec![0, 6],
+            game_state
+                .units_visible_to_player(1)
+                .into_keys()
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            vec![10],
+            game_state
+                .units_visible_to_player(2)
+                .into_keys()
+                .collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn player_view_matches_team_vision() {
+        let mut game_state = game_state!(".0inf . . . . c . . . .1inf . .");
+        game_state.set_owner(5, Some(0));
+        game_state.add_reveal(Reveal {
+            player: Some(0),
+            center: 9,
+            radius: 1,
+        });
+
+        let view = game_state.player_view(0);
+        assert_eq!(game_state.visible_to_team(0), view.visible_tiles);
+        assert!(view.visible_tiles.contains(&5));
+        assert_eq!(vec![0, 9], view.units.into_keys().collect::<Vec<usize>>());
+
+        game_state.settings.fog = false;
+        assert_eq!(
+            game_state.visible_to_team(0),
+            game_state.visible_to_player(0)
+        );
+        assert_eq!(
+            vec![0, 9],
+            game_state
+                .units_visible_to_player(0)
+                .into_keys()
+                .collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn ghosts_forgotten_when_tile_seen() {
+        let mut knowledge = Knowledge::new();
+        knowledge.observe(&game_state!(".0inf . .1tank . . . . .").player_view(0), 1);
+
+        let mut view = game_state!(".0inf . . . . .1tank . .").player_view(0);
+        knowledge.observe(&view, 3);
+        view.include_ghosts(&knowledge);
+
+        // Tile 2 is still visible so the tank is known to have left it.
+        assert!(view.ghosts.is_empty());
+    }
+
+    #[test]
+    fn ghosts_remain_after_leaving_vision() {
+        let mut knowledge = Knowledge::new();
+        knowledge.observe(&game_state!(".0inf . .1tank . . . . .").player_view(0), 1);
+
+        let mut view = game_state!(". . .1tank . . . . .0inf").player_view(0);
+        knowledge.observe(&view, 3);
+        view.include_ghosts(&knowledge);
+
+        let ghost = view.ghosts.get(&2).expect("Tank should be remembered");
+        assert_eq!(UnitKind::Tank, ghost.unit.kind);
+        assert_eq!(2, ghost.staleness(3));
+
+        knowledge.forget_older_than(3, 1);
+        assert!(knowledge.sightings().is_empty());
+    }
+
+    #[test]
+    fn fog_memory_remembers_last_sighting() {
+        let mut memory = FogMemory::new(&VisionConfig::default());
+
+        let mut game_state = game_state!(". . .0inf . .1tank . . .");
+        memory.observe(&game_state);
+        assert_eq!(
+            vec![&4],
+            memory
+                .team(0)
+                .unwrap()
+                .sightings()
+                .keys()
+                .collect::<Vec<_>>()
+        );
+
+        game_state.end_turn();
+        game_state.end_turn();
+        game_state.relocate_unit(2, 0).unwrap();
+        memory.observe(&game_state);
+
+        let sighting = &memory.team(0).unwrap().sightings()[&4];
+        assert_eq!(1, sighting.day);
+        assert_eq!(1, sighting.staleness(game_state.day()));
+        assert!(memory.team(1).unwrap().sightings().is_empty());
+        assert!(memory.team(2).is_none());
+
+        memory.forget_older_than(game_state.day(), 0);
+        assert!(memory.team(0).unwrap().sightings().is_empty());
+    }
+
+    #[test]
+    fn fog_memory_sees_reveals_and_fog_off() {
+        let mut game_state = game_state!(".0inf . . . . . .1inf . .2inf");
+        game_state.add_reveal(Reveal {
+            player: Some(0),
+            center: 5,
+            radius: 1,
+        });
+
+        let mut memory = FogMemory::new(&VisionConfig::default());
+        memory.observe(&game_state);
+        assert_eq!(
+            vec![&6],
+            memory
+                .team(0)
+                .unwrap()
+                .sightings()
+                .keys()
+                .collect::<Vec<_>>()
+        );
+
+        game_state.settings.fog = false;
+        memory.observe(&game_state);
+        assert_eq!(
+            vec![&6, &8],
+            memory
+                .team(0)
+                .unwrap()
+                .sightings()
+                .keys()
+                .collect::<Vec<_>>()
+        );
+    }
+}
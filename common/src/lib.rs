@@ -1,57 +1,434 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use map::{CountryKind, TileKind};
+extern crate alloc;
 
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("common needs either the `std` or the `alloc` feature");
+
+use prelude::*;
+
+pub use data::{OfficerData, TileData, UnitData};
+use grid::Grid;
+use map::{Map, TileKind};
 use officer::{OfficerKind, PowerKind};
-use unit::UnitKind;
 
+use player::PlayerState;
+use reveal::Reveal;
+use ruleset::Ruleset;
+use settings::GameSettings;
+use unit::{UnitKind, MAX_HP};
+use vision::{Fixpoint, FixpointDiagnostics, VisionConfig, VisionGrid};
+use weather::Weather;
+
+#[cfg(feature = "std")]
+pub mod action;
+#[cfg(feature = "std")]
+pub mod ascii;
+#[cfg(feature = "std")]
+pub mod balance;
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "std")]
+pub mod canonical;
+#[cfg(feature = "std")]
+pub mod capture;
+pub mod cargo;
+#[cfg(feature = "std")]
+pub mod chokepoint;
+#[cfg(feature = "std")]
+pub mod contact;
+pub mod coordinate;
+pub mod data;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod gen;
+#[cfg(feature = "std")]
+pub mod golden;
+pub mod grid;
+#[cfg(feature = "std")]
+pub mod hiding;
+#[cfg(feature = "std")]
+pub mod income;
+#[cfg(feature = "std")]
+pub mod incremental;
+#[cfg(feature = "std")]
+pub mod knowledge;
+#[cfg(feature = "std")]
+pub mod lint;
 pub mod map;
+pub mod mask;
+#[cfg(feature = "std")]
+pub mod movement;
+#[cfg(feature = "std")]
+pub mod naval;
 pub mod officer;
+pub mod ownership;
+#[cfg(feature = "std")]
+pub mod path;
+#[cfg(feature = "std")]
+pub mod pipeline;
+pub mod placement;
+pub mod player;
+#[cfg(feature = "std")]
+pub mod pool;
+mod prelude;
+#[cfg(feature = "std")]
+pub mod production;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod report;
+pub mod reveal;
+pub mod ruleset;
+#[cfg(feature = "std")]
+pub mod scouting;
+pub mod settings;
+pub mod sorted;
+#[cfg(feature = "std")]
+pub mod stealth;
+#[cfg(any(all(test, feature = "std"), feature = "proptest"))]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod teams;
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod threat;
+#[cfg(feature = "std")]
+pub mod tracker;
+pub mod turn;
 pub mod unit;
+pub mod validate;
+pub mod vision;
+pub mod weather;
+pub mod zobrist;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnitState {
     /** Index into players of who owns the units. */
     player: usize,
     /** If true then only adjacent units can reveal it. */
     stealthed: bool,
     kind: UnitKind,
+    /** Hit points as displayed in game, from 1 to `MAX_HP`. */
+    #[cfg_attr(feature = "serde", serde(default = "unit::max_hp"))]
+    hp: u8,
+    /** Fuel left, or None if the unit has as much as it is built with. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    fuel: Option<u8>,
+    /** Ammo left, or None if the unit has as much as it is built with. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    ammo: Option<u8>,
+    /** Units being carried, which can neither see nor be seen. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    cargo: Vec<UnitState>,
 }
 
 impl UnitState {
-    fn new(player: usize, stealthed: bool, kind: UnitKind) -> UnitState {
+    /**
+     * Returns a unit at full health with full fuel and ammo.
+     */
+    pub fn new(player: usize, stealthed: bool, kind: UnitKind) -> UnitState {
         UnitState {
             player,
             stealthed,
             kind,
+            hp: MAX_HP,
+            fuel: None,
+            ammo: None,
+            cargo: Vec::new(),
         }
     }
+
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    pub fn stealthed(&self) -> bool {
+        self.stealthed
+    }
+
+    pub fn kind(&self) -> &UnitKind {
+        &self.kind
+    }
+
+    pub fn hp(&self) -> u8 {
+        self.hp
+    }
+
+    pub fn fuel(&self) -> u8 {
+        self.fuel.unwrap_or_else(|| self.kind.max_fuel())
+    }
+
+    pub fn ammo(&self) -> u8 {
+        self.ammo.unwrap_or_else(|| self.kind.max_ammo())
+    }
+
+    /**
+     * Sets the hit points, capped at `MAX_HP`.
+     */
+    pub fn set_hp(&mut self, hp: u8) {
+        self.hp = core::cmp::min(hp, MAX_HP);
+    }
+
+    /**
+     * Sets the fuel left, capped at what the unit is built with.
+     */
+    pub fn set_fuel(&mut self, fuel: u8) {
+        self.fuel = Some(fuel).filter(|fuel| *fuel < self.kind.max_fuel());
+    }
+
+    /**
+     * Sets the ammo left, capped at what the unit is built with.
+     */
+    pub fn set_ammo(&mut self, ammo: u8) {
+        self.ammo = Some(ammo).filter(|ammo| *ammo < self.kind.max_ammo());
+    }
+
+    pub fn with_hp(mut self, hp: u8) -> UnitState {
+        self.set_hp(hp);
+        self
+    }
+
+    pub fn with_fuel(mut self, fuel: u8) -> UnitState {
+        self.set_fuel(fuel);
+        self
+    }
+
+    pub fn with_ammo(mut self, ammo: u8) -> UnitState {
+        self.set_ammo(ammo);
+        self
+    }
+
+    /**
+     * Returns the units being carried in the order they were loaded.
+     */
+    pub fn cargo(&self) -> &[UnitState] {
+        &self.cargo
+    }
+
+    /**
+     * Sets the units being carried without checking the unit can carry
+     * them, see `GameState::load` for a checked version.
+     */
+    pub fn with_cargo(mut self, cargo: Vec<UnitState>) -> UnitState {
+        self.cargo = cargo;
+        self
+    }
 }
 
+/**
+ * A snapshot of a game: the terrain, every unit and who is playing with
+ * whom.
+ *
+ * With the `serde` feature enabled a state serializes as a struct with the
+ * fields below, in this order, and every enum as its variant name. This
+ * layout is kept stable between releases:
+ *
+ * - `map`: list of `TileKind` row by row from the top left
+ * - `map_dimensions`: `[width, height]`
+ * - `units`: map from tile index to `{ player, stealthed, kind, hp, fuel,
+ *   ammo, cargo }` where `hp` is `MAX_HP` if missing, `fuel` and `ammo` are
+ *   null if the unit has as much as it is built with and `cargo` is a list
+ *   of units in the same layout, empty if missing
+ * - `owners`: map from tile index to the player owning the property there,
+ *   empty if missing
+ * - `players`: list of `{ country, officer, power, charge, powers_used }`
+ *   where `charge` and `powers_used` are 0 if missing, lists of
+ *   `[CountryKind, OfficerKind, PowerKind]` from before power meters are
+ *   read the same way
+ * - `teams`: list of lists of player indices
+ * - `weather`: `Weather`, `Clear` if missing
+ * - `settings`: `{ fog, starting_funds, funds_per_property, banned_officers,
+ *   banned_units, weather }`, AWBW's fog league if missing
+ * - `reveals`: list of `{ player, center, radius }`, empty if missing
+ * - `ruleset`: `Ruleset`, `Awbw` if missing
+ * - `day`: the day starting from 1, 1 if missing
+ * - `active_player`: index of the player whose turn it is, 0 if missing
+ *
+ * Deserializing does not check the state is consistent.
+ */
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
-    /** 1D Vec of the map starting from the top left. */
-    map: Vec<TileKind>,
-    /** The (width, height) of the map. */
-    map_dimensions: (usize, usize),
+    /** The terrain, indexed by tile the same as everything else. Written
+     * as the `map` and `map_dimensions` fields. */
+    #[cfg_attr(feature = "serde", serde(flatten, with = "terrain_fields"))]
+    map: Grid<TileKind>,
 
     /** BTreeMap storing for at a given index in `map` what unit is stored
      * there. */
     units: BTreeMap<usize, UnitState>,
+    /** For each owned property which player (index) owns it. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    owners: BTreeMap<usize, usize>,
 
-    players: Vec<(CountryKind, OfficerKind, PowerKind)>,
+    players: Vec<PlayerState>,
     teams: Vec<HashSet<usize>>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    weather: Weather,
+    /** When fog of war is off every team sees the whole map. */
+    #[cfg_attr(feature = "serde", serde(default = "GameSettings::fog_league"))]
+    settings: GameSettings,
+    /** Areas revealed for the rest of the turn, see `Reveal`. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    reveals: Vec<Reveal>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    ruleset: Ruleset,
+    #[cfg_attr(feature = "serde", serde(default = "turn::first_day"))]
+    day: u32,
+    /** Index into players of whose turn it is. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    active_player: usize,
+}
+
+/**
+ * Reads and writes the terrain of a `GameState` as the flat `map` and
+ * `map_dimensions` fields, failing to read tiles that do not fill the
+ * dimensions.
+ */
+#[cfg(feature = "serde")]
+mod terrain_fields {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::builder::BuildError;
+    use crate::grid::Grid;
+    use crate::map::TileKind;
+
+    #[derive(Serialize)]
+    struct TerrainRef<'a> {
+        map: &'a [TileKind],
+        map_dimensions: (usize, usize),
+    }
+
+    #[derive(Deserialize)]
+    struct Terrain {
+        map: Vec<TileKind>,
+        map_dimensions: (usize, usize),
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        map: &Grid<TileKind>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        TerrainRef {
+            map: map.cells(),
+            map_dimensions: map.dimensions(),
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Grid<TileKind>, D::Error> {
+        let terrain = Terrain::deserialize(deserializer)?;
+        let tiles = terrain.map.len();
+        Grid::new(terrain.map, terrain.map_dimensions).ok_or_else(|| {
+            serde::de::Error::custom(BuildError::DimensionsMismatch {
+                tiles,
+                dimensions: terrain.map_dimensions,
+            })
+        })
+    }
 }
 
 impl GameState {
+    /**
+     * Returns a state with the terrain of `map` and nothing on it.
+     *
+     * Tiles missing from a map that does not fill its dimensions are Void
+     * and any extra are ignored.
+     */
+    #[cfg(feature = "std")]
+    pub(crate) fn from_map(map: &Map) -> GameState {
+        GameState {
+            map: map.to_grid().unwrap_or_else(|| {
+                Grid::from_fn(map.dimensions(), |position| {
+                    position
+                        .to_index(map.dimensions())
+                        .and_then(|index| map.tiles().get(index))
+                        .cloned()
+                        .unwrap_or(TileKind::Void)
+                })
+            }),
+            units: BTreeMap::new(),
+            players: Vec::new(),
+            teams: Vec::new(),
+            weather: Weather::Clear,
+            settings: GameSettings::fog_league(),
+            owners: BTreeMap::new(),
+            reveals: Vec::new(),
+            ruleset: Ruleset::Awbw,
+            day: 1,
+            active_player: 0,
+        }
+    }
+
+    /**
+     * Returns the index into teams of the team `player` is on.
+     */
+    pub(crate) fn team_of(&self, player: usize) -> Option<usize> {
+        self.teams.iter().position(|team| team.contains(&player))
+    }
+
+    /**
+     * Returns the terrain of the state.
+     */
+    pub fn terrain(&self) -> Map {
+        Map::from(self.map.clone())
+    }
+
+    /**
+     * Returns the (width, height) of the map without copying the terrain.
+     */
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.map.dimensions()
+    }
+
+    /**
+     * For a given location returns all of the tiles within a certain
+     * distance of that tile, or None if the location is not on the map.
+     */
+    pub fn checked_neighbors(&self, location: usize, distance: usize) -> Option<HashSet<usize>> {
+        if location >= self.map.len() {
+            return None;
+        }
+
+        Some(self.neighbors(location, distance))
+    }
+
+    /**
+     * Returns how many steps apart two tiles are.
+     */
+    pub(crate) fn distance(&self, a: usize, b: usize) -> usize {
+        let width = self.map.dimensions().0.max(1);
+        let (ax, ay) = (a % width, a / width);
+        let (bx, by) = (b % width, b / width);
+
+        ax.abs_diff(bx) + ay.abs_diff(by)
+    }
+
     /**
      * For a given location returns all of the tiles within a certain
      * distance of that tile.
+     *
+     * An out of bounds location is treated as if the map extended past its
+     * edges so can still have neighbors on the map, see
+     * `checked_neighbors` to reject it instead.
      */
     fn neighbors(&self, location: usize, distance: usize) -> HashSet<usize> {
-        use std::cmp::{max, min};
+        use core::cmp::{max, min};
 
-        let (width, height) = self.map_dimensions;
+        let (width, height) = self.map.dimensions();
         let mut neighbors = HashSet::new();
 
         let (x, y) = (location % width, location / width);
@@ -74,34 +451,115 @@ impl GameState {
         neighbors
     }
 
+    /**
+     * Returns how far `unit` can see from `location` and whether it can see
+     * into tiles that would otherwise hide units.
+     */
+    fn vision_range(&self, location: usize, unit: &UnitState, config: &VisionConfig) -> (u8, bool) {
+        let (owner_vision, forests_revealed) = match self.players.get(unit.player) {
+            Some(player) => {
+                let mut bonus = self
+                    .ruleset
+                    .officer_vision_bonus(&player.officer, &player.power);
+                if player.officer == OfficerKind::Sonja && !config.sonja_day_to_day_vision {
+                    bonus = bonus.saturating_sub(
+                        self.ruleset
+                            .officer_vision_bonus(&player.officer, &PowerKind::None),
+                    );
+                }
+                (bonus, player.officer.reveals_hidden(&player.power))
+            }
+            None => (0, false),
+        };
+
+        let terrain_vision = self
+            .map
+            .cells()
+            .get(location)
+            .filter(|_| config.terrain_vision_bonus)
+            .map(|tile| self.ruleset.terrain_vision_bonus(&unit.kind, tile))
+            .unwrap_or(0);
+
+        let vision = (unit.kind.vision() + owner_vision + terrain_vision)
+            .saturating_sub(self.weather.vision_penalty());
+
+        (core::cmp::max(vision, 1), forests_revealed)
+    }
+
     /**
      * For a given location returns all of the tiles that are revealed by a
      * unit on that tile and which player (index) owns that unit.
      *
      * Returns None if no unit is on the tile.
      */
-    // TODO: Player-owned buildings give vision of thier own tile
-    fn vision_from_tiles(&self, location: usize) -> Option<(usize, HashSet<usize>)> {
-        let Some(unit) = self.units.get(&location) else {
-            return None;
-        };
+    fn vision_from_tiles(
+        &self,
+        location: usize,
+        config: &VisionConfig,
+    ) -> Option<(usize, HashSet<usize>)> {
+        let unit = self.units.get(&location)?;
+        Some((unit.player, self.vision_of(location, unit, config)?))
+    }
 
-        let (owner_vision, forests_revealed) = match self.players.get(unit.player) {
-            Some((_, OfficerKind::Sonja, PowerKind::Super)) => (2, true),
-            Some((_, OfficerKind::Sonja, PowerKind::Normal)) => (2, true),
-            Some((_, OfficerKind::Sonja, PowerKind::None)) => (1, false),
-            _ => (0, false),
-        };
+    /**
+     * Returns every tile `unit` would reveal if it stood on `location`,
+     * whether or not it is actually there.
+     *
+     * Returns None if `location` is off the map.
+     */
+    pub(crate) fn vision_of(
+        &self,
+        location: usize,
+        unit: &UnitState,
+        config: &VisionConfig,
+    ) -> Option<HashSet<usize>> {
+        let (vision_range, forests_revealed) = self.vision_range(location, unit, config);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "unit_vision",
+            location,
+            player = unit.player,
+            kind = ?unit.kind,
+            vision_range,
+            forests_revealed
+        )
+        .entered();
+
+        // Units off the map see nothing.
+        let adjacent_locations = self.checked_neighbors(location, 1)?;
+        let mut revealed_locations = HashSet::new();
+
+        for neighbor in self.neighbors(location, core::cmp::max(vision_range as usize, 1)) {
+            if neighbor == location {
+                // A unit can always see its own tile.
+                revealed_locations.insert(neighbor);
+                continue;
+            }
 
-        let vision_range = unit.kind.vision() + owner_vision;
+            if self.map.cells().get(neighbor) == Some(&TileKind::Void) {
+                // Nothing can be seen on out of play tiles.
+                continue;
+            }
 
-        // Always reveal adjancent tiles (even if forest / stealthed)
-        let mut revealed_locations = self.neighbors(location, 1);
+            let neighbor_unit = self.units.get(&neighbor);
+
+            // Typically adjancent tiles are revealed (even if forest / stealthed)
+            let adjacency_reveals = config.adjacency_reveals_hidden
+                && adjacent_locations.contains(&neighbor)
+                && !(config.dived_revealed_by_sea_only
+                    && !unit.kind.is_naval()
+                    && neighbor_unit
+                        .map(|unit_state| {
+                            unit_state.stealthed && unit_state.kind == UnitKind::Submarine
+                        })
+                        .unwrap_or(false));
+
+            if adjacency_reveals {
+                revealed_locations.insert(neighbor);
+                continue;
+            }
 
-        for neighbor in self.neighbors(location, vision_range as usize) {
-            if self
-                .units
-                .get(&neighbor)
+            if neighbor_unit
                 .map(|unit_state| unit_state.stealthed)
                 .unwrap_or(false)
             {
@@ -111,8 +569,9 @@ impl GameState {
 
             if self
                 .map
+                .cells()
                 .get(neighbor)
-                .map(|tile| tile.hides_units())
+                .map(|tile| config.hides_units(tile))
                 .unwrap_or(false)
                 && !forests_revealed
             {
@@ -123,104 +582,334 @@ impl GameState {
             revealed_locations.insert(neighbor);
         }
 
-        Some((unit.player.clone(), revealed_locations))
+        #[cfg(feature = "tracing")]
+        tracing::trace!(tiles = revealed_locations.len(), "unit vision computed");
+        Some(revealed_locations)
     }
 
     /**
-     * Returns a list containing for each team all of the locations that can
-     * see the tile.
+     * Returns the player (index) owning the unit at `location` and every
+     * tile it sees on its own using the default `VisionConfig`.
+     *
+     * Returns None if there is no unit at `location` or it is off the map.
      */
-    fn vision_for_units(&self, units: &BTreeMap<usize, UnitState>) -> Vec<Vec<HashSet<usize>>> {
-        let player_to_team_map = {
-            let mut map = HashMap::new();
-            for (index, team) in self.teams.iter().enumerate() {
-                for player in team.iter() {
-                    map.insert(player.clone(), index);
-                }
-            }
-            map
+    pub fn unit_vision(&self, location: usize) -> Option<(usize, HashSet<usize>)> {
+        self.unit_vision_with_config(location, &VisionConfig::default())
+    }
+
+    /**
+     * Returns the player (index) owning the unit at `location` and every
+     * tile it sees on its own under the rules given by `config`.
+     *
+     * This ignores fog being off, owned properties and revealed areas and
+     * whether the unit itself is commonly visible.
+     */
+    pub fn unit_vision_with_config(
+        &self,
+        location: usize,
+        config: &VisionConfig,
+    ) -> Option<(usize, HashSet<usize>)> {
+        self.vision_from_tiles(location, config)
+    }
+
+    /**
+     * Returns each (player, unit location) whose unit sees `tile` using the
+     * default `VisionConfig`, ordered by location.
+     */
+    pub fn watchers_of(&self, tile: usize) -> Vec<(usize, usize)> {
+        self.watchers_of_with_config(tile, &VisionConfig::default())
+    }
+
+    /**
+     * Returns each (player, unit location) whose unit sees `tile` under the
+     * rules given by `config`, ordered by location.
+     *
+     * Only units whose vision range reaches `tile` are scanned. Owned
+     * properties and revealed areas are not units so are not listed.
+     */
+    pub fn watchers_of_with_config(
+        &self,
+        tile: usize,
+        config: &VisionConfig,
+    ) -> Vec<(usize, usize)> {
+        if tile >= self.map.len() {
+            return Vec::new();
+        }
+
+        self.units
+            .iter()
+            .filter(|(location, unit)| {
+                self.distance(**location, tile)
+                    <= self.vision_range(**location, unit, config).0 as usize
+            })
+            .filter_map(|(location, _)| {
+                let (player, tiles) = self.vision_from_tiles(*location, config)?;
+                tiles.contains(&tile).then_some((player, *location))
+            })
+            .collect()
+    }
+
+    /**
+     * Returns whether `team` can see the unit at `unit_location` using the
+     * default `VisionConfig`.
+     */
+    pub fn unit_visible_to(&self, unit_location: usize, team: usize) -> bool {
+        self.unit_visible_to_with_config(unit_location, team, &VisionConfig::default())
+    }
+
+    /**
+     * Returns whether `team` can see the unit at `unit_location` under the
+     * rules given by `config`, so forests, reefs, stealth and adjacency are
+     * all accounted for. A team always sees its own units.
+     *
+     * False if there is no unit there or no such team.
+     */
+    pub fn unit_visible_to_with_config(
+        &self,
+        unit_location: usize,
+        team: usize,
+        config: &VisionConfig,
+    ) -> bool {
+        let Some(unit) = self.units.get(&unit_location) else {
+            return false;
         };
+        if team >= self.teams.len() {
+            return false;
+        }
+        if !self.settings.fog || self.team_of(unit.player) == Some(team) {
+            return true;
+        }
+
+        self.fixed_vision(config).contains(&(unit_location, team))
+            || self
+                .watchers_of_with_config(unit_location, config)
+                .into_iter()
+                .any(|(player, _)| self.team_of(player) == Some(team))
+    }
 
-        let mut empty_watchers = Vec::with_capacity(self.teams.len());
-        for _ in 0..self.teams.len() {
-            empty_watchers.push(HashSet::new());
+    /**
+     * Returns `vision_from_tiles` for each unit at `locations` that can see,
+     * computed on rayon's thread pool with the `parallel` feature.
+     */
+    fn unit_visions(
+        &self,
+        locations: impl Iterator<Item = usize>,
+        config: &VisionConfig,
+    ) -> Vec<(usize, HashSet<usize>)> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            locations
+                .collect::<Vec<usize>>()
+                .into_par_iter()
+                .filter_map(|location| self.vision_from_tiles(location, config))
+                .collect()
         }
 
-        let mut vision_data = Vec::with_capacity(self.map.len());
-        for _ in 0..self.map.len() {
-            vision_data.push(empty_watchers.clone());
+        #[cfg(not(feature = "parallel"))]
+        {
+            locations
+                .filter_map(|location| self.vision_from_tiles(location, config))
+                .collect()
         }
+    }
 
-        for (location, _) in units.iter() {
-            let Some((player, tiles)) = self.vision_from_tiles(location.clone()) else {
+    /**
+     * Marks in `vision` every tile the units at `locations` let their team
+     * see, along with `fixed_vision`.
+     */
+    fn vision_for_units(
+        &self,
+        locations: impl Iterator<Item = usize>,
+        fixed_vision: &[(usize, usize)],
+        config: &VisionConfig,
+        vision: &mut VisionGrid,
+    ) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("vision_for_units").entered();
+
+        for (player, tiles) in self.unit_visions(locations, config) {
+            let Some(team) = self.team_of(player) else {
                 continue;
-           };
-
-            let Some(team) = player_to_team_map.get(&player) else {
-            continue;
-           };
+            };
 
             for tile in tiles {
-                vision_data
-                    .get_mut(tile)
-                    .expect("Tile was not in vision_state")
-                    .get_mut(team.clone())
-                    .expect("Team was not in watchers")
-                    .insert(tile);
+                vision.insert(tile, team);
             }
         }
 
-        vision_data
+        for (location, team) in fixed_vision {
+            vision.insert(*location, *team);
+        }
     }
 
     /**
-     * Computes all of the tiles that are commonly visible to all players
+     * Returns every tile any unit on `team` can see using the default
+     * `VisionConfig`.
      */
-    pub fn common_vision(&self) -> HashSet<usize> {
-        let mut visible_units = self.units.clone();
-        let mut visible_tiles = self
-            .map
-            .iter()
-            .enumerate()
-            .map(|(index, _)| index)
-            .collect::<HashSet<usize>>();
-
-        for counter in 0..=self.units.len() {
-            if counter == self.units.len() {
-                // Algorithm is deterministic but avoid unbounded loops.
-                return HashSet::new();
-            }
+    pub fn visible_to_team(&self, team: usize) -> HashSet<usize> {
+        self.visible_to_team_with_config(team, &VisionConfig::default())
+    }
 
-            let mut vision_changed = false;
+    /**
+     * Returns every tile any unit on `team` can see under the rules given
+     * by `config`. Empty if there is no such team.
+     */
+    pub fn visible_to_team_with_config(
+        &self,
+        team: usize,
+        config: &VisionConfig,
+    ) -> HashSet<usize> {
+        if team >= self.teams.len() {
+            return HashSet::new();
+        }
+        if !self.settings.fog {
+            return (0..self.map.len()).collect();
+        }
 
-            for (location, teams) in self
-                .vision_for_units(&visible_units)
-                .into_iter()
-                .enumerate()
-            {
-                let num_teams_with_vision =
-                    teams.into_iter().filter(|units| !units.is_empty()).count();
+        self.vision_grid_with_config(config)
+            .tiles_seen_by(team)
+            .collect()
+    }
 
-                if num_teams_with_vision != self.teams.len() {
-                    vision_changed = vision_changed
-                        || visible_units.remove(&location).is_some()
-                        || visible_tiles.remove(&location);
+    /**
+     * Returns every tile at least one team can see using the default
+     * `VisionConfig`.
+     */
+    pub fn any_vision(&self) -> HashSet<usize> {
+        self.any_vision_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Returns every tile at least one team can see under the rules given by
+     * `config`, which is what a spectator can tell apart from fog.
+     */
+    pub fn any_vision_with_config(&self, config: &VisionConfig) -> HashSet<usize> {
+        if !self.settings.fog {
+            return (0..self.map.len()).collect();
+        }
+
+        let vision = self.vision_grid_with_config(config);
+        (0..self.map.len())
+            .filter(|location| vision.teams_seeing(*location) > 0)
+            .collect()
+    }
+
+    /**
+     * Returns which teams see each tile using the default `VisionConfig`.
+     */
+    pub fn vision_grid(&self) -> VisionGrid {
+        self.vision_grid_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Returns which teams see each tile from every unit, owned property and
+     * revealed area under the rules given by `config`. Unlike common vision
+     * no units are ruled out for being unseen. With fog of war off every
+     * team sees every tile.
+     */
+    pub fn vision_grid_with_config(&self, config: &VisionConfig) -> VisionGrid {
+        let mut vision = VisionGrid::new(self.map.dimensions(), self.teams.len());
+        if !self.settings.fog {
+            for tile in 0..self.map.len() {
+                for team in 0..self.teams.len() {
+                    vision.insert(tile, team);
                 }
             }
+            return vision;
+        }
 
-            if !vision_changed {
-                break;
-            }
+        self.vision_for_units(
+            self.units.keys().copied(),
+            &self.fixed_vision(config),
+            config,
+            &mut vision,
+        );
+        vision
+    }
+
+    /**
+     * Computes all of the tiles that are commonly visible to all players
+     * using the default `VisionConfig`.
+     */
+    pub fn common_vision(&self) -> HashSet<usize> {
+        self.common_vision_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Computes all of the tiles that are commonly visible to all players
+     * under the rules given by `config`.
+     *
+     * Empty if the fixed point does not settle, see
+     * `try_common_vision_with_config` to tell that apart.
+     */
+    pub fn common_vision_with_config(&self, config: &VisionConfig) -> HashSet<usize> {
+        self.common_vision_with_diagnostics(config).0
+    }
+
+    /**
+     * Computes the same result as `common_vision_with_config` along with
+     * how the fixed point got there.
+     */
+    pub fn common_vision_with_diagnostics(
+        &self,
+        config: &VisionConfig,
+    ) -> (HashSet<usize>, FixpointDiagnostics) {
+        if !self.settings.fog {
+            return (
+                (0..self.map.len()).collect(),
+                FixpointDiagnostics::default(),
+            );
         }
 
-        visible_tiles
+        let (fixpoint, diagnostics) = self.common_vision_fixpoint(config);
+        let visible_tiles = fixpoint.map_or_else(HashSet::new, |fixpoint| {
+            fixpoint.visible_tiles(self.map.len())
+        });
+        (visible_tiles, diagnostics)
+    }
+
+    /**
+     * Runs the common vision fixed point with fog of war on over every
+     * unit, see `run_fixpoint`.
+     */
+    pub(crate) fn common_vision_fixpoint(
+        &self,
+        config: &VisionConfig,
+    ) -> (Option<Fixpoint>, FixpointDiagnostics) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "common_vision",
+            tiles = self.map.len(),
+            units = self.units.len(),
+            teams = self.teams.len()
+        )
+        .entered();
+
+        let fixed_vision = self.fixed_vision(config);
+        self.run_fixpoint(|fixpoint, vision| {
+            self.vision_for_units(
+                self.units
+                    .keys()
+                    .copied()
+                    .filter(|location| fixpoint.counts(*location)),
+                &fixed_vision,
+                config,
+                vision,
+            )
+        })
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
+#[allow(non_snake_case)]
 mod tests {
     use super::*;
 
+    use crate::map::CountryKind;
+    use crate::officer::{OfficerKind, PowerKind};
+
     fn into_set(items: Vec<usize>) -> HashSet<usize> {
         items.into_iter().collect()
     }
@@ -229,17 +918,18 @@ mod tests {
         use super::*;
 
         fn make_map(map_tile: TileKind, map_dimensions: (usize, usize)) -> GameState {
-            let mut map = Vec::with_capacity(map_dimensions.0 * map_dimensions.1);
-            for _ in 0..(map_dimensions.0 * map_dimensions.1) {
-                map.push(map_tile.clone());
-            }
-
             GameState {
-                map,
-                map_dimensions,
+                map: Grid::filled(map_tile, map_dimensions),
                 units: BTreeMap::new(),
                 players: Vec::new(),
                 teams: Vec::new(),
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             }
         }
 
@@ -273,6 +963,18 @@ mod tests {
             assert_eq!(into_set(vec![]), game_state.neighbors(100, 1));
         }
 
+        #[test]
+        fn checked_neighbors_2x2() {
+            let game_state = make_map(TileKind::Sea, (2, 2));
+
+            assert_eq!(
+                Some(into_set(vec![0, 1, 2])),
+                game_state.checked_neighbors(0, 1)
+            );
+            assert_eq!(None, game_state.checked_neighbors(4, 1));
+            assert_eq!(None, game_state.checked_neighbors(100, 1));
+        }
+
         #[test]
         fn neighbors_3x3() {
             let game_state = make_map(TileKind::Sea, (3, 3));
@@ -305,13 +1007,16 @@ mod tests {
         #[test]
         pub fn simple_2x2() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::HeadQuarters,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::HeadQuarters,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::HeadQuarters,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::HeadQuarters,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Infantry)),
                     (3, UnitState::new(1, false, UnitKind::Infantry)),
@@ -319,34 +1024,50 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(
                 Some((0, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(0)
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(1, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(2, &VisionConfig::default())
             );
-            assert_eq!(None, game_state.vision_from_tiles(1));
-            assert_eq!(None, game_state.vision_from_tiles(2));
             assert_eq!(
                 Some((1, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(3)
+                game_state.vision_from_tiles(3, &VisionConfig::default())
             );
         }
 
         #[test]
         pub fn sonja_2x2() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::HeadQuarters,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::HeadQuarters,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::HeadQuarters,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::HeadQuarters,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -354,34 +1075,50 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(
                 Some((0, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(0)
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(1, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(2, &VisionConfig::default())
             );
-            assert_eq!(None, game_state.vision_from_tiles(1));
-            assert_eq!(None, game_state.vision_from_tiles(2));
             assert_eq!(
                 Some((1, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(3)
+                game_state.vision_from_tiles(3, &VisionConfig::default())
             );
         }
 
         #[test]
         pub fn sonja_2x2__forest__no_power() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -389,34 +1126,50 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(
                 Some((0, into_set(vec![0, 1, 2]))),
-                game_state.vision_from_tiles(0)
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(1, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(2, &VisionConfig::default())
             );
-            assert_eq!(None, game_state.vision_from_tiles(1));
-            assert_eq!(None, game_state.vision_from_tiles(2));
             assert_eq!(
                 Some((1, into_set(vec![1, 2, 3]))),
-                game_state.vision_from_tiles(3)
+                game_state.vision_from_tiles(3, &VisionConfig::default())
             );
         }
 
         #[test]
         pub fn sonja_2x2__forest__power() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -424,42 +1177,493 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (
+                    PlayerState::new(
                         CountryKind::OrangeStar,
                         OfficerKind::Sonja,
                         PowerKind::Normal,
                     ),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::Super),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::Super),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(
                 Some((0, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(0)
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(1, &VisionConfig::default())
+            );
+            assert_eq!(
+                None,
+                game_state.vision_from_tiles(2, &VisionConfig::default())
             );
-            assert_eq!(None, game_state.vision_from_tiles(1));
-            assert_eq!(None, game_state.vision_from_tiles(2));
             assert_eq!(
                 Some((1, into_set(vec![0, 1, 2, 3]))),
-                game_state.vision_from_tiles(3)
+                game_state.vision_from_tiles(3, &VisionConfig::default())
+            );
+        }
+
+        #[test]
+        pub fn config_2x2__forest__no_adjacency() {
+            let game_state = GameState {
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
+                units: [
+                    (0, UnitState::new(0, false, UnitKind::Artillery)),
+                    (3, UnitState::new(1, false, UnitKind::Artillery)),
+                ]
+                .into_iter()
+                .collect(),
+                players: vec![
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                ],
+                teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
+            };
+
+            let config = VisionConfig {
+                adjacency_reveals_hidden: false,
+                ..VisionConfig::default()
+            };
+
+            assert_eq!(
+                Some((0, into_set(vec![0]))),
+                game_state.vision_from_tiles(0, &config)
+            );
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2]))),
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+        }
+
+        #[test]
+        pub fn void_3x1() {
+            let game_state = GameState {
+                map: Grid::new(
+                    vec![TileKind::Plain, TileKind::Void, TileKind::Plain],
+                    (3, 1),
+                )
+                .expect("Tiles do not fill the map"),
+                units: [(0, UnitState::new(0, false, UnitKind::Recon))]
+                    .into_iter()
+                    .collect(),
+                players: vec![PlayerState::new(
+                    CountryKind::OrangeStar,
+                    OfficerKind::Andy,
+                    PowerKind::None,
+                )],
+                teams: vec![into_set(vec![0])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
+            };
+
+            assert_eq!(
+                Some((0, into_set(vec![0, 2]))),
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+        }
+
+        #[test]
+        pub fn teleporter_5x1__does_not_link_vision() {
+            // Teleporters do not hide units and vision does not pass from one
+            // Teleporter to the others.
+            let game_state = game_state!(".0inf @1pipe . @ .");
+
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2]))),
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+        }
+
+        #[test]
+        pub fn unit_vision_5x1__ignores_fixed_vision() {
+            let mut game_state = game_state!(".0inf f . c .1inf");
+            game_state.set_owner(3, Some(0));
+            assert!(game_state.visible_to_team(0).contains(&3));
+
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2]))),
+                game_state.unit_vision(0)
+            );
+            assert_eq!(None, game_state.unit_vision(2));
+            assert_eq!(None, game_state.unit_vision(9));
+        }
+
+        #[test]
+        pub fn mountain_7x1__foot_soldiers_see_further() {
+            // Infantry and Mech get 3 extra vision on a Mountain under every
+            // ruleset, other units do not.
+            let mut game_state = game_state!("^0inf . . . . . ^1bcop");
+
+            for ruleset in Ruleset::ALL {
+                game_state.set_ruleset(ruleset.clone());
+                assert_eq!(
+                    Some((0, into_set(vec![0, 1, 2, 3, 4, 5]))),
+                    game_state.vision_from_tiles(0, &VisionConfig::default())
+                );
+                assert_eq!(
+                    Some((1, into_set(vec![3, 4, 5, 6]))),
+                    game_state.vision_from_tiles(6, &VisionConfig::default())
+                );
+            }
+
+            let config = VisionConfig {
+                terrain_vision_bonus: false,
+                ..VisionConfig::default()
+            };
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2]))),
+                game_state.vision_from_tiles(0, &config)
+            );
+            assert_eq!(
+                Some((1, into_set(vec![3, 4, 5, 6]))),
+                game_state.vision_from_tiles(6, &config)
+            );
+        }
+
+        #[test]
+        pub fn config_6x1__sonja_day_to_day_vision() {
+            let mut game_state = game_state!(".0inf . . . . .1inf");
+            game_state.players[0] =
+                PlayerState::new(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None);
+            let config = VisionConfig {
+                sonja_day_to_day_vision: false,
+                ..VisionConfig::default()
+            };
+
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2, 3]))),
+                game_state.vision_from_tiles(0, &VisionConfig::default())
+            );
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2]))),
+                game_state.vision_from_tiles(0, &config)
+            );
+
+            game_state.players[0].power = PowerKind::Normal;
+            assert_eq!(
+                Some((0, into_set(vec![0, 1, 2, 3]))),
+                game_state.vision_from_tiles(0, &config)
+            );
+        }
+
+        #[test]
+        pub fn config_3x1__custom_hiding_tiles() {
+            let game_state = GameState {
+                map: Grid::new(
+                    vec![TileKind::Plain, TileKind::Plain, TileKind::Plain],
+                    (3, 1),
+                )
+                .expect("Tiles do not fill the map"),
+                units: [(0, UnitState::new(0, false, UnitKind::Recon))]
+                    .into_iter()
+                    .collect(),
+                players: vec![PlayerState::new(
+                    CountryKind::OrangeStar,
+                    OfficerKind::Andy,
+                    PowerKind::None,
+                )],
+                teams: vec![into_set(vec![0])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
+            };
+
+            let config = VisionConfig {
+                hiding_tiles: [TileKind::Plain].into_iter().collect(),
+                ..VisionConfig::default()
+            };
+
+            assert_eq!(
+                Some((0, into_set(vec![0, 1]))),
+                game_state.vision_from_tiles(0, &config)
+            );
+        }
+
+        #[test]
+        pub fn config_2x1__dived_submarine__sea_only() {
+            let make_state = |observer: UnitKind| GameState {
+                map: Grid::new(vec![TileKind::Sea, TileKind::Shoal], (2, 1))
+                    .expect("Tiles do not fill the map"),
+                units: [
+                    (0, UnitState::new(0, true, UnitKind::Submarine)),
+                    (1, UnitState::new(1, false, observer)),
+                ]
+                .into_iter()
+                .collect(),
+                players: vec![
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                ],
+                teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
+            };
+
+            let config = VisionConfig {
+                dived_revealed_by_sea_only: true,
+                ..VisionConfig::default()
+            };
+
+            assert_eq!(
+                Some((1, into_set(vec![1]))),
+                make_state(UnitKind::Infantry).vision_from_tiles(1, &config)
+            );
+            assert_eq!(
+                Some((1, into_set(vec![0, 1]))),
+                make_state(UnitKind::Lander).vision_from_tiles(1, &config)
+            );
+            assert_eq!(
+                Some((1, into_set(vec![0, 1]))),
+                make_state(UnitKind::Infantry).vision_from_tiles(1, &VisionConfig::default())
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serialization {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let game_state = game_state!(
+                "
+                teams: 0,1 2
+                .0inf f1tank* ~
+                . . ~2sub*
+                "
+            );
+
+            let json = serde_json::to_string(&game_state).unwrap();
+            assert_eq!(game_state, serde_json::from_str(&json).unwrap());
+        }
+
+        #[test]
+        fn stable_layout() {
+            let game_state = game_state!(".0inf");
+
+            assert_eq!(
+                serde_json::json!({
+                    "map": ["Plain"],
+                    "map_dimensions": [1, 1],
+                    "units": {
+                        "0": {
+                            "player": 0,
+                            "stealthed": false,
+                            "kind": "Infantry",
+                            "hp": 10,
+                            "fuel": null,
+                            "ammo": null,
+                            "cargo": [],
+                        },
+                    },
+                    "owners": {},
+                    "players": [{
+                        "country": "OrangeStar",
+                        "officer": "Andy",
+                        "power": "None",
+                        "charge": 0,
+                        "powers_used": 0,
+                    }],
+                    "teams": [[0]],
+                    "weather": "Clear",
+                    "settings": {
+                        "fog": true,
+                        "starting_funds": 0,
+                        "funds_per_property": 1000,
+                        "banned_officers": [],
+                        "banned_units": [],
+                        "weather": "Clear",
+                    },
+                    "reveals": [],
+                    "ruleset": "Awbw",
+                    "day": 1,
+                    "active_player": 0,
+                }),
+                serde_json::to_value(&game_state).unwrap()
+            );
+        }
+
+        #[test]
+        fn map_longer_than_dimensions() {
+            let mut json = serde_json::to_value(game_state!(".0inf")).unwrap();
+            json["map"] = serde_json::json!(["Plain", "Forest"]);
+
+            assert!(serde_json::from_value::<GameState>(json).is_err());
+        }
+
+        #[test]
+        fn players_from_before_power_meters() {
+            let players: Vec<PlayerState> =
+                serde_json::from_value(serde_json::json!([["BlueMoon", "Sonja", "Normal"]]))
+                    .unwrap();
+
+            assert_eq!(
+                vec![PlayerState::new(
+                    CountryKind::BlueMoon,
+                    OfficerKind::Sonja,
+                    PowerKind::Normal
+                )],
+                players
+            );
+        }
+
+        #[test]
+        fn unit_defaults_when_missing() {
+            let unit: UnitState = serde_json::from_value(serde_json::json!({
+                "player": 1,
+                "stealthed": false,
+                "kind": "Tank",
+            }))
+            .unwrap();
+
+            assert_eq!(UnitState::new(1, false, UnitKind::Tank), unit);
+            assert_eq!((10, 70, 9), (unit.hp(), unit.fuel(), unit.ammo()));
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn white_nova_alias() {
+            assert_eq!(CountryKind::WhiteNova, CountryKind::WhiteNove);
+            assert_eq!(
+                CountryKind::WhiteNova,
+                serde_json::from_str::<CountryKind>("\"WhiteNove\"").unwrap()
             );
         }
     }
 
+    mod visible_to_team {
+        use super::*;
+
+        #[test]
+        pub fn duel_5x1() {
+            let game_state = game_state!(".0inf . . . .1inf");
+
+            assert_eq!(into_set(vec![0, 1, 2]), game_state.visible_to_team(0));
+            assert_eq!(into_set(vec![2, 3, 4]), game_state.visible_to_team(1));
+        }
+
+        #[test]
+        pub fn unknown_team__empty() {
+            let game_state = game_state!(".0inf . .1inf");
+
+            assert_eq!(into_set(vec![]), game_state.visible_to_team(2));
+        }
+    }
+
+    mod unit_visible_to {
+        use super::*;
+
+        #[test]
+        pub fn forest_4x1__hidden_until_adjacent() {
+            let mut game_state = game_state!(".0inf . f1inf .");
+
+            assert!(game_state.unit_visible_to(2, 1));
+            assert!(!game_state.unit_visible_to(2, 0));
+            assert!(!game_state.unit_visible_to(1, 0));
+            assert!(!game_state.unit_visible_to(2, 2));
+
+            game_state.relocate_unit(0, 1).unwrap();
+            assert!(game_state.unit_visible_to(2, 0));
+        }
+
+        #[test]
+        pub fn dived_sub_4x1__hidden_until_adjacent() {
+            let mut game_state = game_state!("~0sub* ~ ~1bship ~");
+
+            assert!(!game_state.unit_visible_to(0, 1));
+
+            game_state.relocate_unit(2, 1).unwrap();
+            assert!(game_state.unit_visible_to(0, 1));
+        }
+    }
+
+    mod watchers_of {
+        use super::*;
+
+        #[test]
+        pub fn forest_5x1__only_adjacent() {
+            let game_state = game_state!(".0inf f .1rec . .0inf");
+
+            assert_eq!(vec![(0, 0), (1, 2)], game_state.watchers_of(1));
+            assert_eq!(vec![(1, 2), (0, 4)], game_state.watchers_of(3));
+            assert_eq!(Vec::<(usize, usize)>::new(), game_state.watchers_of(5));
+        }
+    }
+
+    mod any_vision {
+        use super::*;
+
+        #[test]
+        pub fn duel_7x1__union_of_teams() {
+            let game_state = game_state!(".0inf . . . . . .1inf");
+
+            assert_eq!(into_set(vec![0, 1, 2, 4, 5, 6]), game_state.any_vision());
+        }
+
+        #[test]
+        pub fn fog_off_3x1__full_map() {
+            let mut game_state = game_state!(".0inf . .");
+            game_state.settings.fog = false;
+
+            assert_eq!(into_set(vec![0, 1, 2]), game_state.any_vision());
+        }
+    }
+
     mod common_vision {
         use super::*;
 
         #[test]
         pub fn simple_2x2_all() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::HeadQuarters,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::HeadQuarters,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::HeadQuarters,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::HeadQuarters,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Infantry)),
                     (3, UnitState::new(1, false, UnitKind::Infantry)),
@@ -467,10 +1671,17 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![0, 1, 2, 3]), game_state.common_vision());
@@ -479,13 +1690,16 @@ mod tests {
         #[test]
         pub fn simple_2x2_none() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::HeadQuarters,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::HeadQuarters,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::HeadQuarters,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::HeadQuarters,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -493,10 +1707,17 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![]), game_state.common_vision());
@@ -505,13 +1726,16 @@ mod tests {
         #[test]
         pub fn sonja_2x2() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::HeadQuarters,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::HeadQuarters,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::HeadQuarters,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::HeadQuarters,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -519,10 +1743,17 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![0, 1, 2, 3]), game_state.common_vision());
@@ -531,13 +1762,16 @@ mod tests {
         #[test]
         pub fn sonja_2x2__forest__no_power() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -545,10 +1779,17 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![]), game_state.common_vision());
@@ -557,13 +1798,16 @@ mod tests {
         #[test]
         pub fn sonja_2x2__forest__power() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (3, UnitState::new(1, false, UnitKind::Artillery)),
@@ -571,14 +1815,21 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (
+                    PlayerState::new(
                         CountryKind::OrangeStar,
                         OfficerKind::Sonja,
                         PowerKind::Normal,
                     ),
-                    (CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::Super),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Sonja, PowerKind::Super),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![0, 1, 2, 3]), game_state.common_vision());
@@ -587,13 +1838,16 @@ mod tests {
         #[test]
         pub fn team_2x2__cycle__all() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                        TileKind::Forest,
+                    ],
+                    (2, 2),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (1, UnitState::new(1, false, UnitKind::Artillery)),
@@ -603,16 +1857,23 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
-                    (CountryKind::GreenEarth, OfficerKind::Drake, PowerKind::None),
-                    (
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                    PlayerState::new(CountryKind::GreenEarth, OfficerKind::Drake, PowerKind::None),
+                    PlayerState::new(
                         CountryKind::YellowComet,
                         OfficerKind::Kanbei,
                         PowerKind::Super,
                     ),
                 ],
                 teams: vec![into_set(vec![0, 2]), into_set(vec![1, 3])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
             assert_eq!(into_set(vec![0, 1, 2, 3]), game_state.common_vision());
@@ -621,18 +1882,21 @@ mod tests {
         #[test]
         pub fn team_3x3__recon() {
             let game_state = GameState {
-                map: vec![
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::City,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::Plain,
-                    TileKind::Forest,
-                ],
-                map_dimensions: (2, 2),
+                map: Grid::new(
+                    vec![
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::City,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::Plain,
+                        TileKind::Forest,
+                    ],
+                    (3, 3),
+                )
+                .expect("Tiles do not fill the map"),
                 units: [
                     (0, UnitState::new(0, false, UnitKind::Artillery)),
                     (2, UnitState::new(1, false, UnitKind::Infantry)),
@@ -641,13 +1905,51 @@ mod tests {
                 .into_iter()
                 .collect(),
                 players: vec![
-                    (CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
-                    (CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+                    PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                    PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
                 ],
                 teams: vec![into_set(vec![0]), into_set(vec![1])],
+                weather: Weather::Clear,
+                settings: GameSettings::fog_league(),
+                owners: BTreeMap::new(),
+                reveals: Vec::new(),
+                ruleset: Ruleset::Awbw,
+                day: 1,
+                active_player: 0,
             };
 
+            // The infantry cannot see the recon in the forest, and once the
+            // recon is hidden the artillery cannot see the infantry on the
+            // city, so every unit drops out of the common vision.
             assert_eq!(into_set(vec![]), game_state.common_vision());
         }
+
+        #[test]
+        pub fn fog_off_5x1__full_map() {
+            let mut game_state = game_state!(".0inf . . . .1inf");
+            game_state.set_settings(GameSettings::standard());
+
+            assert_eq!(into_set(vec![0, 1, 2, 3, 4]), game_state.common_vision());
+            assert_eq!(into_set(vec![0, 1, 2, 3, 4]), game_state.visible_to_team(0));
+        }
+
+        #[test]
+        pub fn diagnostics_5x1__converges_to_nothing() {
+            let game_state = game_state!(".0inf . . . .1inf");
+
+            let (tiles, diagnostics) =
+                game_state.common_vision_with_diagnostics(&VisionConfig::default());
+
+            assert_eq!(into_set(vec![]), tiles);
+            assert!(!diagnostics.hit_bound);
+            // Each pass hides every tile some team can not see, not just the
+            // first one it finds, so both units go in the first pass.
+            assert_eq!(3, diagnostics.iterations);
+            assert_eq!(
+                vec![vec![0, 1, 3, 4], vec![2], vec![]],
+                diagnostics.removed_tiles
+            );
+            assert_eq!(vec![vec![0, 4], vec![], vec![]], diagnostics.removed_units);
+        }
     }
 }
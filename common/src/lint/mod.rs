@@ -0,0 +1,220 @@
+use crate::data::{TileData, UnitData};
+use crate::map::TileKind;
+use crate::officer::OfficerKind;
+use crate::unit::{MovementKind, UnitKind};
+use crate::GameState;
+
+/**
+ * A suspicious but legal part of a state, typically left behind by an
+ * importer.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Lint {
+    /** A unit is standing on terrain it could never move onto. */
+    ImpassableTerrain {
+        location: usize,
+        kind: UnitKind,
+        tile: TileKind,
+    },
+    /** A unit is stealthed but is not a Submarine or Stealth. */
+    CannotStealth { location: usize, kind: UnitKind },
+    /** A unit's owner is not on any team so it gives no vision. */
+    NoTeam { location: usize, player: usize },
+    /** An air or naval unit has no fuel left, so it should have crashed or
+     * sunk at the start of its owner's turn. */
+    OutOfFuel { location: usize, kind: UnitKind },
+    /** A unit does not exist under the state's ruleset. */
+    NotInRuleset { location: usize, kind: UnitKind },
+    /** A property next to an HQ has no owner, though it would almost
+     * always have been captured by the HQ's owner. */
+    UnownedNearHeadQuarters { location: usize, tile: TileKind },
+    /** A unit is stealthed but its owner's officer does not exist under the
+     * state's ruleset, so either the officer or the unit was misread. */
+    StealthedUnknownOfficer {
+        location: usize,
+        officer: OfficerKind,
+    },
+}
+
+impl Lint {
+    /**
+     * Returns the tile the warning is about.
+     */
+    pub fn location(&self) -> usize {
+        match self {
+            Lint::ImpassableTerrain { location, .. }
+            | Lint::CannotStealth { location, .. }
+            | Lint::NoTeam { location, .. }
+            | Lint::OutOfFuel { location, .. }
+            | Lint::NotInRuleset { location, .. }
+            | Lint::UnownedNearHeadQuarters { location, .. }
+            | Lint::StealthedUnknownOfficer { location, .. } => *location,
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns warnings about anything in the state that is allowed but
+     * unlikely to be intended, ordered by location.
+     */
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+
+        for (location, unit) in self.units.iter() {
+            if let Some(tile) = self.map.cells().get(*location) {
+                if tile.movement_cost(unit.kind.movement_kind()).is_none() {
+                    lints.push(Lint::ImpassableTerrain {
+                        location: *location,
+                        kind: unit.kind.clone(),
+                        tile: tile.clone(),
+                    });
+                }
+            }
+
+            if unit.stealthed && !unit.kind.can_stealth() {
+                lints.push(Lint::CannotStealth {
+                    location: *location,
+                    kind: unit.kind.clone(),
+                });
+            }
+
+            if unit.fuel() == 0
+                && matches!(
+                    unit.kind.movement_kind(),
+                    MovementKind::Air | MovementKind::Sea | MovementKind::Lander
+                )
+            {
+                lints.push(Lint::OutOfFuel {
+                    location: *location,
+                    kind: unit.kind.clone(),
+                });
+            }
+
+            if !self.ruleset.has_unit(&unit.kind) {
+                lints.push(Lint::NotInRuleset {
+                    location: *location,
+                    kind: unit.kind.clone(),
+                });
+            }
+
+            if self.team_of(unit.player).is_none() {
+                lints.push(Lint::NoTeam {
+                    location: *location,
+                    player: unit.player,
+                });
+            }
+
+            if let Some(player) = self.players.get(unit.player) {
+                if unit.stealthed && !self.ruleset.has_officer(&player.officer) {
+                    lints.push(Lint::StealthedUnknownOfficer {
+                        location: *location,
+                        officer: player.officer.clone(),
+                    });
+                }
+            }
+        }
+
+        for (location, tile) in self.map.cells().iter().enumerate() {
+            if !tile.is_property() || self.owners.contains_key(&location) {
+                continue;
+            }
+            if self.neighbors(location, 1).into_iter().any(|neighbor| {
+                neighbor != location
+                    && self.map.cells().get(neighbor) == Some(&TileKind::HeadQuarters)
+            }) {
+                lints.push(Lint::UnownedNearHeadQuarters {
+                    location,
+                    tile: tile.clone(),
+                });
+            }
+        }
+
+        lints.sort_by_key(|lint| lint.location());
+        lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ruleset::Ruleset;
+
+    #[test]
+    fn lint_warnings() {
+        let mut game_state = crate::game_state!("~0tank .0inf* ~1sub*");
+        game_state.units.get_mut(&2).unwrap().set_fuel(0);
+        game_state.teams = vec![[0].into_iter().collect()];
+
+        assert_eq!(
+            vec![
+                Lint::ImpassableTerrain {
+                    location: 0,
+                    kind: UnitKind::Tank,
+                    tile: TileKind::Sea,
+                },
+                Lint::CannotStealth {
+                    location: 1,
+                    kind: UnitKind::Infantry,
+                },
+                Lint::OutOfFuel {
+                    location: 2,
+                    kind: UnitKind::Submarine,
+                },
+                Lint::NoTeam {
+                    location: 2,
+                    player: 1,
+                },
+            ],
+            game_state.lint()
+        );
+    }
+
+    #[test]
+    fn lint_not_in_ruleset() {
+        let mut game_state = crate::game_state!(".0stl .0tank");
+        assert!(game_state.lint().is_empty());
+
+        game_state.set_ruleset(Ruleset::Aw2);
+        assert_eq!(
+            vec![Lint::NotInRuleset {
+                location: 0,
+                kind: UnitKind::Stealth,
+            }],
+            game_state.lint()
+        );
+    }
+
+    #[test]
+    fn lint_unowned_near_head_quarters() {
+        let mut game_state = crate::game_state!(".0inf c b .\n. q . c\n. . . .1inf");
+        game_state.set_owner(5, Some(0));
+        assert_eq!(
+            vec![Lint::UnownedNearHeadQuarters {
+                location: 1,
+                tile: TileKind::City,
+            }],
+            game_state.lint()
+        );
+
+        game_state.set_owner(1, Some(0));
+        assert!(game_state.lint().is_empty());
+    }
+
+    #[test]
+    fn lint_stealthed_unknown_officer() {
+        let mut game_state = crate::game_state!("~0sub* .0tank");
+        game_state.players[0].officer = OfficerKind::Jake;
+        assert!(game_state.lint().is_empty());
+
+        game_state.set_ruleset(Ruleset::Aw2);
+        assert_eq!(
+            vec![Lint::StealthedUnknownOfficer {
+                location: 0,
+                officer: OfficerKind::Jake,
+            }],
+            game_state.lint()
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use core::fmt;
+
+use crate::map::{CountryKind, TileKind};
+use crate::prelude::*;
+
+/**
+ * Why an AWBW map export could not be read.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AwbwMapError {
+    /** The export has no rows. */
+    Empty,
+    /** A row has a different number of tiles than the first. */
+    RaggedRow {
+        row: usize,
+        tiles: usize,
+        expected: usize,
+    },
+    /** An entry of a row is not a number. */
+    NotATileId { row: usize, text: String },
+    /** An entry of a row is not one of AWBW's terrain IDs. */
+    UnknownTileId { row: usize, id: u32 },
+}
+
+impl fmt::Display for AwbwMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AwbwMapError::Empty => write!(f, "The map has no rows"),
+            AwbwMapError::RaggedRow {
+                row,
+                tiles,
+                expected,
+            } => write!(
+                f,
+                "Row {} has {} tiles but expected {}",
+                row, tiles, expected
+            ),
+            AwbwMapError::NotATileId { row, text } => {
+                write!(f, "Terrain `{}` in row {} is not a number", text, row)
+            }
+            AwbwMapError::UnknownTileId { row, id } => {
+                write!(f, "Unknown terrain {} in row {}", id, row)
+            }
+        }
+    }
+}
+
+impl core::error::Error for AwbwMapError {}
+
+/**
+ * Maps one of AWBW's terrain IDs to a tile.
+ *
+ * Properties of every country map to the same tile since tiles do not track
+ * an owner.
+ */
+pub fn tile_from_awbw_id(id: u32) -> Option<TileKind> {
+    Some(match id {
+        1 => TileKind::Plain,
+        2 => TileKind::Mountain,
+        3 => TileKind::Forest,
+        // Every direction of river and road.
+        4..=14 => TileKind::River,
+        15..=25 => TileKind::Road,
+        26 | 27 => TileKind::Bridge,
+        28 => TileKind::Sea,
+        29..=32 => TileKind::Shoal,
+        33 => TileKind::Reef,
+        // Neutral and then each country in the order AWBW added them.
+        34 | 38 | 43 | 48 | 53 | 81 | 86 | 91 | 96 | 119 | 124 | 151 | 158 | 165 | 172 | 183
+        | 190 | 198 | 205 | 212 => TileKind::City,
+        35 | 39 | 44 | 49 | 54 | 82 | 87 | 92 | 97 | 118 | 123 | 150 | 157 | 164 | 171 | 182
+        | 189 | 197 | 204 | 211 => TileKind::Base,
+        36 | 40 | 45 | 50 | 55 | 83 | 88 | 93 | 98 | 117 | 122 | 149 | 156 | 163 | 170 | 181
+        | 188 | 196 | 203 | 210 => TileKind::Airport,
+        37 | 41 | 46 | 51 | 56 | 84 | 89 | 94 | 99 | 121 | 126 | 155 | 162 | 169 | 176 | 187
+        | 194 | 202 | 209 | 216 => TileKind::Harbour,
+        42 | 47 | 52 | 57 | 85 | 90 | 95 | 100 | 120 | 125 | 153 | 160 | 167 | 174 | 185 | 192
+        | 200 | 207 | 214 => TileKind::HeadQuarters,
+        127..=137 | 152 | 159 | 166 | 173 | 184 | 191 | 199 | 206 | 213 => {
+            TileKind::CommunicationsTower
+        }
+        138..=148 | 154 | 161 | 168 | 175 | 186 | 193 | 201 | 208 | 215 => TileKind::Laboratory,
+        // Every direction of pipe and pipe end.
+        101..=110 => TileKind::Pipe,
+        111 => TileKind::Silo,
+        112 => TileKind::UsedSilo,
+        // Pipe seams and then broken pipe seams.
+        113 | 114 => TileKind::PipeSeam,
+        115 | 116 => TileKind::BrokenPipeSeam,
+        195 => TileKind::Teleporter,
+        _ => return None,
+    })
+}
+
+/**
+ * Maps one of AWBW's terrain IDs to the country owning the property, or
+ * None if it is neutral or not a property.
+ *
+ * Cities, bases, airports, harbours and HQs of a country have consecutive
+ * IDs, as do its communications tower and lab for the later countries.
+ * The first countries' towers and labs are listed alphabetically.
+ */
+pub fn country_from_awbw_id(id: u32) -> Option<CountryKind> {
+    Some(match id {
+        38..=42 | 134 | 146 => CountryKind::OrangeStar,
+        43..=47 | 129 | 140 => CountryKind::BlueMoon,
+        48..=52 | 131 | 142 => CountryKind::GreenEarth,
+        53..=57 | 136 | 148 => CountryKind::YellowComet,
+        81..=85 | 135 | 147 => CountryKind::RedFire,
+        86..=90 | 137 | 143 => CountryKind::GreySky,
+        91..=95 | 128 | 139 => CountryKind::BlackHole,
+        96..=100 | 130 | 141 => CountryKind::BrownDesert,
+        117..=121 | 127 | 138 => CountryKind::AmberBlaze,
+        122..=126 | 132 | 144 => CountryKind::JadeSun,
+        149..=155 => CountryKind::CobaltIce,
+        156..=162 => CountryKind::PinkCosmos,
+        163..=169 => CountryKind::TealGalaxy,
+        170..=176 => CountryKind::PurpleLightning,
+        181..=187 => CountryKind::AcidRain,
+        188..=194 => CountryKind::WhiteNova,
+        196..=202 => CountryKind::AzureAsteroid,
+        203..=209 => CountryKind::NoirEclipse,
+        210..=216 => CountryKind::SilverClaw,
+        _ => return None,
+    })
+}
+
+/**
+ * Parses a map exported from AWBW, one row per line with the terrain IDs of
+ * a row separated by commas.
+ *
+ * Blank lines and trailing commas are ignored. Returns the tiles starting
+ * from the top left and the (width, height).
+ */
+pub fn from_awbw_text(text: &str) -> Result<(Vec<TileKind>, (usize, usize)), AwbwMapError> {
+    let mut tiles = Vec::new();
+    let mut width = None;
+    let mut height = 0;
+
+    for (row, line) in text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+    {
+        let ids = line
+            .trim_end_matches(',')
+            .split(',')
+            .map(str::trim)
+            .collect::<Vec<&str>>();
+
+        match width {
+            None => width = Some(ids.len()),
+            Some(width) if width != ids.len() => {
+                return Err(AwbwMapError::RaggedRow {
+                    row,
+                    tiles: ids.len(),
+                    expected: width,
+                });
+            }
+            Some(_) => {}
+        }
+
+        for id in ids {
+            let id = id.parse::<u32>().map_err(|_| AwbwMapError::NotATileId {
+                row,
+                text: String::from(id),
+            })?;
+            let tile = tile_from_awbw_id(id).ok_or(AwbwMapError::UnknownTileId { row, id })?;
+            tiles.push(tile);
+        }
+        height += 1;
+    }
+
+    match width {
+        Some(width) => Ok((tiles, (width, height))),
+        None => Err(AwbwMapError::Empty),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_awbw_text_parses() {
+        let text = "28,28,33\n1,3,42,\n\n2,195,101\n";
+        assert_eq!(
+            Ok((
+                vec![
+                    TileKind::Sea,
+                    TileKind::Sea,
+                    TileKind::Reef,
+                    TileKind::Plain,
+                    TileKind::Forest,
+                    TileKind::HeadQuarters,
+                    TileKind::Mountain,
+                    TileKind::Teleporter,
+                    TileKind::Pipe,
+                ],
+                (3, 3)
+            )),
+            from_awbw_text(text)
+        );
+    }
+
+    #[test]
+    fn from_awbw_text_errors() {
+        assert_eq!(
+            Err(AwbwMapError::RaggedRow {
+                row: 1,
+                tiles: 1,
+                expected: 2
+            }),
+            from_awbw_text("1,1\n1")
+        );
+        assert_eq!(
+            Err(AwbwMapError::UnknownTileId { row: 0, id: 999 }),
+            from_awbw_text("1,999")
+        );
+        assert_eq!(
+            Err(AwbwMapError::NotATileId {
+                row: 0,
+                text: "plain".to_string()
+            }),
+            from_awbw_text("1,plain")
+        );
+        assert_eq!(Err(AwbwMapError::Empty), from_awbw_text(""));
+        assert_eq!(Err(AwbwMapError::Empty), from_awbw_text("\n  \n"));
+    }
+
+    #[test]
+    fn country_properties() {
+        // Neutral, Orange Star, Cobalt Ice and White Nova.
+        for city in [34, 38, 151, 190] {
+            assert_eq!(Some(TileKind::City), tile_from_awbw_id(city));
+        }
+        for hq in [
+            42, 47, 52, 57, 85, 90, 95, 100, 120, 125, 153, 160, 167, 174, 185, 192,
+        ] {
+            assert_eq!(Some(TileKind::HeadQuarters), tile_from_awbw_id(hq));
+        }
+        assert_eq!(None, tile_from_awbw_id(0));
+        assert_eq!(None, tile_from_awbw_id(58));
+    }
+
+    #[test]
+    fn property_owners() {
+        assert_eq!(Some(CountryKind::OrangeStar), country_from_awbw_id(38));
+        assert_eq!(Some(CountryKind::OrangeStar), country_from_awbw_id(42));
+        assert_eq!(Some(CountryKind::BlackHole), country_from_awbw_id(95));
+        assert_eq!(Some(CountryKind::CobaltIce), country_from_awbw_id(151));
+        assert_eq!(Some(CountryKind::WhiteNova), country_from_awbw_id(190));
+        // Neutral city, tower and lab, and a plain.
+        for id in [34, 133, 145, 1] {
+            assert_eq!(None, country_from_awbw_id(id));
+        }
+
+        // Every owned property is a property.
+        for id in 0..256 {
+            if country_from_awbw_id(id).is_some() {
+                assert!(tile_from_awbw_id(id).is_some_and(|tile| tile.is_property()));
+            }
+        }
+    }
+
+    #[test]
+    fn newer_country_properties() {
+        // An Azure Asteroid HQ, Noir Eclipse city and Silver Claw lab.
+        let (tiles, dimensions) = from_awbw_text("200,205,215\n").unwrap();
+        assert_eq!(
+            vec![TileKind::HeadQuarters, TileKind::City, TileKind::Laboratory,],
+            tiles
+        );
+        assert_eq!((3, 1), dimensions);
+        assert_eq!(
+            vec![
+                Some(CountryKind::AzureAsteroid),
+                Some(CountryKind::NoirEclipse),
+                Some(CountryKind::SilverClaw),
+            ],
+            [200, 205, 215].map(country_from_awbw_id).to_vec()
+        );
+    }
+
+    #[test]
+    fn pipe_seams_and_silos() {
+        let text = "113,114,115,116\n111,112,1,1\n";
+        assert_eq!(
+            Ok((
+                vec![
+                    TileKind::PipeSeam,
+                    TileKind::PipeSeam,
+                    TileKind::BrokenPipeSeam,
+                    TileKind::BrokenPipeSeam,
+                    TileKind::Silo,
+                    TileKind::UsedSilo,
+                    TileKind::Plain,
+                    TileKind::Plain,
+                ],
+                (4, 2)
+            )),
+            from_awbw_text(text)
+        );
+    }
+}
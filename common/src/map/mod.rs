@@ -1,24 +1,47 @@
+use crate::data::TileData;
+use crate::grid::Grid;
+use crate::prelude::*;
+use crate::unit::MovementKind;
 
+mod awbw;
+mod symmetry;
+
+pub use awbw::{country_from_awbw_id, from_awbw_text, tile_from_awbw_id, AwbwMapError};
+pub use symmetry::{symmetry, Symmetry, SymmetryCheck};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CountryKind {
     OrangeStar,
     BlueMoon,
     GreenEarth,
     YellowComet,
     BlackHole,
+    RedFire,
     GreySky,
     BrownDesert,
     AmberBlaze,
     JadeSun,
+    CobaltIce,
     PinkCosmos,
     TealGalaxy,
     PurpleLightning,
     AcidRain,
-    WhiteNove,
+    #[cfg_attr(feature = "serde", serde(alias = "WhiteNove"))]
+    WhiteNova,
+    AzureAsteroid,
+    NoirEclipse,
+    SilverClaw,
+}
+
+impl CountryKind {
+    #[deprecated(note = "Renamed to `WhiteNova`")]
+    #[allow(non_upper_case_globals)]
+    pub const WhiteNove: CountryKind = CountryKind::WhiteNova;
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TileKind {
     Plain,
     Mountain,
@@ -35,17 +58,251 @@ pub enum TileKind {
     Harbour,
     HeadQuarters,
     Pipe,
+    /** A weak point in a pipe that can be attacked to break it. */
+    PipeSeam,
+    /** A pipe seam that has been destroyed, which plays like a plain. */
+    BrokenPipeSeam,
     Silo,
+    /** A Silo whose missile has been launched. */
+    UsedSilo,
     CommunicationsTower,
     Laboratory,
+    /** Links every other Teleporter on the map for pipe movement. */
+    Teleporter,
+    /** Out of play: nothing can enter or be seen on it. */
+    Void,
 }
 
-impl TileKind {
-    pub fn hides_units(&self) -> bool {
+impl TileData for TileKind {
+    const ALL: &'static [TileKind] = &[
+        TileKind::Plain,
+        TileKind::Mountain,
+        TileKind::Forest,
+        TileKind::River,
+        TileKind::Road,
+        TileKind::Bridge,
+        TileKind::Sea,
+        TileKind::Shoal,
+        TileKind::Reef,
+        TileKind::City,
+        TileKind::Base,
+        TileKind::Airport,
+        TileKind::Harbour,
+        TileKind::HeadQuarters,
+        TileKind::Pipe,
+        TileKind::PipeSeam,
+        TileKind::BrokenPipeSeam,
+        TileKind::Silo,
+        TileKind::UsedSilo,
+        TileKind::CommunicationsTower,
+        TileKind::Laboratory,
+        TileKind::Teleporter,
+        TileKind::Void,
+    ];
+
+    fn hides_units(&self) -> bool {
         match self {
-            TileKind::Forest => true,
-            TileKind::Reef => true,
-            _ => false,
+            TileKind::Forest | TileKind::Reef => true,
+            TileKind::Plain
+            | TileKind::Mountain
+            | TileKind::River
+            | TileKind::Road
+            | TileKind::Bridge
+            | TileKind::Sea
+            | TileKind::Shoal
+            | TileKind::City
+            | TileKind::Base
+            | TileKind::Airport
+            | TileKind::Harbour
+            | TileKind::HeadQuarters
+            | TileKind::Pipe
+            | TileKind::PipeSeam
+            | TileKind::BrokenPipeSeam
+            | TileKind::Silo
+            | TileKind::UsedSilo
+            | TileKind::CommunicationsTower
+            | TileKind::Laboratory
+            | TileKind::Teleporter
+            | TileKind::Void => false,
         }
     }
-}
\ No newline at end of file
+
+    fn movement_cost(&self, movement: MovementKind) -> Option<u8> {
+        use MovementKind::*;
+
+        match self {
+            TileKind::Plain | TileKind::BrokenPipeSeam => match movement {
+                Infantry | Mech | Treads | Air => Some(1),
+                Tires => Some(2),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Mountain => match movement {
+                Mech | Air => Some(1),
+                Infantry => Some(2),
+                Tires | Treads | Sea | Lander | Pipe => None,
+            },
+            TileKind::Forest => match movement {
+                Infantry | Mech | Air => Some(1),
+                Treads => Some(2),
+                Tires => Some(3),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::River => match movement {
+                Mech | Air => Some(1),
+                Infantry => Some(2),
+                Tires | Treads | Sea | Lander | Pipe => None,
+            },
+            TileKind::Road => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Bridge => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Sea => match movement {
+                Air | Sea | Lander => Some(1),
+                Infantry | Mech | Tires | Treads | Pipe => None,
+            },
+            TileKind::Shoal => match movement {
+                Infantry | Mech | Tires | Treads | Air | Lander => Some(1),
+                Sea | Pipe => None,
+            },
+            TileKind::Reef => match movement {
+                Air => Some(1),
+                Sea | Lander => Some(2),
+                Infantry | Mech | Tires | Treads | Pipe => None,
+            },
+            TileKind::City => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Base => match movement {
+                Infantry | Mech | Tires | Treads | Air | Pipe => Some(1),
+                Sea | Lander => None,
+            },
+            TileKind::Airport => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Harbour => match movement {
+                Infantry | Mech | Tires | Treads | Air | Sea | Lander => Some(1),
+                Pipe => None,
+            },
+            TileKind::HeadQuarters => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Pipe | TileKind::PipeSeam => match movement {
+                Pipe => Some(1),
+                Infantry | Mech | Tires | Treads | Air | Sea | Lander => None,
+            },
+            TileKind::Silo | TileKind::UsedSilo => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::CommunicationsTower => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Laboratory => match movement {
+                Infantry | Mech | Tires | Treads | Air => Some(1),
+                Sea | Lander | Pipe => None,
+            },
+            TileKind::Teleporter => match movement {
+                Pipe => Some(1),
+                Infantry | Mech | Tires | Treads | Air | Sea | Lander => None,
+            },
+            TileKind::Void => match movement {
+                Infantry | Mech | Tires | Treads | Air | Sea | Lander | Pipe => None,
+            },
+        }
+    }
+
+    fn defense(&self) -> u8 {
+        match self {
+            TileKind::Plain => 1,
+            TileKind::Mountain => 4,
+            TileKind::Forest => 2,
+            TileKind::River => 0,
+            TileKind::Road => 0,
+            TileKind::Bridge => 0,
+            TileKind::Sea => 0,
+            TileKind::Shoal => 0,
+            TileKind::Reef => 1,
+            TileKind::City => 3,
+            TileKind::Base => 3,
+            TileKind::Airport => 3,
+            TileKind::Harbour => 3,
+            TileKind::HeadQuarters => 4,
+            TileKind::Pipe => 0,
+            TileKind::PipeSeam => 0,
+            TileKind::BrokenPipeSeam => 1,
+            TileKind::Silo => 3,
+            TileKind::UsedSilo => 3,
+            TileKind::CommunicationsTower => 3,
+            TileKind::Laboratory => 3,
+            TileKind::Teleporter => 0,
+            TileKind::Void => 0,
+        }
+    }
+}
+
+impl TileKind {
+    /**
+     * Returns true if the tile can be owned and captured.
+     */
+    pub fn is_property(&self) -> bool {
+        matches!(
+            self,
+            TileKind::City
+                | TileKind::Base
+                | TileKind::Airport
+                | TileKind::Harbour
+                | TileKind::HeadQuarters
+                | TileKind::CommunicationsTower
+                | TileKind::Laboratory
+        )
+    }
+}
+
+/**
+ * The terrain of a map without any units or players on it.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Map {
+    /** 1D Vec of the map starting from the top left. */
+    tiles: Vec<TileKind>,
+    /** The (width, height) of the map. */
+    dimensions: (usize, usize),
+}
+
+impl Map {
+    pub fn new(tiles: Vec<TileKind>, dimensions: (usize, usize)) -> Map {
+        Map { tiles, dimensions }
+    }
+
+    pub fn tiles(&self) -> &[TileKind] {
+        &self.tiles
+    }
+
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.dimensions
+    }
+
+    /**
+     * Returns the terrain as a `Grid`, or None if the tiles do not fill the
+     * dimensions.
+     */
+    pub fn to_grid(&self) -> Option<Grid<TileKind>> {
+        Grid::new(self.tiles.clone(), self.dimensions)
+    }
+}
+
+impl From<Grid<TileKind>> for Map {
+    fn from(grid: Grid<TileKind>) -> Map {
+        let dimensions = grid.dimensions();
+        Map::new(grid.into_cells(), dimensions)
+    }
+}
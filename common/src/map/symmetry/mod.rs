@@ -0,0 +1,159 @@
+use crate::map::TileKind;
+use crate::prelude::*;
+use crate::GameState;
+
+/**
+ * A way a map can mirror onto itself.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Symmetry {
+    /** Mirrored left to right: column `x` matches column `width - 1 - x`. */
+    Horizontal,
+    /** Mirrored top to bottom: row `y` matches row `height - 1 - y`. */
+    Vertical,
+    /** Turned half way around the centre, as most two player maps are. */
+    Rotational,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 3] = [
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Rotational,
+    ];
+
+    /**
+     * Returns the tile `index` maps onto on a map of (width, height).
+     */
+    pub fn mirror(self, index: usize, (width, height): (usize, usize)) -> usize {
+        let (x, y) = (index % width, index / width);
+        let (x, y) = match self {
+            Symmetry::Horizontal => (width - 1 - x, y),
+            Symmetry::Vertical => (x, height - 1 - y),
+            Symmetry::Rotational => (width - 1 - x, height - 1 - y),
+        };
+        y * width + x
+    }
+}
+
+/**
+ * Whether a map has a `Symmetry`.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SymmetryCheck {
+    pub symmetry: Symmetry,
+    /** The first pair of tiles, in index order, that do not match. None if
+     * the symmetry holds. */
+    pub violation: Option<(usize, usize)>,
+}
+
+impl SymmetryCheck {
+    pub fn holds(&self) -> bool {
+        self.violation.is_none()
+    }
+}
+
+/**
+ * Checks the terrain of a map of (width, height) against every
+ * `Symmetry`, in the order of `Symmetry::ALL`. Empty if the tiles do not
+ * fill the dimensions.
+ *
+ * Only the kind of each tile is compared, not the direction rivers, roads
+ * or pipes are drawn in.
+ */
+pub fn symmetry(map: &[TileKind], dimensions: (usize, usize)) -> Vec<SymmetryCheck> {
+    check(map.len(), dimensions, |a, b| map[a] == map[b])
+}
+
+fn check(
+    len: usize,
+    dimensions: (usize, usize),
+    matches: impl Fn(usize, usize) -> bool,
+) -> Vec<SymmetryCheck> {
+    if dimensions.0.checked_mul(dimensions.1) != Some(len) {
+        return Vec::new();
+    }
+
+    Symmetry::ALL
+        .iter()
+        .map(|&symmetry| {
+            let violation = (0..len)
+                .map(|index| (index, symmetry.mirror(index, dimensions)))
+                .find(|&(index, mirror)| index < mirror && !matches(index, mirror));
+            SymmetryCheck {
+                symmetry,
+                violation,
+            }
+        })
+        .collect()
+}
+
+impl GameState {
+    /**
+     * Checks the state against every `Symmetry` like `symmetry`, also
+     * requiring mirrored tiles to hold the same kind of unit and to both be
+     * owned or both be unowned. Who owns them is not compared since the
+     * mirror of a player's property is their opponent's.
+     */
+    pub fn symmetry(&self) -> Vec<SymmetryCheck> {
+        check(self.map.len(), self.map.dimensions(), |a, b| {
+            self.map[a] == self.map[b]
+                && self.units.get(&a).map(|unit| &unit.kind)
+                    == self.units.get(&b).map(|unit| &unit.kind)
+                && self.owners.contains_key(&a) == self.owners.contains_key(&b)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::{game_state, map};
+
+    fn holding(checks: &[SymmetryCheck]) -> Vec<Symmetry> {
+        checks
+            .iter()
+            .filter(|check| check.holds())
+            .map(|check| check.symmetry)
+            .collect()
+    }
+
+    #[test]
+    fn terrain_symmetry() {
+        let rotational = map!("f . c\n c . f");
+        let checks = symmetry(rotational.tiles(), rotational.dimensions());
+        assert_eq!(vec![Symmetry::Rotational], holding(&checks));
+        assert_eq!(Some((0, 2)), checks[0].violation);
+        assert_eq!(Some((0, 3)), checks[1].violation);
+
+        let all = map!("f . f\n f . f");
+        assert_eq!(
+            Symmetry::ALL.to_vec(),
+            holding(&symmetry(all.tiles(), all.dimensions()))
+        );
+        assert!(symmetry(&[TileKind::Plain], (2, 2)).is_empty());
+    }
+
+    #[test]
+    fn state_symmetry_compares_units_and_owners() {
+        let mut game_state = game_state!(".0inf c c .1inf");
+        assert_eq!(Symmetry::ALL.to_vec(), holding(&game_state.symmetry()));
+
+        game_state.set_owner(1, Some(0));
+        let checks = game_state.symmetry();
+        assert_eq!(Some((1, 2)), checks[0].violation);
+        assert!(checks[1].holds());
+
+        game_state.set_owner(2, Some(1));
+        assert!(game_state.symmetry()[0].holds());
+    }
+
+    #[test]
+    fn mirror_indices() {
+        assert_eq!(2, Symmetry::Horizontal.mirror(0, (3, 2)));
+        assert_eq!(3, Symmetry::Vertical.mirror(0, (3, 2)));
+        assert_eq!(5, Symmetry::Rotational.mirror(0, (3, 2)));
+        assert_eq!(1, Symmetry::Rotational.mirror(4, (3, 2)));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::grid::Grid;
+use crate::prelude::*;
+
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+const BITS: usize = u64::BITS as usize;
+
+/**
+ * A set of tiles stored as one bit per tile of the map.
+ *
+ * Cheaper to build, combine and compare than a `HashSet<usize>` on large
+ * maps. Tiles past the end of the map are never in the mask.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VisionMask {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl VisionMask {
+    /**
+     * Returns a mask for a map of `len` tiles with none of them set.
+     */
+    pub fn new(len: usize) -> VisionMask {
+        VisionMask {
+            words: vec![0; len.div_ceil(BITS)],
+            len,
+        }
+    }
+
+    /**
+     * Returns a mask for a map of `len` tiles with all of them set.
+     */
+    pub fn full(len: usize) -> VisionMask {
+        let mut mask = VisionMask {
+            words: vec![u64::MAX; len.div_ceil(BITS)],
+            len,
+        };
+        mask.clear_padding();
+        mask
+    }
+
+    /**
+     * Returns a mask for a map of `len` tiles with `tiles` set, ignoring any
+     * past the end of the map.
+     */
+    pub fn from_tiles(len: usize, tiles: impl IntoIterator<Item = usize>) -> VisionMask {
+        let mut mask = VisionMask::new(len);
+        for tile in tiles {
+            mask.insert(tile);
+        }
+        mask
+    }
+
+    /**
+     * Returns the number of tiles on the map, not the number set.
+     */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /**
+     * Returns the number of tiles set.
+     */
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn contains(&self, tile: usize) -> bool {
+        tile < self.len && self.words[tile / BITS] & (1 << (tile % BITS)) != 0
+    }
+
+    /**
+     * Sets `tile`, returning true if it was not already set. Tiles past the
+     * end of the map are ignored.
+     */
+    pub fn insert(&mut self, tile: usize) -> bool {
+        if tile >= self.len {
+            return false;
+        }
+        let was_set = self.contains(tile);
+        self.words[tile / BITS] |= 1 << (tile % BITS);
+        !was_set
+    }
+
+    /**
+     * Clears `tile`, returning true if it was set.
+     */
+    pub fn remove(&mut self, tile: usize) -> bool {
+        if tile >= self.len {
+            return false;
+        }
+        let was_set = self.contains(tile);
+        self.words[tile / BITS] &= !(1 << (tile % BITS));
+        was_set
+    }
+
+    /**
+     * Returns the tiles set in either mask. The result is as long as the
+     * longer of the two.
+     */
+    pub fn union(&self, other: &VisionMask) -> VisionMask {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /**
+     * Returns the tiles set in both masks.
+     */
+    pub fn intersection(&self, other: &VisionMask) -> VisionMask {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /**
+     * Returns the tiles set in this mask but not in `other`.
+     */
+    pub fn difference(&self, other: &VisionMask) -> VisionMask {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /**
+     * Iterates over the tiles set in ascending order.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            let mut word = *word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(index * BITS + bit)
+            })
+        })
+    }
+
+    pub fn to_set(&self) -> HashSet<usize> {
+        self.iter().collect()
+    }
+
+    /**
+     * Returns one byte per tile of the map in order, 1 if the tile is set
+     * and 0 otherwise.
+     */
+    pub fn to_flags(&self) -> Vec<u8> {
+        (0..self.len)
+            .map(|tile| u8::from(self.contains(tile)))
+            .collect()
+    }
+
+    /**
+     * Returns the mask packed eight tiles to a byte, with tile `i` in bit
+     * `i % 8` of byte `i / 8`.
+     */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.len.div_ceil(8))
+            .collect()
+    }
+
+    fn combine(&self, other: &VisionMask, op: impl Fn(u64, u64) -> u64) -> VisionMask {
+        let len = core::cmp::max(self.len, other.len);
+        let word = |mask: &VisionMask, index: usize| mask.words.get(index).copied().unwrap_or(0);
+
+        let mut mask = VisionMask {
+            words: (0..len.div_ceil(BITS))
+                .map(|index| op(word(self, index), word(other, index)))
+                .collect(),
+            len,
+        };
+        mask.clear_padding();
+        mask
+    }
+
+    /**
+     * Returns whether each tile is in the mask as a `Grid` of
+     * (width, height), or None if that is not `len` tiles.
+     */
+    pub fn to_grid(&self, dimensions: (usize, usize)) -> Option<Grid<bool>> {
+        Grid::new(
+            (0..self.len).map(|tile| self.contains(tile)).collect(),
+            dimensions,
+        )
+    }
+
+    fn clear_padding(&mut self) {
+        if !self.len.is_multiple_of(BITS) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << (self.len % BITS)) - 1;
+            }
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the same tiles as `common_vision` as a `VisionMask`.
+     */
+    pub fn common_vision_mask(&self) -> VisionMask {
+        self.common_vision_mask_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Returns the same tiles as `common_vision_with_config` as a
+     * `VisionMask`, built straight from the shared fixed point rather than
+     * through a `HashSet`.
+     */
+    pub fn common_vision_mask_with_config(&self, config: &VisionConfig) -> VisionMask {
+        if !self.settings.fog {
+            return VisionMask::full(self.map.len());
+        }
+
+        match self.common_vision_fixpoint(config).0 {
+            Some(fixpoint) => fixpoint.visible_mask(self.map.len()),
+            // Algorithm is deterministic but avoid unbounded loops.
+            None => VisionMask::new(self.map.len()),
+        }
+    }
+
+    /**
+     * Returns the same tiles as `visible_to_team_with_config` as a
+     * `VisionMask`. Empty if there is no such team.
+     */
+    pub fn visible_to_team_mask(&self, team: usize, config: &VisionConfig) -> VisionMask {
+        if team >= self.teams.len() {
+            return VisionMask::new(self.map.len());
+        }
+        if !self.settings.fog {
+            return VisionMask::full(self.map.len());
+        }
+
+        self.team_masks(self.units.keys().copied(), config)
+            .swap_remove(team)
+    }
+
+    /**
+     * Returns for each team the tiles seen by the units at `locations` or
+     * through `fixed_vision`.
+     */
+    fn team_masks(
+        &self,
+        locations: impl Iterator<Item = usize>,
+        config: &VisionConfig,
+    ) -> Vec<VisionMask> {
+        let mut masks = vec![VisionMask::new(self.map.len()); self.teams.len()];
+
+        for (player, tiles) in self.unit_visions(locations, config) {
+            let Some(team) = self.team_of(player) else {
+                continue;
+            };
+
+            for tile in tiles {
+                masks[team].insert(tile);
+            }
+        }
+
+        for (location, team) in self.fixed_vision(config) {
+            masks[team].insert(location);
+        }
+
+        masks
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::coordinate::Position;
+    use crate::game_state;
+
+    #[test]
+    fn set_operations() {
+        let a = VisionMask::from_tiles(70, [0, 3, 64, 69, 70]);
+        let b = VisionMask::from_tiles(70, [3, 65]);
+
+        assert_eq!(vec![0, 3, 64, 69], a.iter().collect::<Vec<usize>>());
+        assert_eq!(4, a.count());
+        assert!(a.contains(64) && !a.contains(70));
+        assert_eq!(
+            vec![0, 3, 64, 65, 69],
+            a.union(&b).iter().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![3], a.intersection(&b).iter().collect::<Vec<_>>());
+        assert_eq!(vec![0, 64, 69], a.difference(&b).iter().collect::<Vec<_>>());
+
+        assert_eq!(70, VisionMask::full(70).count());
+        assert!(VisionMask::new(70).is_empty());
+
+        let mut c = b.clone();
+        assert!(c.remove(3) && !c.remove(3));
+        assert!(c.insert(3) && !c.insert(3));
+        assert_eq!(b, c);
+
+        let grid = b.to_grid((10, 7)).unwrap();
+        assert!(grid[Position::new(3, 0)] && grid[Position::new(5, 6)]);
+        assert_eq!(2, grid.cells().iter().filter(|tile| **tile).count());
+        assert!(b.to_grid((10, 6)).is_none());
+    }
+
+    #[test]
+    fn flags() {
+        let mask = VisionMask::from_tiles(6, [0, 1, 2, 4]);
+        assert_eq!(vec![1, 1, 1, 0, 1, 0], mask.to_flags());
+        assert!(VisionMask::new(0).to_flags().is_empty());
+    }
+
+    #[test]
+    fn bytes() {
+        let mask = VisionMask::from_tiles(70, [0, 2, 4, 8, 69]);
+        let mut bytes = vec![0; 9];
+        bytes[0] = 0b10101;
+        bytes[1] = 1;
+        bytes[8] = 0b100000;
+        assert_eq!(bytes, mask.to_bytes());
+        assert!(VisionMask::new(0).to_bytes().is_empty());
+    }
+
+    #[test]
+    fn matches_hash_set_results() {
+        let game_state = game_state!(
+            "
+            teams: 0 1,2
+            .0inf . f   .     .
+            c     ~ ~0sub* .2rec .1inf
+            f1inf . ~   ~     ~1sub
+            "
+        );
+        let config = VisionConfig::default();
+
+        assert_eq!(
+            game_state.common_vision(),
+            game_state.common_vision_mask().to_set()
+        );
+        for team in 0..3 {
+            assert_eq!(
+                game_state.visible_to_team_with_config(team, &config),
+                game_state.visible_to_team_mask(team, &config).to_set()
+            );
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::data::UnitData;
+use crate::map::TileKind;
+use crate::unit::MovementKind;
+use crate::GameState;
+
+impl GameState {
+    /**
+     * For a given location returns every tile a unit could step to next,
+     * ignoring terrain costs. Teleporters lead to every other Teleporter.
+     */
+    pub(crate) fn movement_neighbors(&self, location: usize) -> Vec<usize> {
+        let mut tiles = self
+            .neighbors(location, 1)
+            .into_iter()
+            .filter(|tile| *tile != location)
+            .collect::<Vec<usize>>();
+
+        if self.map.cells().get(location) == Some(&TileKind::Teleporter) {
+            tiles.extend(
+                self.map
+                    .cells()
+                    .iter()
+                    .enumerate()
+                    .filter(|(tile, kind)| **kind == TileKind::Teleporter && *tile != location)
+                    .map(|(tile, _)| tile),
+            );
+        }
+
+        tiles
+    }
+
+    /**
+     * Returns every tile that can be reached from any of `sources` with at
+     * most `movement_points` along with the cheapest cost to get there.
+     *
+     * Tiles for which `blocked` returns true cannot be entered.
+     */
+    fn movement_costs(
+        &self,
+        sources: &[usize],
+        movement: MovementKind,
+        movement_points: u8,
+        blocked: impl Fn(usize) -> bool,
+    ) -> HashMap<usize, u8> {
+        let mut costs = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+        for source in sources {
+            costs.insert(*source, 0u8);
+            frontier.push(Reverse((0u8, *source)));
+        }
+
+        while let Some(Reverse((cost, tile))) = frontier.pop() {
+            if costs.get(&tile).map(|best| *best < cost).unwrap_or(false) {
+                continue;
+            }
+
+            for neighbor in self.movement_neighbors(tile) {
+                if blocked(neighbor) {
+                    continue;
+                }
+
+                let Some(step) = self
+                    .map
+                    .cells()
+                    .get(neighbor)
+                    .and_then(|tile_kind| self.weather.movement_cost(tile_kind, movement))
+                else {
+                    continue;
+                };
+
+                let next_cost = cost.saturating_add(step);
+                if next_cost > movement_points {
+                    continue;
+                }
+
+                if costs
+                    .get(&neighbor)
+                    .map(|best| *best <= next_cost)
+                    .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                costs.insert(neighbor, next_cost);
+                frontier.push(Reverse((next_cost, neighbor)));
+            }
+        }
+
+        costs
+    }
+
+    /**
+     * For a given location returns every tile the unit on it could end its
+     * move on along with the movement points needed to get there.
+     *
+     * Units cannot move through enemy units, cannot end their move on a
+     * tile occupied by another unit and cannot spend more fuel than they
+     * have.
+     */
+    pub(crate) fn reachable_tiles(&self, location: usize) -> HashMap<usize, u8> {
+        self.reachable_tiles_where(location, |_| true)
+    }
+
+    /**
+     * Same as `reachable_tiles` but only units on `visible` tiles block or
+     * occupy, so the result is what the owner of the unit believes it can
+     * reach rather than leaking units hidden in fog.
+     */
+    pub(crate) fn reachable_tiles_seeing(
+        &self,
+        location: usize,
+        visible: &HashSet<usize>,
+    ) -> HashMap<usize, u8> {
+        self.reachable_tiles_where(location, |tile| visible.contains(&tile))
+    }
+
+    fn reachable_tiles_where(
+        &self,
+        location: usize,
+        seen: impl Fn(usize) -> bool,
+    ) -> HashMap<usize, u8> {
+        let Some(unit) = self.units.get(&location) else {
+            return HashMap::new();
+        };
+
+        let allies = self.allies_of(unit.player);
+
+        let mut costs = self.movement_costs(
+            &[location],
+            unit.kind.movement_kind(),
+            std::cmp::min(unit.kind.movement_points(), unit.fuel()),
+            |tile| {
+                // Enemy units that are seen block movement.
+                self.units
+                    .get(&tile)
+                    .map(|other| !allies.contains(&other.player) && seen(tile))
+                    .unwrap_or(false)
+            },
+        );
+
+        costs.retain(|tile, _| *tile == location || !self.units.contains_key(tile) || !seen(*tile));
+        costs
+    }
+
+    /**
+     * Returns every tile the unit at `location` could end its move on this
+     * turn, including where it stands, given the terrain and weather. Empty
+     * if there is no unit there.
+     *
+     * Units cannot move through enemy units, cannot end their move on a
+     * tile occupied by another unit and cannot spend more fuel than they
+     * have.
+     */
+    pub fn movement_range(&self, location: usize) -> HashSet<usize> {
+        self.reachable_tiles(location).into_keys().collect()
+    }
+
+    /**
+     * For a given start returns the earliest turn (starting from 1) on which
+     * a unit moving in the given way could end its move on each tile, up to
+     * `max_turns`. The start itself is reached on turn 0.
+     *
+     * Other units are ignored since they will have moved by then.
+     */
+    pub(crate) fn turns_to_reach(
+        &self,
+        start: usize,
+        movement: MovementKind,
+        movement_points: u8,
+        max_turns: u32,
+    ) -> HashMap<usize, u32> {
+        let mut turns = HashMap::new();
+        turns.insert(start, 0);
+
+        let mut frontier = vec![start];
+        for turn in 1..=max_turns {
+            let mut reached = self
+                .movement_costs(&frontier, movement, movement_points, |_| false)
+                .into_keys()
+                .filter(|tile| !turns.contains_key(tile))
+                .collect::<Vec<usize>>();
+
+            if reached.is_empty() {
+                break;
+            }
+
+            reached.sort();
+            for tile in reached.iter() {
+                turns.insert(*tile, turn);
+            }
+            frontier = reached;
+        }
+
+        turns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::unit::MovementKind;
+    use crate::weather::Weather;
+
+    #[test]
+    fn reachable_tiles_terrain_costs() {
+        let game_state = crate::game_state!(".0inf ^ . ~ .1inf");
+
+        let reachable = game_state.reachable_tiles(0);
+        assert_eq!(
+            [0, 1, 2].into_iter().collect::<HashSet<usize>>(),
+            reachable.keys().cloned().collect()
+        );
+        assert_eq!(Some(&3), reachable.get(&2));
+    }
+
+    #[test]
+    fn movement_range_weather_and_fuel() {
+        let mut game_state = crate::game_state!(".0tank . . . . . . .1inf");
+        assert_eq!(
+            (0..=6).collect::<HashSet<usize>>(),
+            game_state.movement_range(0)
+        );
+
+        game_state.set_weather(Weather::Rain);
+        assert_eq!(
+            (0..=3).collect::<HashSet<usize>>(),
+            game_state.movement_range(0)
+        );
+
+        game_state.set_weather(Weather::Clear);
+        game_state.units.get_mut(&0).unwrap().set_fuel(2);
+        assert_eq!(
+            (0..=2).collect::<HashSet<usize>>(),
+            game_state.movement_range(0)
+        );
+        assert!(game_state.movement_range(1).is_empty());
+    }
+
+    #[test]
+    fn reachable_tiles_blocked_by_enemies() {
+        let game_state = crate::game_state!(".0tank .0inf . .1inf .");
+
+        // Allies can be passed through but not stopped on.
+        assert_eq!(
+            [0, 2].into_iter().collect::<HashSet<usize>>(),
+            game_state.reachable_tiles(0).keys().cloned().collect()
+        );
+    }
+
+    #[test]
+    fn turns_to_reach_multiple_turns() {
+        let game_state = crate::game_state!(". . . ^ . ~");
+
+        let turns = game_state.turns_to_reach(0, MovementKind::Infantry, 3, 5);
+        assert_eq!(Some(&0), turns.get(&0));
+        assert_eq!(Some(&1), turns.get(&2));
+        assert_eq!(Some(&2), turns.get(&3));
+        assert_eq!(Some(&2), turns.get(&4));
+        assert_eq!(None, turns.get(&5));
+    }
+
+    #[test]
+    fn reachable_tiles_teleporter() {
+        // Pipe runner at 0 can warp between the teleporters at 2 and 5.
+        let game_state = crate::game_state!("p0pipe p @ x . @ p");
+
+        assert_eq!(
+            [0, 1, 2, 5, 6].into_iter().collect::<HashSet<usize>>(),
+            game_state.reachable_tiles(0).keys().cloned().collect()
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::data::{TileData, UnitData};
+use crate::map::{Map, TileKind};
+use crate::unit::{MovementKind, UnitKind};
+use crate::GameState;
+
+/**
+ * A connected piece of land.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Island {
+    /** Every tile of the island in ascending order. */
+    pub tiles: Vec<usize>,
+    /** True if a Lander built at any Harbour can land units on the island. */
+    pub lander_reachable: bool,
+}
+
+/**
+ * How the sea on a map connects the Harbours and islands.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NavalAnalysis {
+    /** Connected bodies of water that ships can move within. Each basin
+     * lists its tiles in ascending order. */
+    pub basins: Vec<Vec<usize>>,
+    /** For each Harbour which basin (index into `basins`) it opens onto. */
+    pub harbour_basins: Vec<(usize, Option<usize>)>,
+    /** Connected bodies of land. */
+    pub islands: Vec<Island>,
+    /** For each ordered pair of different Harbours (from, to) the fewest
+     * turns a Lander needs to sail between them, or None if it cannot. */
+    pub harbour_travel_turns: Vec<(usize, usize, Option<u32>)>,
+}
+
+impl GameState {
+    /**
+     * Groups the tiles a unit moving in the given way can enter into
+     * connected components.
+     */
+    fn connected_components(&self, movement: MovementKind) -> Vec<Vec<usize>> {
+        let mut component_of = HashMap::new();
+        let mut components = Vec::new();
+
+        for (start, tile) in self.map.cells().iter().enumerate() {
+            if component_of.contains_key(&start) || tile.movement_cost(movement).is_none() {
+                continue;
+            }
+
+            let mut component = BTreeSet::new();
+            let mut frontier = vec![start];
+            component_of.insert(start, components.len());
+
+            while let Some(location) = frontier.pop() {
+                component.insert(location);
+
+                for neighbor in self.movement_neighbors(location) {
+                    if component_of.contains_key(&neighbor)
+                        || self.map[neighbor].movement_cost(movement).is_none()
+                    {
+                        continue;
+                    }
+
+                    component_of.insert(neighbor, components.len());
+                    frontier.push(neighbor);
+                }
+            }
+
+            components.push(component.into_iter().collect());
+        }
+
+        components
+    }
+}
+
+impl Map {
+    /**
+     * Reports which Harbours connect to which bodies of water, which islands
+     * Landers can reach and how long it takes to sail between Harbours.
+     */
+    pub fn naval_analysis(&self) -> NavalAnalysis {
+        let game_state = GameState::from_map(self);
+
+        let harbours = self
+            .tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile == TileKind::Harbour)
+            .map(|(location, _)| location)
+            .collect::<Vec<usize>>();
+
+        // A landlocked Harbour is not a body of water of its own.
+        let basins = game_state
+            .connected_components(MovementKind::Sea)
+            .into_iter()
+            .filter(|basin| {
+                basin
+                    .iter()
+                    .any(|tile| self.tiles()[*tile] != TileKind::Harbour)
+            })
+            .collect::<Vec<Vec<usize>>>();
+        let harbour_basins = harbours
+            .iter()
+            .map(|harbour| {
+                (
+                    *harbour,
+                    basins.iter().position(|basin| basin.contains(harbour)),
+                )
+            })
+            .collect();
+
+        let lander_waters = game_state.connected_components(MovementKind::Lander);
+        let landable = lander_waters
+            .iter()
+            .filter(|water| {
+                harbours.iter().any(|harbour| water.contains(harbour))
+                    && water
+                        .iter()
+                        .any(|tile| self.tiles()[*tile] != TileKind::Harbour)
+            })
+            .flatten()
+            .cloned()
+            .collect::<BTreeSet<usize>>();
+
+        let islands = game_state
+            .connected_components(MovementKind::Infantry)
+            .into_iter()
+            .map(|tiles| {
+                // Landers can only load and unload from beaches and Harbours.
+                let lander_reachable = tiles.iter().any(|tile| landable.contains(tile));
+                Island {
+                    tiles,
+                    lander_reachable,
+                }
+            })
+            .collect();
+
+        let mut harbour_travel_turns = Vec::new();
+        for from in harbours.iter() {
+            let turns = game_state.turns_to_reach(
+                *from,
+                MovementKind::Lander,
+                UnitKind::Lander.movement_points(),
+                self.tiles().len() as u32,
+            );
+
+            for to in harbours.iter() {
+                if from != to {
+                    harbour_travel_turns.push((*from, *to, turns.get(to).cloned()));
+                }
+            }
+        }
+
+        NavalAnalysis {
+            basins,
+            harbour_basins,
+            islands,
+            harbour_travel_turns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn naval_analysis_islands() {
+        // H = Harbour, ~ = Sea, . = Plain
+        //
+        // . H ~ ~ ~ .
+        // . . ~ ~ ~ ~
+        // ~ ~ ~ ~ H .
+        let map = Map::new(
+            vec![
+                TileKind::Plain,
+                TileKind::Harbour,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Plain,
+                TileKind::Plain,
+                TileKind::Plain,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Sea,
+                TileKind::Harbour,
+                TileKind::Plain,
+            ],
+            (6, 3),
+        );
+
+        let analysis = map.naval_analysis();
+
+        assert_eq!(1, analysis.basins.len());
+        assert_eq!(vec![(1, Some(0)), (16, Some(0))], analysis.harbour_basins);
+        assert_eq!(
+            vec![(1, 16, Some(1)), (16, 1, Some(1))],
+            analysis.harbour_travel_turns
+        );
+
+        let reachable = analysis
+            .islands
+            .iter()
+            .map(|island| (island.tiles[0], island.lander_reachable))
+            .collect::<Vec<(usize, bool)>>();
+        assert_eq!(vec![(0, true), (5, false), (16, true)], reachable);
+    }
+
+    #[test]
+    fn naval_analysis_landlocked_harbour() {
+        let map = Map::new(
+            vec![TileKind::Sea, TileKind::Plain, TileKind::Harbour],
+            (3, 1),
+        );
+
+        let analysis = map.naval_analysis();
+
+        assert_eq!(vec![vec![0]], analysis.basins);
+        assert_eq!(vec![(2, None)], analysis.harbour_basins);
+        assert!(!analysis.islands[0].lander_reachable);
+    }
+}
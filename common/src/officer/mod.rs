@@ -1,5 +1,9 @@
+use crate::data::OfficerData;
+use crate::unit::UnitKind;
+use crate::GameState;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PowerKind {
     None,
     Normal,
@@ -7,6 +11,7 @@ pub enum PowerKind {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OfficerKind {
     Andy,
     Hachi,
@@ -37,3 +42,338 @@ pub enum OfficerKind {
     Sturm,
     VonBolt,
 }
+
+impl OfficerData for OfficerKind {
+    const ALL: &'static [OfficerKind] = &[
+        OfficerKind::Andy,
+        OfficerKind::Hachi,
+        OfficerKind::Jake,
+        OfficerKind::Max,
+        OfficerKind::Nell,
+        OfficerKind::Rachel,
+        OfficerKind::Sami,
+        OfficerKind::Colin,
+        OfficerKind::Grit,
+        OfficerKind::Olaf,
+        OfficerKind::Sasha,
+        OfficerKind::Drake,
+        OfficerKind::Eagle,
+        OfficerKind::Javier,
+        OfficerKind::Jess,
+        OfficerKind::Grimm,
+        OfficerKind::Kanbei,
+        OfficerKind::Sensei,
+        OfficerKind::Sonja,
+        OfficerKind::Adder,
+        OfficerKind::Flak,
+        OfficerKind::Hawke,
+        OfficerKind::Jugger,
+        OfficerKind::Kindle,
+        OfficerKind::Koal,
+        OfficerKind::Lash,
+        OfficerKind::Sturm,
+        OfficerKind::VonBolt,
+    ];
+
+    fn vision_bonus(&self, power: &PowerKind) -> u8 {
+        match self {
+            OfficerKind::Sonja => match power {
+                PowerKind::None => 1,
+                PowerKind::Normal | PowerKind::Super => 2,
+            },
+            OfficerKind::Andy
+            | OfficerKind::Hachi
+            | OfficerKind::Jake
+            | OfficerKind::Max
+            | OfficerKind::Nell
+            | OfficerKind::Rachel
+            | OfficerKind::Sami
+            | OfficerKind::Colin
+            | OfficerKind::Grit
+            | OfficerKind::Olaf
+            | OfficerKind::Sasha
+            | OfficerKind::Drake
+            | OfficerKind::Eagle
+            | OfficerKind::Javier
+            | OfficerKind::Jess
+            | OfficerKind::Grimm
+            | OfficerKind::Kanbei
+            | OfficerKind::Sensei
+            | OfficerKind::Adder
+            | OfficerKind::Flak
+            | OfficerKind::Hawke
+            | OfficerKind::Jugger
+            | OfficerKind::Kindle
+            | OfficerKind::Koal
+            | OfficerKind::Lash
+            | OfficerKind::Sturm
+            | OfficerKind::VonBolt => 0,
+        }
+    }
+
+    fn reveals_hidden(&self, power: &PowerKind) -> bool {
+        match self {
+            OfficerKind::Sonja => match power {
+                PowerKind::None => false,
+                PowerKind::Normal | PowerKind::Super => true,
+            },
+            OfficerKind::Andy
+            | OfficerKind::Hachi
+            | OfficerKind::Jake
+            | OfficerKind::Max
+            | OfficerKind::Nell
+            | OfficerKind::Rachel
+            | OfficerKind::Sami
+            | OfficerKind::Colin
+            | OfficerKind::Grit
+            | OfficerKind::Olaf
+            | OfficerKind::Sasha
+            | OfficerKind::Drake
+            | OfficerKind::Eagle
+            | OfficerKind::Javier
+            | OfficerKind::Jess
+            | OfficerKind::Grimm
+            | OfficerKind::Kanbei
+            | OfficerKind::Sensei
+            | OfficerKind::Adder
+            | OfficerKind::Flak
+            | OfficerKind::Hawke
+            | OfficerKind::Jugger
+            | OfficerKind::Kindle
+            | OfficerKind::Koal
+            | OfficerKind::Lash
+            | OfficerKind::Sturm
+            | OfficerKind::VonBolt => false,
+        }
+    }
+
+    fn has_power(&self, power: &PowerKind) -> bool {
+        match self {
+            // Von Bolt only has a super power.
+            OfficerKind::VonBolt => *power != PowerKind::Normal,
+            OfficerKind::Andy
+            | OfficerKind::Hachi
+            | OfficerKind::Jake
+            | OfficerKind::Max
+            | OfficerKind::Nell
+            | OfficerKind::Rachel
+            | OfficerKind::Sami
+            | OfficerKind::Colin
+            | OfficerKind::Grit
+            | OfficerKind::Olaf
+            | OfficerKind::Sasha
+            | OfficerKind::Drake
+            | OfficerKind::Eagle
+            | OfficerKind::Javier
+            | OfficerKind::Jess
+            | OfficerKind::Grimm
+            | OfficerKind::Kanbei
+            | OfficerKind::Sensei
+            | OfficerKind::Sonja
+            | OfficerKind::Adder
+            | OfficerKind::Flak
+            | OfficerKind::Hawke
+            | OfficerKind::Jugger
+            | OfficerKind::Kindle
+            | OfficerKind::Koal
+            | OfficerKind::Lash
+            | OfficerKind::Sturm => true,
+        }
+    }
+
+    fn power_stars(&self, power: &PowerKind) -> u8 {
+        // Stars as listed on AWBW, Von Bolt only has a super power.
+        let (normal, super_power) = match self {
+            OfficerKind::Andy => (3, 6),
+            OfficerKind::Hachi => (3, 5),
+            OfficerKind::Jake => (3, 6),
+            OfficerKind::Max => (3, 6),
+            OfficerKind::Nell => (3, 6),
+            OfficerKind::Rachel => (3, 6),
+            OfficerKind::Sami => (3, 8),
+            OfficerKind::Colin => (2, 6),
+            OfficerKind::Grit => (3, 6),
+            OfficerKind::Olaf => (3, 7),
+            OfficerKind::Sasha => (2, 6),
+            OfficerKind::Drake => (4, 7),
+            OfficerKind::Eagle => (3, 9),
+            OfficerKind::Javier => (3, 6),
+            OfficerKind::Jess => (3, 6),
+            OfficerKind::Grimm => (3, 6),
+            OfficerKind::Kanbei => (4, 7),
+            OfficerKind::Sensei => (2, 6),
+            OfficerKind::Sonja => (3, 5),
+            OfficerKind::Adder => (2, 5),
+            OfficerKind::Flak => (3, 6),
+            OfficerKind::Hawke => (5, 9),
+            OfficerKind::Jugger => (3, 7),
+            OfficerKind::Kindle => (3, 6),
+            OfficerKind::Koal => (3, 5),
+            OfficerKind::Lash => (4, 7),
+            OfficerKind::Sturm => (6, 10),
+            OfficerKind::VonBolt => (0, 10),
+        };
+        match power {
+            PowerKind::None => 0,
+            PowerKind::Normal => normal,
+            PowerKind::Super => super_power,
+        }
+    }
+
+    fn range_bonus(&self, power: &PowerKind, kind: &UnitKind) -> i8 {
+        match self {
+            OfficerKind::Grit => match power {
+                PowerKind::None => 1,
+                PowerKind::Normal => 2,
+                PowerKind::Super => 3,
+            },
+            OfficerKind::Max => -1,
+            OfficerKind::Jake => match power {
+                PowerKind::Normal | PowerKind::Super if !kind.is_naval() => 1,
+                _ => 0,
+            },
+            OfficerKind::Andy
+            | OfficerKind::Hachi
+            | OfficerKind::Nell
+            | OfficerKind::Rachel
+            | OfficerKind::Sami
+            | OfficerKind::Colin
+            | OfficerKind::Olaf
+            | OfficerKind::Sasha
+            | OfficerKind::Sonja
+            | OfficerKind::Drake
+            | OfficerKind::Eagle
+            | OfficerKind::Javier
+            | OfficerKind::Jess
+            | OfficerKind::Grimm
+            | OfficerKind::Kanbei
+            | OfficerKind::Sensei
+            | OfficerKind::Adder
+            | OfficerKind::Flak
+            | OfficerKind::Hawke
+            | OfficerKind::Jugger
+            | OfficerKind::Kindle
+            | OfficerKind::Koal
+            | OfficerKind::Lash
+            | OfficerKind::Sturm
+            | OfficerKind::VonBolt => 0,
+        }
+    }
+
+    fn income_bonus(&self) -> u32 {
+        match self {
+            OfficerKind::Sasha => 100,
+            OfficerKind::Andy
+            | OfficerKind::Hachi
+            | OfficerKind::Jake
+            | OfficerKind::Max
+            | OfficerKind::Nell
+            | OfficerKind::Rachel
+            | OfficerKind::Sami
+            | OfficerKind::Colin
+            | OfficerKind::Grit
+            | OfficerKind::Olaf
+            | OfficerKind::Sonja
+            | OfficerKind::Drake
+            | OfficerKind::Eagle
+            | OfficerKind::Javier
+            | OfficerKind::Jess
+            | OfficerKind::Grimm
+            | OfficerKind::Kanbei
+            | OfficerKind::Sensei
+            | OfficerKind::Adder
+            | OfficerKind::Flak
+            | OfficerKind::Hawke
+            | OfficerKind::Jugger
+            | OfficerKind::Kindle
+            | OfficerKind::Koal
+            | OfficerKind::Lash
+            | OfficerKind::Sturm
+            | OfficerKind::VonBolt => 0,
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the power `player` has active, or None if there is no such
+     * player.
+     */
+    pub fn power(&self, player: usize) -> Option<&PowerKind> {
+        self.players.get(player).map(|player| &player.power)
+    }
+
+    /**
+     * Activates `power` for `player`, emptying their power meter, which lasts
+     * until `deactivate_power` is called when their next turn starts.
+     *
+     * Returns false and changes nothing if there is no such player, `power`
+     * is `PowerKind::None`, their officer does not have it, their meter does
+     * not hold enough charge or a power is already active.
+     */
+    pub fn activate_power(&mut self, player: usize, power: PowerKind) -> bool {
+        self.players
+            .get_mut(player)
+            .is_some_and(|player| player.activate(power))
+    }
+
+    /**
+     * Ends whatever power `player` has active.
+     */
+    pub fn deactivate_power(&mut self, player: usize) {
+        if let Some(player) = self.players.get_mut(player) {
+            player.deactivate();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::builder::GameStateBuilder;
+    use crate::map::{CountryKind, Map, TileKind};
+
+    #[test]
+    fn activate_power_validates() {
+        let mut game_state = GameStateBuilder::new(&Map::new(vec![TileKind::Plain], (1, 1)))
+            .player(CountryKind::OrangeStar, OfficerKind::Sonja, PowerKind::None)
+            .player(CountryKind::BlueMoon, OfficerKind::VonBolt, PowerKind::None)
+            .team([0])
+            .team([1])
+            .build()
+            .unwrap();
+
+        assert!(!game_state.activate_power(0, PowerKind::Normal));
+        game_state.add_charge(0, u32::MAX);
+        game_state.add_charge(1, u32::MAX);
+
+        assert!(!game_state.activate_power(0, PowerKind::None));
+        assert!(!game_state.activate_power(1, PowerKind::Normal));
+        assert!(!game_state.activate_power(2, PowerKind::Super));
+
+        assert!(game_state.activate_power(0, PowerKind::Normal));
+        assert_eq!(Some(&PowerKind::Normal), game_state.power(0));
+        assert_eq!(0, game_state.player_state(0).unwrap().charge());
+        assert!(!game_state.activate_power(0, PowerKind::Super));
+
+        game_state.deactivate_power(0);
+        assert_eq!(Some(&PowerKind::None), game_state.power(0));
+        assert!(!game_state.activate_power(0, PowerKind::Normal));
+        assert!(game_state.activate_power(1, PowerKind::Super));
+    }
+
+    #[test]
+    fn power_changes_vision() {
+        let mut game_state = crate::game_state!(".0inf . . . . .1inf");
+        game_state.players[0].officer = OfficerKind::Sonja;
+        assert!(!game_state.visible_to_team(0).contains(&4));
+
+        game_state.add_charge(0, u32::MAX);
+        assert!(game_state.activate_power(0, PowerKind::Super));
+        assert!(game_state.visible_to_team(0).contains(&4));
+
+        game_state.deactivate_power(0);
+        assert!(!game_state.visible_to_team(0).contains(&4));
+    }
+}
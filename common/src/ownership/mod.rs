@@ -0,0 +1,134 @@
+use crate::prelude::*;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+impl GameState {
+    /**
+     * Returns the player (index) owning the property at `location`, or None
+     * if it is neutral or not a property.
+     */
+    pub fn owner_of(&self, location: usize) -> Option<usize> {
+        self.owners.get(&location).cloned()
+    }
+
+    /**
+     * Hands the property at `location` to `owner`, or makes it neutral if
+     * `owner` is None.
+     *
+     * Returns false and changes nothing if there is no property at
+     * `location`.
+     */
+    pub fn set_owner(&mut self, location: usize, owner: Option<usize>) -> bool {
+        if !self
+            .map
+            .cells()
+            .get(location)
+            .map(|tile| tile.is_property())
+            .unwrap_or(false)
+        {
+            return false;
+        }
+
+        match owner {
+            Some(owner) => self.owners.insert(location, owner),
+            None => self.owners.remove(&location),
+        };
+        true
+    }
+
+    /**
+     * Returns the locations of every property `player` owns in ascending
+     * order.
+     */
+    pub fn properties_of(&self, player: usize) -> Vec<usize> {
+        self.owners
+            .iter()
+            .filter(|(_, owner)| **owner == player)
+            .map(|(location, _)| *location)
+            .collect()
+    }
+
+    /**
+     * Returns each (location, team) where an owned property reveals its own
+     * tile to its owner's team.
+     *
+     * Everyone can see who owns a property so unlike units these count
+     * towards common vision even when nobody else can see the tile.
+     */
+    pub(crate) fn property_vision(&self, config: &VisionConfig) -> Vec<(usize, usize)> {
+        if !config.properties_grant_self_vision {
+            return Vec::new();
+        }
+
+        self.owners
+            .iter()
+            .filter(|(location, _)| **location < self.map.len())
+            .filter_map(|(location, owner)| Some((*location, self.team_of(*owner)?)))
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn set_owner_only_on_properties() {
+        let mut game_state = game_state!(".0inf c . .1inf");
+
+        assert!(game_state.set_owner(1, Some(1)));
+        assert!(!game_state.set_owner(2, Some(1)));
+        assert!(!game_state.set_owner(9, Some(1)));
+        assert_eq!(Some(1), game_state.owner_of(1));
+        assert_eq!(None, game_state.owner_of(2));
+        assert_eq!(vec![1], game_state.properties_of(1));
+
+        assert!(game_state.set_owner(1, None));
+        assert_eq!(None, game_state.owner_of(1));
+    }
+
+    #[test]
+    fn owned_property_sees_itself() {
+        let mut game_state = game_state!(".0inf . . c .1inf");
+        assert_eq!(
+            [0, 1, 2].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team(0)
+        );
+
+        game_state.set_owner(3, Some(0));
+        assert_eq!(
+            [0, 1, 2, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team(0)
+        );
+
+        let config = VisionConfig {
+            properties_grant_self_vision: false,
+            ..VisionConfig::default()
+        };
+        assert_eq!(
+            [0, 1, 2].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team_with_config(0, &config)
+        );
+    }
+
+    #[test]
+    fn owned_properties_join_common_vision() {
+        let mut game_state = game_state!("c .0inf . .1inf c");
+        assert_eq!(
+            [1, 2, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state.common_vision()
+        );
+
+        // Each city is seen by the enemy infantry and by its owner.
+        game_state.set_owner(0, Some(1));
+        game_state.set_owner(4, Some(0));
+        assert_eq!(
+            [0, 1, 2, 3, 4].into_iter().collect::<HashSet<usize>>(),
+            game_state.common_vision()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::data::UnitData;
+use crate::map::TileKind;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * A route across the map.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Path {
+    /** Every tile stepped on in order, starting with where the unit stands
+     * and ending with its destination. */
+    pub tiles: Vec<usize>,
+    /** The movement points needed to follow the whole route. */
+    pub cost: u32,
+}
+
+impl GameState {
+    /**
+     * Finds the cheapest route for the unit at `from` to `to` using the
+     * default `VisionConfig`, see `find_path_with_config`.
+     */
+    pub fn find_path(&self, from: usize, to: usize) -> Option<Path> {
+        self.find_path_with_config(from, to, &VisionConfig::default())
+    }
+
+    /**
+     * Finds the cheapest route for the unit at `from` to `to` over as many
+     * turns as it takes, given the terrain and weather.
+     *
+     * Enemy units only block the route if the unit's team can see them
+     * under the rules given by `config`, so a route may run into a unit
+     * hidden in fog just as a move in a real game would. Fuel is not
+     * checked.
+     *
+     * Returns None if there is no unit at `from`, `to` is off the map or
+     * occupied by a unit the team can see, or `to` cannot be reached.
+     */
+    pub fn find_path_with_config(
+        &self,
+        from: usize,
+        to: usize,
+        config: &VisionConfig,
+    ) -> Option<Path> {
+        let unit = self.units.get(&from)?;
+        if to >= self.map.len() {
+            return None;
+        }
+
+        let allies = self.allies_of(unit.player);
+        let visible = match self.team_of(unit.player) {
+            Some(team) => self.visible_to_team_with_config(team, config),
+            None => self.vision_from_tiles(from, config)?.1,
+        };
+        let blocked = |tile: usize| {
+            self.units
+                .get(&tile)
+                .map(|other| !allies.contains(&other.player) && visible.contains(&tile))
+                .unwrap_or(false)
+        };
+        if to != from && self.units.contains_key(&to) && visible.contains(&to) {
+            return None;
+        }
+
+        // Teleporters join distant tiles so distance would overestimate.
+        let has_teleporters = self.map.cells().contains(&TileKind::Teleporter);
+        let estimate = |tile: usize| {
+            if has_teleporters {
+                0
+            } else {
+                self.distance(tile, to) as u32
+            }
+        };
+
+        let movement = unit.kind.movement_kind();
+        let mut costs = HashMap::from([(from, 0u32)]);
+        let mut came_from = HashMap::new();
+        let mut frontier = BinaryHeap::from([Reverse((estimate(from), 0u32, from))]);
+
+        while let Some(Reverse((_, cost, tile))) = frontier.pop() {
+            if tile == to {
+                let mut tiles = vec![to];
+                while let Some(previous) = came_from.get(tiles.last()?) {
+                    tiles.push(*previous);
+                }
+                tiles.reverse();
+                return Some(Path { tiles, cost });
+            }
+            if costs.get(&tile).map(|best| *best < cost).unwrap_or(false) {
+                continue;
+            }
+
+            for neighbor in self.movement_neighbors(tile) {
+                if blocked(neighbor) {
+                    continue;
+                }
+                let Some(step) = self
+                    .map
+                    .cells()
+                    .get(neighbor)
+                    .and_then(|tile_kind| self.weather.movement_cost(tile_kind, movement))
+                else {
+                    continue;
+                };
+
+                let next_cost = cost + step as u32;
+                if costs
+                    .get(&neighbor)
+                    .map(|best| *best <= next_cost)
+                    .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                costs.insert(neighbor, next_cost);
+                came_from.insert(neighbor, tile);
+                frontier.push(Reverse((
+                    next_cost + estimate(neighbor),
+                    next_cost,
+                    neighbor,
+                )));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn path_around_terrain() {
+        let game_state = game_state!(
+            "
+            .0tank ^ .
+            .      f .
+            .      . .
+            "
+        );
+
+        assert_eq!(
+            Some(Path {
+                tiles: vec![0, 3, 4, 5, 2],
+                cost: 5,
+            }),
+            game_state.find_path(0, 2)
+        );
+        assert_eq!(None, game_state.find_path(1, 2));
+        assert_eq!(None, game_state.find_path(0, 9));
+    }
+
+    #[test]
+    fn hidden_enemies_do_not_block() {
+        let game_state = game_state!(
+            "
+            teams: 0 1
+            .0inf . . . .
+            ^     ^ f1inf ^ .1inf
+            "
+        );
+
+        assert_eq!(
+            Some(Path {
+                tiles: vec![0, 1, 2, 7],
+                cost: 3,
+            }),
+            game_state.find_path(0, 7)
+        );
+        assert_eq!(
+            Some(Path {
+                tiles: vec![0, 1, 2, 3, 4, 9],
+                cost: 5,
+            }),
+            game_state.find_path(0, 9)
+        );
+
+        let game_state = game_state!(".0inf .1inf .");
+        assert_eq!(None, game_state.find_path(0, 2));
+    }
+}
@@ -0,0 +1,327 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::cancel::CancelToken;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * How far along a batch analysis is.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Progress {
+    /** The file that was just finished. */
+    pub path: PathBuf,
+    /** How many files have been finished so far. */
+    pub completed: usize,
+    /** How many files will be analyzed in total. */
+    pub total: usize,
+}
+
+/**
+ * The analysis of a single map or replay.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /** How many states (turns) the file contained. */
+    pub states: usize,
+    /** Average over every state of the smallest team's visible tile count
+     * divided by the largest's. 1.0 means every team sees equally much. */
+    pub vision_parity: f64,
+    /** Number of chokepoints on the (first state's) terrain. */
+    pub chokepoints: usize,
+    /** For each state the number of tiles each team can see, which traces
+     * how the information advantage shifts over a replay. */
+    pub information_curve: Vec<Vec<usize>>,
+}
+
+/**
+ * The aggregated analysis of a directory.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineReport {
+    /** Reports for every file that loaded, ordered by path. */
+    pub files: Vec<FileReport>,
+    /** Files that could not be loaded and why, ordered by path. */
+    pub failures: Vec<(PathBuf, String)>,
+    /** True if the analysis was cancelled before every file was finished,
+     * `files` and `failures` then only hold the finished files. */
+    pub cancelled: bool,
+}
+
+impl PipelineReport {
+    /**
+     * Average vision parity over every analyzed file.
+     */
+    pub fn mean_vision_parity(&self) -> Option<f64> {
+        if self.files.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.files
+                .iter()
+                .map(|file| file.vision_parity)
+                .sum::<f64>()
+                / self.files.len() as f64,
+        )
+    }
+
+    /**
+     * Average chokepoint count over every analyzed file.
+     */
+    pub fn mean_chokepoints(&self) -> Option<f64> {
+        if self.files.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.files
+                .iter()
+                .map(|file| file.chokepoints)
+                .sum::<usize>() as f64
+                / self.files.len() as f64,
+        )
+    }
+}
+
+/**
+ * Analyzes a single file worth of states.
+ */
+pub fn analyze_states(path: &Path, states: &[GameState], config: &VisionConfig) -> FileReport {
+    let information_curve = states
+        .iter()
+        .map(|state| {
+            (0..state.teams.len())
+                .map(|team| state.visible_to_team_with_config(team, config).len())
+                .collect::<Vec<usize>>()
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    let parities = information_curve
+        .iter()
+        .filter_map(|counts| {
+            let max = *counts.iter().max()?;
+            let min = *counts.iter().min()?;
+            Some(if max == 0 {
+                1.0
+            } else {
+                min as f64 / max as f64
+            })
+        })
+        .collect::<Vec<f64>>();
+
+    let vision_parity = if parities.is_empty() {
+        1.0
+    } else {
+        parities.iter().sum::<f64>() / parities.len() as f64
+    };
+
+    FileReport {
+        path: path.to_path_buf(),
+        states: states.len(),
+        vision_parity,
+        chokepoints: states
+            .first()
+            .map(|state| state.terrain().chokepoints().len())
+            .unwrap_or(0),
+        information_curve,
+    }
+}
+
+/**
+ * Analyzes every file in `directory` in parallel.
+ *
+ * `loader` turns a file into the states it holds (one for a map, one per
+ * turn for a replay) or explains why it could not. `progress` is called
+ * from the worker threads after each file is finished.
+ */
+pub fn analyze_directory<L, P>(
+    directory: &Path,
+    config: &VisionConfig,
+    loader: L,
+    progress: P,
+) -> std::io::Result<PipelineReport>
+where
+    L: Fn(&Path) -> Result<Vec<GameState>, String> + Sync,
+    P: Fn(Progress) + Sync,
+{
+    analyze_directory_with_cancel(directory, config, loader, progress, &CancelToken::new())
+}
+
+/**
+ * Same as `analyze_directory` but stops starting new files once `cancel` is
+ * cancelled and returns a report of the files finished so far.
+ */
+pub fn analyze_directory_with_cancel<L, P>(
+    directory: &Path,
+    config: &VisionConfig,
+    loader: L,
+    progress: P,
+    cancel: &CancelToken,
+) -> std::io::Result<PipelineReport>
+where
+    L: Fn(&Path) -> Result<Vec<GameState>, String> + Sync,
+    P: Fn(Progress) + Sync,
+{
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let next = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(paths.len()));
+
+    let workers = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .min(paths.len().max(1));
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if cancel.is_cancelled() {
+                    break;
+                }
+
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+
+                let result = loader(path).map(|states| analyze_states(path, &states, config));
+                results
+                    .lock()
+                    .expect("Pipeline results were poisoned")
+                    .push((path.clone(), result));
+
+                progress(Progress {
+                    path: path.clone(),
+                    completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                    total: paths.len(),
+                });
+            });
+        }
+    });
+
+    let mut results = results
+        .into_inner()
+        .expect("Pipeline results were poisoned");
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut report = PipelineReport {
+        cancelled: results.len() < paths.len(),
+        ..PipelineReport::default()
+    };
+    for (path, result) in results {
+        match result {
+            Ok(file) => report.files.push(file),
+            Err(reason) => report.failures.push((path, reason)),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    use crate::grid::Grid;
+    use crate::map::{CountryKind, TileKind};
+    use crate::officer::{OfficerKind, PowerKind};
+    use crate::player::PlayerState;
+    use crate::ruleset::Ruleset;
+    use crate::settings::GameSettings;
+    use crate::unit::UnitKind;
+    use crate::weather::Weather;
+    use crate::UnitState;
+
+    fn load(path: &Path) -> Result<Vec<GameState>, String> {
+        let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let width = contents
+            .trim()
+            .parse::<usize>()
+            .map_err(|error| error.to_string())?;
+
+        Ok(vec![GameState {
+            map: Grid::new(vec![TileKind::Plain; width], (width, 1))
+                .expect("Tiles do not fill the map"),
+            units: [
+                (0, UnitState::new(0, false, UnitKind::Infantry)),
+                (width - 1, UnitState::new(1, false, UnitKind::Recon)),
+            ]
+            .into_iter()
+            .collect(),
+            players: vec![
+                PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+            ],
+            teams: vec![[0].into_iter().collect(), [1].into_iter().collect()],
+            weather: Weather::Clear,
+            settings: GameSettings::fog_league(),
+            owners: BTreeMap::new(),
+            reveals: Vec::new(),
+            ruleset: Ruleset::Awbw,
+            day: 1,
+            active_player: 0,
+        }])
+    }
+
+    #[test]
+    fn analyze_directory_reports() {
+        let directory = std::env::temp_dir().join(format!("pipeline-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("a.txt"), "12").unwrap();
+        std::fs::write(directory.join("b.txt"), "not a map").unwrap();
+
+        let calls = AtomicUsize::new(0);
+        let report = analyze_directory(&directory, &VisionConfig::default(), load, |progress| {
+            assert_eq!(2, progress.total);
+            calls.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        assert_eq!(1, report.files.len());
+        assert_eq!(1, report.failures.len());
+        assert_eq!(vec![vec![3, 6]], report.files[0].information_curve);
+        assert_eq!(Some(0.5), report.mean_vision_parity());
+        assert!(!report.cancelled);
+    }
+
+    #[test]
+    fn analyze_directory_cancelled() {
+        let directory =
+            std::env::temp_dir().join(format!("pipeline-cancel-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("a.txt"), "12").unwrap();
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let report = analyze_directory_with_cancel(
+            &directory,
+            &VisionConfig::default(),
+            load,
+            |_| {},
+            &cancel,
+        )
+        .unwrap();
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(report.cancelled);
+        assert!(report.files.is_empty());
+    }
+}
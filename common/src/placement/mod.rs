@@ -0,0 +1,205 @@
+use core::fmt;
+
+use crate::data::{TileData, UnitData};
+use crate::map::TileKind;
+use crate::unit::UnitKind;
+use crate::{GameState, UnitState};
+
+/**
+ * Why a unit could not be placed, removed or relocated.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PlacementError {
+    /** The location is not on the map. */
+    OutOfBounds { location: usize },
+    /** There is already a unit at the location. */
+    Occupied { location: usize },
+    /** There is no unit at the location. */
+    NoUnit { location: usize },
+    /** The unit is owned by a player that is not in the game. */
+    UnknownPlayer { location: usize, player: usize },
+    /** The unit could never move onto the terrain at the location. */
+    IllegalTerrain {
+        location: usize,
+        kind: UnitKind,
+        tile: TileKind,
+    },
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlacementError::OutOfBounds { location } => {
+                write!(f, "{} is not on the map", location)
+            }
+            PlacementError::Occupied { location } => {
+                write!(f, "There is already a unit at {}", location)
+            }
+            PlacementError::NoUnit { location } => write!(f, "There is no unit at {}", location),
+            PlacementError::UnknownPlayer { location, player } => write!(
+                f,
+                "Unit for {} is owned by unknown player {}",
+                location, player
+            ),
+            PlacementError::IllegalTerrain {
+                location,
+                kind,
+                tile,
+            } => write!(f, "{:?} at {} can not be on {:?}", kind, location, tile),
+        }
+    }
+}
+
+impl core::error::Error for PlacementError {}
+
+impl GameState {
+    /**
+     * Puts `unit` at `location`.
+     *
+     * Returns an error and changes nothing if `location` is off the map or
+     * occupied, the unit's player is not in the game or the unit could
+     * never move onto the terrain there.
+     */
+    pub fn place_unit(&mut self, location: usize, unit: UnitState) -> Result<(), PlacementError> {
+        if unit.player >= self.players.len() {
+            return Err(PlacementError::UnknownPlayer {
+                location,
+                player: unit.player,
+            });
+        }
+        self.check_destination(location, &unit.kind)?;
+
+        self.units.insert(location, unit);
+        Ok(())
+    }
+
+    /**
+     * Takes the unit at `location`, along with its cargo, off the map.
+     */
+    pub fn remove_unit(&mut self, location: usize) -> Result<UnitState, PlacementError> {
+        self.units
+            .remove(&location)
+            .ok_or(PlacementError::NoUnit { location })
+    }
+
+    /**
+     * Moves the unit at `from` straight to `to`, the path is not checked.
+     *
+     * Returns an error and changes nothing if there is no unit at `from`,
+     * `to` is off the map or occupied or the unit could never move onto
+     * the terrain there.
+     */
+    pub fn relocate_unit(&mut self, from: usize, to: usize) -> Result<(), PlacementError> {
+        let Some(unit) = self.units.get(&from) else {
+            return Err(PlacementError::NoUnit { location: from });
+        };
+        self.check_destination(to, &unit.kind)?;
+
+        if let Some(unit) = self.units.remove(&from) {
+            self.units.insert(to, unit);
+        }
+        Ok(())
+    }
+
+    fn check_destination(&self, location: usize, kind: &UnitKind) -> Result<(), PlacementError> {
+        let Some(tile) = self.checked_tile(location) else {
+            return Err(PlacementError::OutOfBounds { location });
+        };
+        if self.units.contains_key(&location) {
+            return Err(PlacementError::Occupied { location });
+        }
+        if tile.movement_cost(kind.movement_kind()).is_none() {
+            return Err(PlacementError::IllegalTerrain {
+                location,
+                kind: kind.clone(),
+                tile: tile.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the tile at `location` if it is on the map.
+     */
+    fn checked_tile(&self, location: usize) -> Option<&TileKind> {
+        let (width, height) = self.map.dimensions();
+        if location >= width.saturating_mul(height) {
+            return None;
+        }
+        self.map.cells().get(location)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn place_and_remove() {
+        let mut game_state = game_state!(".0inf . ~\n. . .1inf");
+        let tank = UnitState::new(1, false, UnitKind::Tank);
+
+        assert_eq!(Ok(()), game_state.place_unit(1, tank.clone()));
+        assert_eq!(Some(&tank), game_state.unit(1));
+        assert_eq!(
+            Err(PlacementError::Occupied { location: 1 }),
+            game_state.place_unit(1, tank.clone())
+        );
+        assert_eq!(
+            Err(PlacementError::OutOfBounds { location: 6 }),
+            game_state.place_unit(6, tank.clone())
+        );
+        assert_eq!(
+            Err(PlacementError::IllegalTerrain {
+                location: 2,
+                kind: UnitKind::Tank,
+                tile: TileKind::Sea
+            }),
+            game_state.place_unit(2, tank.clone())
+        );
+        assert_eq!(
+            Err(PlacementError::UnknownPlayer {
+                location: 3,
+                player: 2
+            }),
+            game_state.place_unit(3, UnitState::new(2, false, UnitKind::Tank))
+        );
+
+        assert_eq!(Ok(tank), game_state.remove_unit(1));
+        assert_eq!(
+            Err(PlacementError::NoUnit { location: 1 }),
+            game_state.remove_unit(1)
+        );
+        assert_eq!(Ok(()), game_state.validate());
+    }
+
+    #[test]
+    fn relocate() {
+        let mut game_state = game_state!(".0inf . ~\n. . .1inf");
+
+        assert_eq!(Ok(()), game_state.relocate_unit(0, 4));
+        assert!(game_state.unit(0).is_none() && game_state.unit(4).is_some());
+        assert_eq!(
+            Err(PlacementError::Occupied { location: 5 }),
+            game_state.relocate_unit(4, 5)
+        );
+        assert_eq!(
+            Err(PlacementError::Occupied { location: 4 }),
+            game_state.relocate_unit(4, 4)
+        );
+        assert!(matches!(
+            game_state.relocate_unit(4, 2),
+            Err(PlacementError::IllegalTerrain { .. })
+        ));
+        assert_eq!(
+            Err(PlacementError::NoUnit { location: 0 }),
+            game_state.relocate_unit(0, 1)
+        );
+        assert_eq!(
+            "There is already a unit at 5",
+            game_state.relocate_unit(4, 5).unwrap_err().to_string()
+        );
+    }
+}
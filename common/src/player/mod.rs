@@ -0,0 +1,234 @@
+use crate::data::OfficerData;
+use crate::map::CountryKind;
+use crate::officer::{OfficerKind, PowerKind};
+use crate::GameState;
+
+/** Charge each star of the power meter needs before any power is used. */
+pub const STAR_CHARGE: u32 = 9000;
+/** Charge each star needs on top of `STAR_CHARGE` per power used. */
+pub const STAR_CHARGE_INCREASE: u32 = 1800;
+/** Powers used after which stars stop needing more charge. */
+pub const MAX_STAR_INCREASES: u32 = 10;
+
+/**
+ * A player's country, officer and power meter.
+ *
+ * The meter fills with charge, the funds value of damage the player's
+ * units deal and take, up to what their officer's super power costs. A
+ * power can be activated once the meter holds its cost, which empties the
+ * meter and makes every star cost more for the rest of the game, as on
+ * AWBW.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerState {
+    pub(crate) country: CountryKind,
+    pub(crate) officer: OfficerKind,
+    /** The power active until the player's next turn starts. */
+    pub(crate) power: PowerKind,
+    /** Charge in the power meter. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) charge: u32,
+    /** How many powers the player has activated. */
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) powers_used: u32,
+}
+
+impl PlayerState {
+    /**
+     * Returns a player with an empty meter who has never used a power.
+     */
+    pub fn new(country: CountryKind, officer: OfficerKind, power: PowerKind) -> PlayerState {
+        PlayerState {
+            country,
+            officer,
+            power,
+            charge: 0,
+            powers_used: 0,
+        }
+    }
+
+    pub fn country(&self) -> &CountryKind {
+        &self.country
+    }
+
+    pub fn officer(&self) -> &OfficerKind {
+        &self.officer
+    }
+
+    pub fn power(&self) -> &PowerKind {
+        &self.power
+    }
+
+    pub fn charge(&self) -> u32 {
+        self.charge
+    }
+
+    pub fn powers_used(&self) -> u32 {
+        self.powers_used
+    }
+
+    /**
+     * Sets the charge, capped at `max_charge`.
+     */
+    pub fn set_charge(&mut self, charge: u32) {
+        self.charge = core::cmp::min(charge, self.max_charge());
+    }
+
+    pub fn with_charge(mut self, charge: u32) -> PlayerState {
+        self.set_charge(charge);
+        self
+    }
+
+    pub fn with_powers_used(mut self, powers_used: u32) -> PlayerState {
+        self.powers_used = powers_used;
+        self.set_charge(self.charge);
+        self
+    }
+
+    /**
+     * Returns the charge each star of the meter currently needs.
+     */
+    pub fn star_charge(&self) -> u32 {
+        STAR_CHARGE + STAR_CHARGE_INCREASE * core::cmp::min(self.powers_used, MAX_STAR_INCREASES)
+    }
+
+    /**
+     * Returns the charge needed to activate `power`, or None if the officer
+     * does not have it.
+     */
+    pub fn power_cost(&self, power: &PowerKind) -> Option<u32> {
+        if !self.officer.has_power(power) {
+            return None;
+        }
+        Some(u32::from(self.officer.power_stars(power)) * self.star_charge())
+    }
+
+    /**
+     * Returns the most charge the meter holds, what the super power costs.
+     */
+    pub fn max_charge(&self) -> u32 {
+        self.power_cost(&PowerKind::Super).unwrap_or(0)
+    }
+
+    /**
+     * Adds `charge` to the meter, capped at `max_charge`. The meter does not
+     * fill while a power is active.
+     */
+    pub fn add_charge(&mut self, charge: u32) {
+        if self.power == PowerKind::None {
+            self.set_charge(self.charge.saturating_add(charge));
+        }
+    }
+
+    /**
+     * True if no power is active and the meter holds enough charge for
+     * `power`, which the officer has.
+     */
+    pub fn can_activate(&self, power: &PowerKind) -> bool {
+        *power != PowerKind::None
+            && self.power == PowerKind::None
+            && self
+                .power_cost(power)
+                .is_some_and(|cost| self.charge >= cost)
+    }
+
+    /**
+     * Returns the strongest power that could be activated right now, or
+     * `PowerKind::None` if there is none.
+     */
+    pub fn available_power(&self) -> PowerKind {
+        [PowerKind::Super, PowerKind::Normal]
+            .into_iter()
+            .find(|power| self.can_activate(power))
+            .unwrap_or(PowerKind::None)
+    }
+
+    /**
+     * Activates `power`, emptying the meter.
+     *
+     * Returns false and changes nothing if `can_activate` is false.
+     */
+    pub fn activate(&mut self, power: PowerKind) -> bool {
+        if !self.can_activate(&power) {
+            return false;
+        }
+        self.power = power;
+        self.charge = 0;
+        self.powers_used += 1;
+        true
+    }
+
+    /**
+     * Ends whatever power is active.
+     */
+    pub fn deactivate(&mut self) {
+        self.power = PowerKind::None;
+    }
+}
+
+impl GameState {
+    pub fn players(&self) -> &[PlayerState] {
+        &self.players
+    }
+
+    /**
+     * Returns `player`'s country, officer and power meter, or None if there
+     * is no such player.
+     */
+    pub fn player_state(&self, player: usize) -> Option<&PlayerState> {
+        self.players.get(player)
+    }
+
+    /**
+     * Adds `charge` to `player`'s power meter, see `PlayerState::add_charge`.
+     * Does nothing if there is no such player.
+     */
+    pub fn add_charge(&mut self, player: usize, charge: u32) {
+        if let Some(player) = self.players.get_mut(player) {
+            player.add_charge(charge);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meter_fills_to_super_cost() {
+        let mut player =
+            PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None);
+        assert_eq!(Some(27_000), player.power_cost(&PowerKind::Normal));
+        assert_eq!(54_000, player.max_charge());
+        assert_eq!(PowerKind::None, player.available_power());
+
+        player.add_charge(30_000);
+        assert_eq!(PowerKind::Normal, player.available_power());
+        player.add_charge(100_000);
+        assert_eq!(54_000, player.charge());
+        assert_eq!(PowerKind::Super, player.available_power());
+    }
+
+    #[test]
+    fn activating_empties_meter_and_raises_costs() {
+        let mut player =
+            PlayerState::new(CountryKind::BlueMoon, OfficerKind::VonBolt, PowerKind::None)
+                .with_charge(90_000);
+        assert_eq!(None, player.power_cost(&PowerKind::Normal));
+        assert!(!player.activate(PowerKind::Normal));
+
+        assert!(player.activate(PowerKind::Super));
+        assert_eq!((0, 1), (player.charge(), player.powers_used()));
+        assert_eq!(10_800, player.star_charge());
+
+        player.add_charge(9000);
+        assert_eq!(0, player.charge());
+        player.deactivate();
+        player.add_charge(9000);
+        assert_eq!(9000, player.charge());
+
+        let veteran = player.with_powers_used(30);
+        assert_eq!(STAR_CHARGE * 3, veteran.star_charge());
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use crate::GameState;
+
+/**
+ * How well a `StatePool` is recycling allocations.
+ */
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct PoolStats {
+    /** How many states were handed out. */
+    pub states_acquired: usize,
+    /** How many of those reused a released state's allocations. */
+    pub states_reused: usize,
+    /** How many tile sets were handed out. */
+    pub tile_sets_acquired: usize,
+    /** How many of those reused a released set's allocation. */
+    pub tile_sets_reused: usize,
+}
+
+impl PoolStats {
+    /**
+     * The fraction of states handed out that reused allocations.
+     */
+    pub fn state_reuse_rate(&self) -> f64 {
+        rate(self.states_reused, self.states_acquired)
+    }
+
+    /**
+     * The fraction of tile sets handed out that reused allocations.
+     */
+    pub fn tile_set_reuse_rate(&self) -> f64 {
+        rate(self.tile_sets_reused, self.tile_sets_acquired)
+    }
+}
+
+fn rate(reused: usize, acquired: usize) -> f64 {
+    if acquired == 0 {
+        0.0
+    } else {
+        reused as f64 / acquired as f64
+    }
+}
+
+/**
+ * Recycles the allocations of short-lived states and tile sets, which
+ * search workloads create by the million.
+ *
+ * States and sets are handed out with `acquire_*` and given back with
+ * `release_*`, anything not given back is simply dropped as usual.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct StatePool {
+    states: Vec<GameState>,
+    tile_sets: Vec<HashSet<usize>>,
+    /** The most states or tile sets kept around at once. */
+    max_retained: usize,
+    stats: PoolStats,
+}
+
+impl StatePool {
+    /**
+     * Returns a pool that keeps at most `max_retained` released states and
+     * at most `max_retained` released tile sets.
+     */
+    pub fn new(max_retained: usize) -> StatePool {
+        StatePool {
+            max_retained,
+            ..StatePool::default()
+        }
+    }
+
+    /**
+     * Returns a copy of `source`, reusing the buffers of a released state
+     * if there is one.
+     */
+    pub fn acquire_clone(&mut self, source: &GameState) -> GameState {
+        self.stats.states_acquired += 1;
+
+        match self.states.pop() {
+            Some(mut state) => {
+                self.stats.states_reused += 1;
+                state.map.clone_from(&source.map);
+                state.units.clone_from(&source.units);
+                state.owners.clone_from(&source.owners);
+                state.players.clone_from(&source.players);
+                state.teams.clone_from(&source.teams);
+                state.weather = source.weather.clone();
+                state.settings.clone_from(&source.settings);
+                state.reveals.clone_from(&source.reveals);
+                state.ruleset = source.ruleset.clone();
+                state.day = source.day;
+                state.active_player = source.active_player;
+                state
+            }
+            None => source.clone(),
+        }
+    }
+
+    /**
+     * Gives a state back so its buffers can be reused.
+     */
+    pub fn release(&mut self, state: GameState) {
+        if self.states.len() < self.max_retained {
+            self.states.push(state);
+        }
+    }
+
+    /**
+     * Returns an empty tile set, reusing the allocation of a released set
+     * if there is one.
+     */
+    pub fn acquire_tiles(&mut self) -> HashSet<usize> {
+        self.stats.tile_sets_acquired += 1;
+
+        match self.tile_sets.pop() {
+            Some(tiles) => {
+                self.stats.tile_sets_reused += 1;
+                tiles
+            }
+            None => HashSet::new(),
+        }
+    }
+
+    /**
+     * Gives a tile set back so its allocation can be reused.
+     */
+    pub fn release_tiles(&mut self, mut tiles: HashSet<usize>) {
+        if self.tile_sets.len() < self.max_retained {
+            tiles.clear();
+            self.tile_sets.push(tiles);
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{duel, two_versus_two};
+
+    #[test]
+    fn acquire_clone_reuses() {
+        let mut pool = StatePool::new(1);
+        let (first, second) = (duel(), two_versus_two());
+
+        let state = pool.acquire_clone(&first);
+        assert_eq!(first, state);
+        pool.release(state);
+
+        let state = pool.acquire_clone(&second);
+        assert_eq!(second, state);
+
+        // Only one state is retained.
+        pool.release(state);
+        pool.release(first.clone());
+
+        assert_eq!(
+            PoolStats {
+                states_acquired: 2,
+                states_reused: 1,
+                tile_sets_acquired: 0,
+                tile_sets_reused: 0,
+            },
+            pool.stats()
+        );
+        assert_eq!(0.5, pool.stats().state_reuse_rate());
+        assert_eq!(1, pool.states.len());
+    }
+
+    #[test]
+    fn acquire_tiles_is_empty() {
+        let mut pool = StatePool::new(4);
+
+        let mut tiles = pool.acquire_tiles();
+        tiles.extend([1, 2, 3]);
+        let capacity = tiles.capacity();
+        pool.release_tiles(tiles);
+
+        let tiles = pool.acquire_tiles();
+        assert!(tiles.is_empty());
+        assert_eq!(capacity, tiles.capacity());
+        assert_eq!(0.5, pool.stats().tile_set_reuse_rate());
+    }
+}
@@ -0,0 +1,14 @@
+/**
+ * The collections and allocating types the core modules use, from `std`
+ * when it is enabled and otherwise from `alloc` and `hashbrown`, so the
+ * vision algorithm builds the same way for `no_std` targets.
+ */
+pub(crate) use alloc::collections::{BTreeMap, BTreeSet};
+pub(crate) use alloc::string::String;
+pub(crate) use alloc::vec;
+pub(crate) use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::HashSet;
+#[cfg(feature = "std")]
+pub(crate) use std::collections::HashSet;
@@ -0,0 +1,148 @@
+use crate::data::UnitData;
+use crate::knowledge::PlayerView;
+use crate::unit::UnitKind;
+use crate::GameState;
+
+/**
+ * What is known about an enemy's spending over a period of time.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProductionEvidence {
+    /** Funds the enemy was known to have at the start of `since_day`. */
+    pub funds: u32,
+    /** Funds the enemy gains at the start of each of their turns. */
+    pub income: u32,
+    /** The first day being estimated. */
+    pub since_day: u32,
+    /** The last day being estimated (inclusive). */
+    pub today: u32,
+    /** Units the enemy was seen building over the period. */
+    pub visible_builds: Vec<UnitKind>,
+}
+
+/**
+ * Upper bounds on what an enemy could have built out of sight.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ProductionEstimate {
+    /** Funds the enemy could have spent on units that were not seen. */
+    pub unaccounted_funds: u32,
+    /** Locations of production properties the viewer cannot see. */
+    pub unseen_facilities: Vec<usize>,
+    /** For each unit kind the most that could have been built unseen.
+     * Unit kinds that could not have been built are omitted. */
+    pub possible: Vec<(UnitKind, u32)>,
+}
+
+impl GameState {
+    /**
+     * Bounds what could have been produced on properties hidden from `view`
+     * given what is known about an enemy's funds.
+     *
+     * Each property builds at most one unit a day and only units the
+     * state's ruleset and settings allow. Every unseen production property
+     * not owned by the viewer or an ally is assumed to be the enemy's.
+     */
+    pub fn estimate_production(
+        &self,
+        view: &PlayerView,
+        evidence: &ProductionEvidence,
+    ) -> ProductionEstimate {
+        let days = if evidence.today < evidence.since_day {
+            0
+        } else {
+            evidence.today - evidence.since_day + 1
+        };
+
+        let spent: u32 = evidence.visible_builds.iter().map(UnitKind::cost).sum();
+        let unaccounted_funds = evidence
+            .funds
+            .saturating_add(evidence.income.saturating_mul(days))
+            .saturating_sub(spent);
+
+        let buildable = UnitKind::ALL
+            .iter()
+            .filter(|kind| self.ruleset.has_unit(kind) && self.settings.allows_unit(kind))
+            .collect::<Vec<&UnitKind>>();
+
+        let unseen_facilities = self
+            .map
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(location, tile)| {
+                !view.visible_tiles.contains(location)
+                    && !self
+                        .owner_of(*location)
+                        .map(|owner| view.allies.contains(&owner))
+                        .unwrap_or(false)
+                    && buildable.iter().any(|kind| kind.built_at() == **tile)
+            })
+            .map(|(location, _)| location)
+            .collect::<Vec<usize>>();
+
+        let mut possible = Vec::new();
+        for kind in buildable {
+            let facilities = unseen_facilities
+                .iter()
+                .filter(|location| self.map[**location] == kind.built_at())
+                .count() as u32;
+
+            let count = std::cmp::min(
+                unaccounted_funds / kind.cost(),
+                facilities.saturating_mul(days),
+            );
+
+            if count > 0 {
+                possible.push((kind.clone(), count));
+            }
+        }
+
+        ProductionEstimate {
+            unaccounted_funds,
+            unseen_facilities,
+            possible,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ruleset::Ruleset;
+
+    #[test]
+    fn estimate_production_bounds() {
+        let mut game_state = crate::game_state!("teams: 0 1\nq0inf . . . . b");
+        let evidence = ProductionEvidence {
+            funds: 0,
+            income: 9000,
+            since_day: 6,
+            today: 7,
+            visible_builds: vec![UnitKind::Recon],
+        };
+
+        let estimate = game_state.estimate_production(&game_state.player_view(0), &evidence);
+
+        assert_eq!(14000, estimate.unaccounted_funds);
+        assert_eq!(vec![5], estimate.unseen_facilities);
+        assert!(estimate.possible.contains(&(UnitKind::Artillery, 2)));
+        assert!(estimate.possible.contains(&(UnitKind::Tank, 2)));
+        assert!(!estimate
+            .possible
+            .iter()
+            .any(|(kind, _)| *kind == UnitKind::MediumTank || *kind == UnitKind::Fighter));
+        assert!(estimate.possible.contains(&(UnitKind::Flare, 2)));
+
+        // Units the ruleset lacks or the settings ban could not be built.
+        game_state.set_ruleset(Ruleset::Awds);
+        game_state.settings.banned_units = vec![UnitKind::Tank];
+        let estimate = game_state.estimate_production(&game_state.player_view(0), &evidence);
+        assert!(estimate.possible.contains(&(UnitKind::Artillery, 2)));
+        assert!(!estimate
+            .possible
+            .iter()
+            .any(|(kind, _)| *kind == UnitKind::Flare || *kind == UnitKind::Tank));
+    }
+}
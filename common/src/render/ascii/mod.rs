@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use crate::ascii::{tile_symbol, unit_code};
+use crate::GameState;
+
+/** Background of tiles in `visible`. */
+const VISIBLE_BACKGROUND: &str = "\x1b[42m";
+/** Background of every other tile. */
+const HIDDEN_BACKGROUND: &str = "\x1b[100m";
+const RESET: &str = "\x1b[0m";
+/** Foreground colours given to players in order, repeating after the
+ * last. */
+const PLAYER_FOREGROUNDS: [&str; 6] = [
+    "\x1b[91m", "\x1b[94m", "\x1b[92m", "\x1b[93m", "\x1b[95m", "\x1b[96m",
+];
+
+/**
+ * Returns the character a tile is drawn as: the upper case first letter of
+ * the unit's `unit_code` if there is one, otherwise the `tile_symbol`.
+ */
+pub fn tile_glyph(game_state: &GameState, location: usize) -> char {
+    match game_state.units.get(&location) {
+        Some(unit) => unit_code(&unit.kind)
+            .chars()
+            .next()
+            .map(|letter| letter.to_ascii_uppercase())
+            .unwrap_or('?'),
+        None => game_state
+            .map
+            .cells()
+            .get(location)
+            .map(tile_symbol)
+            .unwrap_or(' '),
+    }
+}
+
+/**
+ * Draws the map one row per line with every tile in `visible` as its
+ * `tile_glyph` and every other tile as `-`.
+ */
+pub fn render_ascii(game_state: &GameState, visible: &HashSet<usize>) -> String {
+    render_rows(game_state, |location, glyph| {
+        if visible.contains(&location) {
+            glyph.to_string()
+        } else {
+            "-".to_string()
+        }
+    })
+}
+
+/**
+ * Draws the map like `render_ascii` but shows every glyph, marking the
+ * tiles in `visible` with a green background and the rest with a grey one.
+ * Units are coloured by their player.
+ */
+pub fn render_ansi(game_state: &GameState, visible: &HashSet<usize>) -> String {
+    render_rows(game_state, |location, glyph| {
+        let background = if visible.contains(&location) {
+            VISIBLE_BACKGROUND
+        } else {
+            HIDDEN_BACKGROUND
+        };
+        let foreground = game_state
+            .units
+            .get(&location)
+            .map(|unit| PLAYER_FOREGROUNDS[unit.player % PLAYER_FOREGROUNDS.len()])
+            .unwrap_or("");
+
+        format!("{}{}{}{}", background, foreground, glyph, RESET)
+    })
+}
+
+fn render_rows(game_state: &GameState, mut draw: impl FnMut(usize, char) -> String) -> String {
+    let width = game_state.map.dimensions().0.max(1);
+
+    let mut text = String::new();
+    for location in 0..game_state.map.len() {
+        text.push_str(&draw(location, tile_glyph(game_state, location)));
+        if location % width == width - 1 {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn renders_glyphs_and_visibility() {
+        let game_state = game_state!(".0inf f .1tank\n. . .");
+        let visible = [0, 1, 4].into_iter().collect::<HashSet<usize>>();
+
+        assert_eq!("If-\n-.-\n", render_ascii(&game_state, &visible));
+
+        let ansi = render_ansi(&game_state, &visible);
+        assert!(ansi.starts_with("\x1b[42m\x1b[91mI\x1b[0m\x1b[42mf\x1b[0m"));
+        assert!(ansi.contains("\x1b[100m\x1b[94mT\x1b[0m\n"));
+        assert_eq!(2, ansi.lines().count());
+    }
+}
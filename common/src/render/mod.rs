@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use crate::data::TileData;
+use crate::map::{CountryKind, TileKind};
+
+pub mod ascii;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod svg;
+
+/**
+ * A 24 bit colour.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /**
+     * Mixes `other` into this colour, `amount` 0 keeps this colour and 255
+     * gives `other`.
+     */
+    pub fn blend(self, other: Rgb, amount: u8) -> Rgb {
+        let mix = |from: u8, to: u8| {
+            ((from as u32 * (255 - amount as u32) + to as u32 * amount as u32 + 127) / 255) as u8
+        };
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+/**
+ * Colours used by the renderers.
+ *
+ * Anything missing from `terrain` or `countries` is drawn as `missing`.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Palette {
+    pub terrain: HashMap<TileKind, Rgb>,
+    pub countries: HashMap<CountryKind, Rgb>,
+    /** Colour fogged tiles are shaded towards. */
+    pub fog: Rgb,
+    /** How strongly fogged tiles are shaded, 0 is not at all and 255 hides
+     * the terrain completely. */
+    pub fog_strength: u8,
+    pub missing: Rgb,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::standard()
+    }
+}
+
+impl Palette {
+    /**
+     * Colours close to the ones Advance Wars By Web uses.
+     */
+    pub fn standard() -> Palette {
+        Palette {
+            terrain: TileKind::ALL
+                .iter()
+                .map(|tile| (tile.clone(), standard_terrain(tile)))
+                .collect(),
+            countries: [
+                (CountryKind::OrangeStar, Rgb(0xf0, 0x58, 0x20)),
+                (CountryKind::BlueMoon, Rgb(0x2f, 0x6f, 0xe8)),
+                (CountryKind::GreenEarth, Rgb(0x30, 0xb0, 0x30)),
+                (CountryKind::YellowComet, Rgb(0xe8, 0xd0, 0x20)),
+                (CountryKind::BlackHole, Rgb(0x70, 0x40, 0x90)),
+                (CountryKind::RedFire, Rgb(0xb8, 0x28, 0x28)),
+                (CountryKind::GreySky, Rgb(0x80, 0x80, 0x80)),
+                (CountryKind::BrownDesert, Rgb(0x90, 0x68, 0x40)),
+                (CountryKind::AmberBlaze, Rgb(0xf8, 0xa0, 0x30)),
+                (CountryKind::JadeSun, Rgb(0x90, 0xd0, 0x90)),
+                (CountryKind::CobaltIce, Rgb(0x40, 0x58, 0xb8)),
+                (CountryKind::PinkCosmos, Rgb(0xf0, 0x90, 0xc0)),
+                (CountryKind::TealGalaxy, Rgb(0x30, 0xb0, 0xb0)),
+                (CountryKind::PurpleLightning, Rgb(0xa0, 0x50, 0xe0)),
+                (CountryKind::AcidRain, Rgb(0xa0, 0xc0, 0x30)),
+                (CountryKind::WhiteNova, Rgb(0xf0, 0xf0, 0xf0)),
+                (CountryKind::AzureAsteroid, Rgb(0x60, 0xc8, 0xf0)),
+                (CountryKind::NoirEclipse, Rgb(0x30, 0x30, 0x38)),
+                (CountryKind::SilverClaw, Rgb(0xb0, 0xb8, 0xc0)),
+            ]
+            .into_iter()
+            .collect(),
+            fog: Rgb(0x20, 0x20, 0x30),
+            fog_strength: 128,
+            missing: Rgb(0xff, 0x00, 0xff),
+        }
+    }
+
+    /**
+     * The standard terrain with countries drawn from the Okabe-Ito palette,
+     * which stays distinguishable under the common forms of colour
+     * blindness. Only the first eight countries get distinct colours.
+     */
+    pub fn colorblind_safe() -> Palette {
+        let okabe_ito = [
+            Rgb(0xe6, 0x9f, 0x00),
+            Rgb(0x00, 0x72, 0xb2),
+            Rgb(0x00, 0x9e, 0x73),
+            Rgb(0xf0, 0xe4, 0x42),
+            Rgb(0x00, 0x00, 0x00),
+            Rgb(0x56, 0xb4, 0xe9),
+            Rgb(0xd5, 0x5e, 0x00),
+            Rgb(0xcc, 0x79, 0xa7),
+        ];
+
+        let mut palette = Palette::standard();
+        let mut countries = palette.countries.keys().cloned().collect::<Vec<_>>();
+        countries.sort_by_key(country_order);
+        palette.countries = countries
+            .into_iter()
+            .enumerate()
+            .map(|(index, country)| (country, okabe_ito[index % okabe_ito.len()]))
+            .collect();
+        palette
+    }
+
+    /**
+     * Greyscale terrain with fully saturated countries and heavy fog, for
+     * low vision users.
+     */
+    pub fn high_contrast() -> Palette {
+        let mut palette = Palette::colorblind_safe();
+        for color in palette.terrain.values_mut() {
+            let grey = ((color.0 as u32 + color.1 as u32 + color.2 as u32) / 3) as u8;
+            *color = Rgb(grey, grey, grey);
+        }
+        palette.fog = Rgb(0, 0, 0);
+        palette.fog_strength = 200;
+        palette
+    }
+
+    pub fn terrain_color(&self, tile: &TileKind) -> Rgb {
+        self.terrain.get(tile).cloned().unwrap_or(self.missing)
+    }
+
+    pub fn country_color(&self, country: &CountryKind) -> Rgb {
+        self.countries.get(country).cloned().unwrap_or(self.missing)
+    }
+
+    /**
+     * Returns the colour to draw `tile` with, shaded if it is fogged.
+     */
+    pub fn tile_color(&self, tile: &TileKind, fogged: bool) -> Rgb {
+        let color = self.terrain_color(tile);
+        if fogged {
+            color.blend(self.fog, self.fog_strength)
+        } else {
+            color
+        }
+    }
+}
+
+fn standard_terrain(tile: &TileKind) -> Rgb {
+    match tile {
+        TileKind::Plain => Rgb(0xa8, 0xd8, 0x68),
+        TileKind::Mountain => Rgb(0xa0, 0x78, 0x48),
+        TileKind::Forest => Rgb(0x38, 0x88, 0x38),
+        TileKind::River => Rgb(0x58, 0xa8, 0xe8),
+        TileKind::Road => Rgb(0xc8, 0xc0, 0xa8),
+        TileKind::Bridge => Rgb(0xb0, 0xa8, 0x90),
+        TileKind::Sea => Rgb(0x28, 0x60, 0xc8),
+        TileKind::Shoal => Rgb(0xe8, 0xd8, 0x98),
+        TileKind::Reef => Rgb(0x38, 0x78, 0x90),
+        TileKind::City => Rgb(0xd0, 0xd0, 0xd0),
+        TileKind::Base => Rgb(0xb8, 0xb8, 0xb8),
+        TileKind::Airport => Rgb(0xc0, 0xc8, 0xd0),
+        TileKind::Harbour => Rgb(0xa8, 0xb8, 0xd0),
+        TileKind::HeadQuarters => Rgb(0xf0, 0xe0, 0x90),
+        TileKind::Pipe => Rgb(0x70, 0x70, 0x78),
+        TileKind::PipeSeam => Rgb(0x80, 0x80, 0x90),
+        TileKind::BrokenPipeSeam => Rgb(0x98, 0xa8, 0x68),
+        TileKind::Silo => Rgb(0x98, 0x90, 0x80),
+        TileKind::UsedSilo => Rgb(0x78, 0x70, 0x68),
+        TileKind::CommunicationsTower => Rgb(0xc8, 0xc8, 0xe0),
+        TileKind::Laboratory => Rgb(0xd0, 0xe0, 0xd0),
+        TileKind::Teleporter => Rgb(0x90, 0x40, 0xc0),
+        TileKind::Void => Rgb(0x00, 0x00, 0x00),
+    }
+}
+
+/**
+ * The order countries appear in on Advance Wars By Web.
+ */
+fn country_order(country: &CountryKind) -> usize {
+    match country {
+        CountryKind::OrangeStar => 0,
+        CountryKind::BlueMoon => 1,
+        CountryKind::GreenEarth => 2,
+        CountryKind::YellowComet => 3,
+        CountryKind::BlackHole => 4,
+        CountryKind::RedFire => 5,
+        CountryKind::GreySky => 6,
+        CountryKind::BrownDesert => 7,
+        CountryKind::AmberBlaze => 8,
+        CountryKind::JadeSun => 9,
+        CountryKind::CobaltIce => 10,
+        CountryKind::PinkCosmos => 11,
+        CountryKind::TealGalaxy => 12,
+        CountryKind::PurpleLightning => 13,
+        CountryKind::AcidRain => 14,
+        CountryKind::WhiteNova => 15,
+        CountryKind::AzureAsteroid => 16,
+        CountryKind::NoirEclipse => 17,
+        CountryKind::SilverClaw => 18,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_cover_everything() {
+        for palette in [
+            Palette::standard(),
+            Palette::colorblind_safe(),
+            Palette::high_contrast(),
+        ] {
+            for tile in TileKind::ALL {
+                assert_ne!(palette.missing, palette.terrain_color(tile), "{:?}", tile);
+            }
+            assert_eq!(19, palette.countries.len());
+        }
+    }
+
+    #[test]
+    fn colorblind_safe_first_countries_distinct() {
+        let palette = Palette::colorblind_safe();
+        assert_ne!(
+            palette.country_color(&CountryKind::OrangeStar),
+            palette.country_color(&CountryKind::BlueMoon)
+        );
+        assert_eq!(
+            Rgb(0x00, 0x72, 0xb2),
+            palette.country_color(&CountryKind::BlueMoon)
+        );
+    }
+
+    #[test]
+    fn fog_shades() {
+        let palette = Palette {
+            fog: Rgb(0, 0, 0),
+            fog_strength: 255,
+            ..Palette::standard()
+        };
+        assert_eq!(Rgb(0, 0, 0), palette.tile_color(&TileKind::Plain, true));
+        assert_eq!(
+            palette.terrain_color(&TileKind::Plain),
+            palette.tile_color(&TileKind::Plain, false)
+        );
+        assert_eq!(Rgb(200, 100, 0), Rgb(200, 100, 0).blend(Rgb(0, 0, 0), 0));
+        assert_eq!(Rgb(100, 50, 0), Rgb(200, 100, 0).blend(Rgb(0, 0, 0), 128));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use png::{BitDepth, ColorType, Encoder, EncodingError};
+
+use crate::render::{Palette, Rgb};
+use crate::GameState;
+
+/**
+ * Draws `game_state` as a PNG with each tile a `tile_size` pixel square of
+ * its terrain colour and each unit a smaller square of its country's colour
+ * in the middle of its tile.
+ *
+ * Tiles outside common vision are shaded with the palette's fog, units on
+ * them are drawn too so casters can see what the players cannot.
+ */
+pub fn write_png(
+    game_state: &GameState,
+    palette: &Palette,
+    tile_size: u32,
+    writer: impl Write,
+) -> Result<(), EncodingError> {
+    let (width, height) = game_state.map.dimensions();
+    let pixels = draw(game_state, palette, tile_size, &game_state.common_vision());
+
+    let mut encoder = Encoder::new(writer, width as u32 * tile_size, height as u32 * tile_size);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)
+}
+
+/**
+ * Same as `write_png` but returns the bytes of the PNG.
+ */
+pub fn to_png(
+    game_state: &GameState,
+    palette: &Palette,
+    tile_size: u32,
+) -> Result<Vec<u8>, EncodingError> {
+    let mut bytes = Vec::new();
+    write_png(game_state, palette, tile_size, &mut bytes)?;
+    Ok(bytes)
+}
+
+/**
+ * Returns the RGB pixels of the image row by row.
+ */
+fn draw(
+    game_state: &GameState,
+    palette: &Palette,
+    tile_size: u32,
+    visible: &HashSet<usize>,
+) -> Vec<u8> {
+    let (width, height) = game_state.map.dimensions();
+    let tile_size = tile_size as usize;
+    let row_pixels = width * tile_size;
+    // Units cover the middle half of their tile.
+    let unit_margin = tile_size / 4;
+
+    let mut pixels = vec![0; row_pixels * height * tile_size * 3];
+    for (location, tile) in game_state.map.cells().iter().enumerate() {
+        let fogged = !visible.contains(&location);
+        let unit_color = game_state.units.get(&location).map(|unit| {
+            let color = game_state
+                .players
+                .get(unit.player)
+                .map(|player| palette.country_color(player.country()))
+                .unwrap_or(palette.missing);
+            if fogged {
+                color.blend(palette.fog, palette.fog_strength)
+            } else {
+                color
+            }
+        });
+        let tile_color = palette.tile_color(tile, fogged);
+
+        let (left, top) = (
+            (location % width) * tile_size,
+            (location / width) * tile_size,
+        );
+        for y in 0..tile_size {
+            for x in 0..tile_size {
+                let in_unit = (unit_margin..tile_size - unit_margin).contains(&x)
+                    && (unit_margin..tile_size - unit_margin).contains(&y);
+                let Rgb(red, green, blue) = match unit_color {
+                    Some(color) if in_unit => color,
+                    _ => tile_color,
+                };
+
+                let offset = ((top + y) * row_pixels + left + x) * 3;
+                pixels[offset..offset + 3].copy_from_slice(&[red, green, blue]);
+            }
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::map::TileKind;
+
+    fn decode(bytes: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    fn pixel(pixels: &[u8], image_width: u32, x: u32, y: u32) -> Rgb {
+        let offset = ((y * image_width + x) * 3) as usize;
+        Rgb(pixels[offset], pixels[offset + 1], pixels[offset + 2])
+    }
+
+    #[test]
+    fn draws_terrain_units_and_fog() {
+        let game_state = game_state!(".0inf . .1inf\n. . .\n. . .");
+        let palette = Palette::standard();
+        let (width, height, pixels) = decode(&to_png(&game_state, &palette, 4).unwrap());
+        assert_eq!((12, 12), (width, height));
+
+        let plain = palette.terrain_color(&TileKind::Plain);
+        let country = palette.country_color(&game_state.players[0].country);
+        assert_eq!(plain, pixel(&pixels, width, 0, 0));
+        assert_eq!(country, pixel(&pixels, width, 1, 1));
+
+        let fogged = (0..game_state.map.len())
+            .find(|tile| !game_state.common_vision().contains(tile))
+            .unwrap();
+        let (x, y) = ((fogged % 3) as u32 * 4, (fogged / 3) as u32 * 4);
+        assert_eq!(
+            palette.tile_color(&TileKind::Plain, true),
+            pixel(&pixels, width, x, y)
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::fmt::{Debug, Write};
+
+use crate::render::{Palette, Rgb};
+use crate::GameState;
+
+/**
+ * Draws `game_state` as an SVG with one unit of the view box per tile.
+ *
+ * The image is made of groups, bottom to top, with the classes `terrain`,
+ * `units`, `team-vision team-N` for each team and `common-vision`. Every
+ * shape has classes naming what it is, e.g. `tile forest` or
+ * `unit player-0 infantry`, so frontends can restyle it with CSS. Shapes
+ * are filled from `palette` and the vision layers are hidden except
+ * common vision, which shades the tiles outside it with the palette's fog.
+ */
+pub fn to_svg(game_state: &GameState, palette: &Palette) -> String {
+    let (width, height) = game_state.map.dimensions();
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}">"#,
+        width,
+        height,
+        width * 16,
+        height * 16
+    );
+
+    svg.push_str("<g class=\"terrain\">\n");
+    for (location, tile) in game_state.map.cells().iter().enumerate() {
+        rect(
+            &mut svg,
+            game_state,
+            location,
+            &format!("tile {}", css_name(tile)),
+            palette.terrain_color(tile),
+            "",
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str("<g class=\"units\">\n");
+    for (location, unit) in game_state.units.iter() {
+        let color = game_state
+            .players
+            .get(unit.player)
+            .map(|player| palette.country_color(player.country()))
+            .unwrap_or(palette.missing);
+        let (x, y) = (location % width.max(1), location / width.max(1));
+        let _ = writeln!(
+            svg,
+            r#"<circle class="unit player-{} {}" cx="{}.5" cy="{}.5" r="0.3" fill="{}"/>"#,
+            unit.player,
+            css_name(&unit.kind),
+            x,
+            y,
+            hex(color)
+        );
+    }
+    svg.push_str("</g>\n");
+
+    for team in 0..game_state.teams.len() {
+        let _ = writeln!(
+            svg,
+            "<g class=\"team-vision team-{}\" visibility=\"hidden\">",
+            team
+        );
+        vision_layer(
+            &mut svg,
+            game_state,
+            &game_state.visible_to_team(team),
+            palette,
+        );
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("<g class=\"common-vision\">\n");
+    vision_layer(&mut svg, game_state, &game_state.common_vision(), palette);
+    svg.push_str("</g>\n");
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/**
+ * Shades every tile not in `visible`, marking it `fog`.
+ */
+fn vision_layer(
+    svg: &mut String,
+    game_state: &GameState,
+    visible: &HashSet<usize>,
+    palette: &Palette,
+) {
+    let opacity = format!(
+        r#" fill-opacity="{:.2}""#,
+        palette.fog_strength as f32 / 255.0
+    );
+    for location in (0..game_state.map.len()).filter(|location| !visible.contains(location)) {
+        rect(svg, game_state, location, "fog", palette.fog, &opacity);
+    }
+}
+
+fn rect(
+    svg: &mut String,
+    game_state: &GameState,
+    location: usize,
+    class: &str,
+    color: Rgb,
+    attributes: &str,
+) {
+    let width = game_state.map.dimensions().0.max(1);
+    let _ = writeln!(
+        svg,
+        r#"<rect class="{}" x="{}" y="{}" width="1" height="1" fill="{}"{}/>"#,
+        class,
+        location % width,
+        location / width,
+        hex(color),
+        attributes
+    );
+}
+
+fn hex(Rgb(red, green, blue): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+/**
+ * Turns a variant name such as `HeadQuarters` into `head-quarters`.
+ */
+fn css_name(value: &impl Debug) -> String {
+    let mut name = String::new();
+    for (index, character) in format!("{:?}", value).chars().enumerate() {
+        if character.is_ascii_uppercase() && index > 0 {
+            name.push('-');
+        }
+        name.push(character.to_ascii_lowercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::map::TileKind;
+
+    #[test]
+    fn layers_and_classes() {
+        let game_state = game_state!(".0inf q .1tank\n. . .");
+        let svg = to_svg(&game_state, &Palette::standard());
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 3 2""#));
+        assert!(svg.contains(r#"<rect class="tile head-quarters" x="1" y="0""#));
+        assert!(svg.contains(r#"<circle class="unit player-1 tank" cx="2.5" cy="0.5""#));
+        assert!(svg.contains("<g class=\"team-vision team-1\" visibility=\"hidden\">"));
+
+        let common = &svg[svg.find("<g class=\"common-vision\">").unwrap()..];
+        let fogged = game_state.map.len() - game_state.common_vision().len();
+        assert_eq!(fogged, common.matches("class=\"fog\"").count());
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn css_names() {
+        assert_eq!("plain", css_name(&TileKind::Plain));
+        assert_eq!("head-quarters", css_name(&TileKind::HeadQuarters));
+        assert_eq!("#0a0b0c", hex(Rgb(10, 11, 12)));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use crate::error::VisionError;
+use crate::tracker::VisionDelta;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * The version of the report layout, bumped whenever a field is removed or
+ * changes meaning. Adding a field does not change the version.
+ */
+pub const REPORT_VERSION: u32 = 1;
+
+/**
+ * JSON Schema describing every report at `REPORT_VERSION`.
+ */
+pub const REPORT_SCHEMA: &str = include_str!("schema.json");
+
+/**
+ * The tiles every team can see, as emitted by the CLI and the bindings.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommonVisionReport {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    /** Row-major indices of the visible tiles in ascending order. */
+    pub tiles: Vec<usize>,
+}
+
+/**
+ * The tiles a single team can see.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeamVisionReport {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    pub team: usize,
+    /** Row-major indices of the visible tiles in ascending order. */
+    pub tiles: Vec<usize>,
+}
+
+/**
+ * How common vision changed between two states.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VisionDiffReport {
+    pub version: u32,
+    /** Tiles that became commonly visible in ascending order. */
+    pub entered: Vec<usize>,
+    /** Tiles that stopped being commonly visible in ascending order. */
+    pub left: Vec<usize>,
+}
+
+impl From<VisionDelta> for VisionDiffReport {
+    fn from(delta: VisionDelta) -> Self {
+        VisionDiffReport {
+            version: REPORT_VERSION,
+            entered: delta.entered,
+            left: delta.left,
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Reports `try_common_vision_with_config` under `config`.
+     */
+    pub fn common_vision_report(
+        &self,
+        config: &VisionConfig,
+    ) -> Result<CommonVisionReport, VisionError> {
+        let (width, height) = self.map.dimensions();
+        Ok(CommonVisionReport {
+            version: REPORT_VERSION,
+            width,
+            height,
+            tiles: sorted(self.try_common_vision_with_config(config)?),
+        })
+    }
+
+    /**
+     * Reports `try_visible_to_team_with_config` for `team` under `config`.
+     */
+    pub fn team_vision_report(
+        &self,
+        team: usize,
+        config: &VisionConfig,
+    ) -> Result<TeamVisionReport, VisionError> {
+        let (width, height) = self.map.dimensions();
+        Ok(TeamVisionReport {
+            version: REPORT_VERSION,
+            width,
+            height,
+            team,
+            tiles: sorted(self.try_visible_to_team_with_config(team, config)?),
+        })
+    }
+
+    /**
+     * Reports `team_vision_report` for every team in order.
+     */
+    pub fn team_vision_reports(
+        &self,
+        config: &VisionConfig,
+    ) -> Result<Vec<TeamVisionReport>, VisionError> {
+        (0..self.teams.len())
+            .map(|team| self.team_vision_report(team, config))
+            .collect()
+    }
+}
+
+fn sorted(tiles: HashSet<usize>) -> Vec<usize> {
+    let mut tiles = tiles.into_iter().collect::<Vec<usize>>();
+    tiles.sort();
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn reports_match_vision() {
+        let game_state = game_state!(".0inf f .1inf\n. . .");
+        let config = VisionConfig::default();
+
+        assert_eq!(
+            CommonVisionReport {
+                version: REPORT_VERSION,
+                width: 3,
+                height: 2,
+                tiles: vec![0, 1, 2, 4],
+            },
+            game_state.common_vision_report(&config).unwrap()
+        );
+
+        let teams = game_state.team_vision_reports(&config).unwrap();
+        assert_eq!(2, teams.len());
+        assert_eq!(1, teams[1].team);
+        assert_eq!(sorted(game_state.visible_to_team(1)), teams[1].tiles);
+        assert_eq!(
+            Err(VisionError::UnknownTeam { team: 2 }),
+            game_state.team_vision_report(2, &config)
+        );
+    }
+
+    #[test]
+    fn diff_report_from_delta() {
+        let delta = VisionDelta {
+            entered: vec![1, 4],
+            left: vec![2],
+        };
+
+        assert_eq!(
+            VisionDiffReport {
+                version: REPORT_VERSION,
+                entered: vec![1, 4],
+                left: vec![2],
+            },
+            VisionDiffReport::from(delta)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reports_serialize_to_schema_layout() {
+        let game_state = game_state!(".0inf f .1inf\n. . .");
+        let report = game_state
+            .common_vision_report(&VisionConfig::default())
+            .unwrap();
+
+        assert_eq!(
+            r#"{"version":1,"width":3,"height":2,"tiles":[0,1,2,4]}"#,
+            serde_json::to_string(&report).unwrap()
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(REPORT_SCHEMA).is_ok());
+    }
+}
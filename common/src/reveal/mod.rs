@@ -0,0 +1,259 @@
+use crate::map::TileKind;
+use crate::prelude::*;
+use crate::unit::UnitKind;
+use crate::GameState;
+
+/**
+ * How far from a Flare its flare can be fired.
+ */
+pub const FLARE_RANGE: usize = 5;
+
+/**
+ * The radius of the diamond a flare lights up, 13 tiles in total.
+ */
+pub const FLARE_RADIUS: usize = 2;
+
+/**
+ * The radius of the diamond a Silo's missile hits, 13 tiles in total.
+ */
+pub const SILO_RADIUS: usize = 2;
+
+/**
+ * An area that is revealed for the rest of the turn, such as by a flare.
+ *
+ * Every tile within `radius` of `center` is seen even if it would otherwise
+ * hide units, apart from tiles holding a stealthed unit which are only
+ * revealed by adjacent units.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reveal {
+    /** The player whose team sees the area, or None if every team does. */
+    pub player: Option<usize>,
+    pub center: usize,
+    pub radius: usize,
+}
+
+impl GameState {
+    /**
+     * Returns the areas revealed for the rest of the turn.
+     */
+    pub fn reveals(&self) -> &[Reveal] {
+        &self.reveals
+    }
+
+    /**
+     * Reveals an area for the rest of the turn.
+     */
+    pub fn add_reveal(&mut self, reveal: Reveal) {
+        self.reveals.push(reveal);
+    }
+
+    /**
+     * Forgets every revealed area, for when the turn ends.
+     */
+    pub fn clear_reveals(&mut self) {
+        self.reveals.clear();
+    }
+
+    /**
+     * Fires the flare of the Flare at `location` at `target`, using one of
+     * its ammo and lighting up the tiles around it for its owner's team for
+     * the rest of the turn.
+     *
+     * Returns false and changes nothing if there is no Flare at `location`,
+     * it has no ammo left or `target` is not within `FLARE_RANGE` of it.
+     */
+    pub fn fire_flare(&mut self, location: usize, target: usize) -> bool {
+        let Some(unit) = self.units.get(&location) else {
+            return false;
+        };
+        if unit.kind != UnitKind::Flare || unit.ammo() == 0 {
+            return false;
+        }
+
+        let player = unit.player;
+        if !self
+            .checked_neighbors(location, FLARE_RANGE)
+            .map(|tiles| tiles.contains(&target))
+            .unwrap_or(false)
+        {
+            return false;
+        }
+
+        if let Some(unit) = self.units.get_mut(&location) {
+            let ammo = unit.ammo() - 1;
+            unit.set_ammo(ammo);
+        }
+        self.add_reveal(Reveal {
+            player: Some(player),
+            center: target,
+            radius: FLARE_RADIUS,
+        });
+        true
+    }
+
+    /**
+     * Launches the missile of the Silo at `location`, which must have an
+     * Infantry or Mech on it, at `target` anywhere on the map.
+     *
+     * The Silo is used up and the blast area is revealed to every team for
+     * the rest of the turn.
+     *
+     * Returns false and changes nothing if the launch is not possible.
+     */
+    pub fn launch_silo(&mut self, location: usize, target: usize) -> bool {
+        if self.map.cells().get(location) != Some(&TileKind::Silo)
+            || target >= self.map.len()
+            || !self
+                .units
+                .get(&location)
+                .map(|unit| unit.kind.can_capture())
+                .unwrap_or(false)
+        {
+            return false;
+        }
+
+        self.map[location] = TileKind::UsedSilo;
+        self.add_reveal(Reveal {
+            player: None,
+            center: target,
+            radius: SILO_RADIUS,
+        });
+        true
+    }
+
+    /**
+     * Returns each (location, team) seen through a revealed area.
+     */
+    pub(crate) fn reveal_vision(&self) -> Vec<(usize, usize)> {
+        let mut vision = Vec::new();
+
+        for reveal in self.reveals.iter() {
+            let teams = match reveal.player {
+                Some(player) => self.team_of(player).into_iter().collect(),
+                None => (0..self.teams.len()).collect::<Vec<usize>>(),
+            };
+
+            let Some(tiles) = self.checked_neighbors(reveal.center, reveal.radius) else {
+                continue;
+            };
+
+            for tile in tiles {
+                if self.map[tile] == TileKind::Void
+                    || self
+                        .units
+                        .get(&tile)
+                        .map(|unit| unit.stealthed)
+                        .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                vision.extend(teams.iter().map(|team| (tile, *team)));
+            }
+        }
+
+        vision
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::prelude::HashSet;
+
+    use super::*;
+
+    use crate::data::UnitData;
+    use crate::game_state;
+
+    #[test]
+    fn fire_flare_validates() {
+        let mut game_state = game_state!(".0flare . . . . . .0inf . .1inf");
+
+        assert!(!game_state.fire_flare(6, 7));
+        assert!(!game_state.fire_flare(0, 6));
+        assert!(game_state.fire_flare(0, 5));
+        assert_eq!(
+            &[Reveal {
+                player: Some(0),
+                center: 5,
+                radius: FLARE_RADIUS,
+            }],
+            game_state.reveals()
+        );
+
+        game_state.clear_reveals();
+        assert!(game_state.reveals().is_empty());
+
+        // Every flare uses one ammo and none can be fired without any.
+        let ammo = game_state.unit(0).unwrap().ammo();
+        assert_eq!(UnitKind::Flare.max_ammo() - 1, ammo);
+        for _ in 0..ammo {
+            assert!(game_state.fire_flare(0, 1));
+        }
+        assert_eq!(0, game_state.unit(0).unwrap().ammo());
+        game_state.clear_reveals();
+        assert!(!game_state.fire_flare(0, 1));
+        assert!(game_state.reveals().is_empty());
+    }
+
+    #[test]
+    fn flare_lights_up_forests() {
+        let mut game_state = game_state!(
+            "
+            .0flare . f f f
+            .       f f f f
+            .       . f f f1inf
+            "
+        );
+        assert!(!game_state.visible_to_team(0).contains(&8));
+
+        assert!(game_state.fire_flare(0, 8));
+        let visible = game_state.visible_to_team(0);
+        for tile in [3, 6, 7, 8, 9, 13] {
+            assert!(visible.contains(&tile));
+        }
+        assert!(!visible.contains(&11));
+    }
+
+    #[test]
+    fn launch_silo_reveals_blast() {
+        let mut game_state = game_state!(
+            "
+            s0inf . . . . . f . . .1inf
+            s     . . . . . . . . .
+            "
+        );
+
+        assert!(!game_state.launch_silo(10, 6));
+        assert!(!game_state.launch_silo(0, 20));
+        assert!(game_state.launch_silo(0, 6));
+        assert_eq!(TileKind::UsedSilo, game_state.terrain().tiles()[0]);
+        assert!(!game_state.launch_silo(0, 6));
+
+        for team in [0, 1] {
+            let visible = game_state.visible_to_team(team);
+            for tile in [4, 5, 6, 7, 8, 15, 16, 17] {
+                assert!(visible.contains(&tile));
+            }
+        }
+    }
+
+    #[test]
+    fn reveal_for_everyone() {
+        let mut game_state = game_state!(".0inf . . . . . .1inf");
+        game_state.add_reveal(Reveal {
+            player: None,
+            center: 3,
+            radius: 0,
+        });
+
+        assert!(game_state.visible_to_team(0).contains(&3));
+        assert!(game_state.visible_to_team(1).contains(&3));
+        assert_eq!(
+            [3].into_iter().collect::<HashSet<usize>>(),
+            game_state.common_vision()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::data::OfficerData;
+use crate::map::TileKind;
+use crate::officer::{OfficerKind, PowerKind};
+use crate::unit::UnitKind;
+use crate::GameState;
+
+/**
+ * Which game's rules a state follows.
+ *
+ * The rulesets differ in which units and officers exist. The unit vision
+ * table and the officer and terrain vision bonuses, such as Sonja's +1
+ * every day and Infantry and Mech seeing further from a Mountain, are
+ * shared by every ruleset.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ruleset {
+    /** Advance Wars By Web, every unit and officer. */
+    #[default]
+    Awbw,
+    /** Advance Wars 2: Black Hole Rising. */
+    Aw2,
+    /** Advance Wars: Dual Strike, every unit apart from the Flare. */
+    Awds,
+}
+
+impl Ruleset {
+    pub const ALL: &'static [Ruleset] = &[Ruleset::Awbw, Ruleset::Aw2, Ruleset::Awds];
+
+    /**
+     * Returns true if `kind` can be built under this ruleset.
+     */
+    pub fn has_unit(&self, kind: &UnitKind) -> bool {
+        match self {
+            Ruleset::Awbw => true,
+            Ruleset::Aw2 => !matches!(
+                kind,
+                UnitKind::BlackBoat
+                    | UnitKind::BlackBomb
+                    | UnitKind::Carrier
+                    | UnitKind::Flare
+                    | UnitKind::MegaTank
+                    | UnitKind::PipeRunner
+                    | UnitKind::Stealth
+            ),
+            Ruleset::Awds => *kind != UnitKind::Flare,
+        }
+    }
+
+    /**
+     * Returns true if `officer` can be picked under this ruleset.
+     */
+    pub fn has_officer(&self, officer: &OfficerKind) -> bool {
+        match self {
+            Ruleset::Awbw | Ruleset::Awds => true,
+            Ruleset::Aw2 => !matches!(
+                officer,
+                OfficerKind::Grimm
+                    | OfficerKind::Jake
+                    | OfficerKind::Javier
+                    | OfficerKind::Jugger
+                    | OfficerKind::Kindle
+                    | OfficerKind::Koal
+                    | OfficerKind::Rachel
+                    | OfficerKind::Sasha
+                    | OfficerKind::VonBolt
+            ),
+        }
+    }
+
+    /**
+     * Returns the extra vision `kind` gets from standing on `tile`, 3 for
+     * Infantry and Mech on a Mountain under every ruleset.
+     */
+    pub fn terrain_vision_bonus(&self, kind: &UnitKind, tile: &TileKind) -> u8 {
+        match self {
+            Ruleset::Awbw | Ruleset::Aw2 | Ruleset::Awds => kind.terrain_vision_bonus(tile),
+        }
+    }
+
+    /**
+     * Returns the extra vision `officer` gives their units with `power`
+     * active, the same under every ruleset.
+     */
+    pub fn officer_vision_bonus(&self, officer: &OfficerKind, power: &PowerKind) -> u8 {
+        match self {
+            Ruleset::Awbw | Ruleset::Aw2 | Ruleset::Awds => officer.vision_bonus(power),
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the rules the state follows.
+     */
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    /**
+     * Changes the rules the state follows.
+     */
+    pub fn set_ruleset(&mut self, ruleset: Ruleset) {
+        self.ruleset = ruleset;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::data::UnitData;
+
+    #[test]
+    fn awbw_has_everything() {
+        for kind in UnitKind::ALL {
+            assert!(Ruleset::Awbw.has_unit(kind));
+        }
+        for officer in OfficerKind::ALL {
+            assert!(Ruleset::Awbw.has_officer(officer));
+            for power in [PowerKind::None, PowerKind::Normal, PowerKind::Super] {
+                assert_eq!(
+                    officer.vision_bonus(&power),
+                    Ruleset::Awbw.officer_vision_bonus(officer, &power)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sonja_vision_by_ruleset() {
+        let sonja = OfficerKind::Sonja;
+        for ruleset in Ruleset::ALL {
+            assert_eq!(1, ruleset.officer_vision_bonus(&sonja, &PowerKind::None));
+            assert_eq!(2, ruleset.officer_vision_bonus(&sonja, &PowerKind::Normal));
+            assert_eq!(
+                0,
+                ruleset.officer_vision_bonus(&OfficerKind::Andy, &PowerKind::Super)
+            );
+        }
+
+        let mut game_state = crate::game_state!(".0inf . . . . .1inf");
+        game_state.players[0].officer = sonja;
+        for ruleset in Ruleset::ALL {
+            game_state.set_ruleset(ruleset.clone());
+            game_state.players[0].power = PowerKind::None;
+            assert_eq!(vec![0, 1, 2, 3], sorted_vision(&game_state, 0));
+            game_state.players[0].power = PowerKind::Normal;
+            assert_eq!(vec![0, 1, 2, 3, 4], sorted_vision(&game_state, 0));
+        }
+    }
+
+    #[test]
+    fn mountain_bonus_by_ruleset() {
+        for ruleset in Ruleset::ALL {
+            for kind in [UnitKind::Infantry, UnitKind::Mech] {
+                assert_eq!(3, ruleset.terrain_vision_bonus(&kind, &TileKind::Mountain));
+                assert_eq!(0, ruleset.terrain_vision_bonus(&kind, &TileKind::Forest));
+            }
+            assert_eq!(
+                0,
+                ruleset.terrain_vision_bonus(&UnitKind::Tank, &TileKind::Mountain)
+            );
+        }
+    }
+
+    fn sorted_vision(game_state: &GameState, location: usize) -> Vec<usize> {
+        let mut tiles = game_state
+            .unit_vision(location)
+            .map(|(_, tiles)| tiles.into_iter().collect::<Vec<usize>>())
+            .unwrap_or_default();
+        tiles.sort();
+        tiles
+    }
+
+    #[test]
+    fn cartridges_lack_later_additions() {
+        assert!(Ruleset::Aw2.has_unit(&UnitKind::Submarine));
+        assert!(!Ruleset::Aw2.has_unit(&UnitKind::Stealth));
+        assert!(Ruleset::Awds.has_unit(&UnitKind::Stealth));
+        assert!(!Ruleset::Awds.has_unit(&UnitKind::Flare));
+
+        assert!(Ruleset::Aw2.has_officer(&OfficerKind::Sonja));
+        assert!(!Ruleset::Aw2.has_officer(&OfficerKind::Jake));
+        assert!(Ruleset::Awds.has_officer(&OfficerKind::Jake));
+    }
+}
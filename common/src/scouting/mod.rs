@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+
+use crate::cancel::CancelToken;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * A candidate move for revealing more of the map.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ScoutingMove {
+    /** Where the unit currently is. */
+    pub from: usize,
+    /** Where the unit would move to. */
+    pub to: usize,
+    /** How many tiles the move reveals that the player cannot currently see. */
+    pub revealed: usize,
+    /** How many visible enemy units could attack `to` next turn. */
+    pub risk: usize,
+}
+
+impl ScoutingMove {
+    /**
+     * Newly revealed tiles per unit of risk.
+     */
+    pub fn score(&self) -> f64 {
+        self.revealed as f64 / (self.risk + 1) as f64
+    }
+}
+
+impl GameState {
+    /**
+     * Proposes up to `k` moves of `player`'s units that reveal the most new
+     * tiles per unit of risk.
+     *
+     * Moves are only blocked by enemy units the player can currently see
+     * and risk only accounts for those, so nothing hidden in fog leaks into
+     * the suggestions.
+     */
+    pub fn suggest_scouting_moves(&self, player: usize, k: usize) -> Vec<ScoutingMove> {
+        self.suggest_scouting_moves_with_cancel(player, k, &CancelToken::new())
+    }
+
+    /**
+     * Same as `suggest_scouting_moves` but stops searching once `cancel` is
+     * cancelled and returns the best moves found so far.
+     */
+    pub fn suggest_scouting_moves_with_cancel(
+        &self,
+        player: usize,
+        k: usize,
+        cancel: &CancelToken,
+    ) -> Vec<ScoutingMove> {
+        let config = VisionConfig::default();
+        let view = self.player_view_with_config(player, &config);
+
+        let threats = self.threat_counts(
+            view.units
+                .iter()
+                .filter(|(_, unit)| !view.allies.contains(&unit.player))
+                .map(|(location, _)| *location),
+        );
+
+        let mut moves = Vec::new();
+        for (from, unit) in self.units.iter() {
+            if unit.player != player {
+                continue;
+            }
+
+            for to in self
+                .reachable_tiles_seeing(*from, &view.visible_tiles)
+                .into_keys()
+            {
+                if cancel.is_cancelled() {
+                    break;
+                }
+
+                let revealed = self
+                    .vision_of(to, unit, &config)
+                    .unwrap_or_default()
+                    .difference(&view.visible_tiles)
+                    .count();
+
+                if revealed == 0 {
+                    continue;
+                }
+
+                moves.push(ScoutingMove {
+                    from: *from,
+                    to,
+                    revealed,
+                    risk: threats.get(&to).cloned().unwrap_or(0),
+                });
+            }
+        }
+
+        moves.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap_or(Ordering::Equal)
+                .then(b.revealed.cmp(&a.revealed))
+                .then(a.from.cmp(&b.from))
+                .then(a.to.cmp(&b.to))
+        });
+        moves.truncate(k);
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cancel::CancelToken;
+    use crate::reveal::Reveal;
+
+    use super::ScoutingMove;
+
+    #[test]
+    fn prefers_safe_reveals() {
+        let game_state = crate::game_state!("=0inf = =0inf = = = = = = =1art");
+
+        let moves = game_state.suggest_scouting_moves(0, 2);
+        assert_eq!(2, moves.len());
+        assert!(moves[0].score() >= moves[1].score());
+        assert!(moves.iter().all(|scouting_move| scouting_move.revealed > 0));
+
+        // The artillery at 9 is hidden so moving next to it is not risky.
+        assert_eq!((2, 5, 3, 0), {
+            let best = &moves[0];
+            (best.from, best.to, best.revealed, best.risk)
+        });
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert!(game_state
+            .suggest_scouting_moves_with_cancel(0, 2, &cancel)
+            .is_empty());
+    }
+
+    #[test]
+    fn revealed_tiles_are_not_new() {
+        let mut game_state = crate::game_state!(".0inf . . . . . . . .1inf");
+        assert!(!game_state.suggest_scouting_moves(0, 5).is_empty());
+
+        // Everything the infantry could see from where it can reach is
+        // already revealed.
+        game_state.add_reveal(Reveal {
+            player: Some(0),
+            center: 3,
+            radius: 3,
+        });
+        assert_eq!(
+            Vec::<ScoutingMove>::new(),
+            game_state.suggest_scouting_moves(0, 5)
+        );
+
+        game_state.settings.fog = false;
+        assert!(game_state.suggest_scouting_moves(0, 5).is_empty());
+    }
+
+    #[test]
+    fn hidden_enemies_do_not_block() {
+        // The infantry at 2 is hidden in the forest so the scout may plan to
+        // move past it, just as the player would without knowing it is there.
+        let mut game_state = crate::game_state!(".0inf . f1inf . . . . .");
+        let moves = game_state.suggest_scouting_moves(0, 10);
+        assert!(moves.iter().any(|scouting_move| scouting_move.to == 3));
+        assert!(game_state.movement_range(0).iter().all(|tile| *tile < 2));
+
+        // Once it is seen it blocks like any other enemy.
+        game_state.add_reveal(Reveal {
+            player: Some(0),
+            center: 2,
+            radius: 0,
+        });
+        let moves = game_state.suggest_scouting_moves(0, 10);
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|scouting_move| scouting_move.to < 2));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::officer::OfficerKind;
+use crate::prelude::*;
+use crate::unit::UnitKind;
+use crate::GameState;
+
+/**
+ * How weather is decided during a match.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeatherMode {
+    /** Weather is always clear unless changed by a CO power. */
+    Clear,
+    /** Weather changes randomly from day to day. */
+    Random,
+}
+
+/**
+ * Options chosen when a match is created.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSettings {
+    /** If true then fog of war is enabled. */
+    pub fog: bool,
+    /** Funds each player starts with. */
+    pub starting_funds: u32,
+    /** Funds each owned property produces at the start of a turn. */
+    pub funds_per_property: u32,
+    /** Officers that cannot be picked. */
+    pub banned_officers: Vec<OfficerKind>,
+    /** Units that cannot be built. */
+    pub banned_units: Vec<UnitKind>,
+    pub weather: WeatherMode,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings::standard()
+    }
+}
+
+impl GameSettings {
+    /**
+     * AWBW's standard league: no fog, 1000 funds per property, clear weather
+     * and nothing banned.
+     */
+    pub fn standard() -> GameSettings {
+        GameSettings {
+            fog: false,
+            starting_funds: 0,
+            funds_per_property: 1000,
+            banned_officers: Vec::new(),
+            banned_units: Vec::new(),
+            weather: WeatherMode::Clear,
+        }
+    }
+
+    /**
+     * AWBW's fog league: the standard league with fog of war enabled.
+     */
+    pub fn fog_league() -> GameSettings {
+        GameSettings {
+            fog: true,
+            ..GameSettings::standard()
+        }
+    }
+
+    /**
+     * AWBW's high funds league: the standard league with 10000 starting
+     * funds and 2000 funds per property.
+     */
+    pub fn high_funds() -> GameSettings {
+        GameSettings {
+            starting_funds: 10000,
+            funds_per_property: 2000,
+            ..GameSettings::standard()
+        }
+    }
+
+    /**
+     * Returns true if `officer` may be picked.
+     */
+    pub fn allows_officer(&self, officer: &OfficerKind) -> bool {
+        !self.banned_officers.contains(officer)
+    }
+
+    /**
+     * Returns true if `unit` may be built.
+     */
+    pub fn allows_unit(&self, unit: &UnitKind) -> bool {
+        !self.banned_units.contains(unit)
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the options the match was created with.
+     */
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    /**
+     * Replaces the options the match was created with.
+     */
+    pub fn set_settings(&mut self, settings: GameSettings) {
+        self.settings = settings;
+    }
+}
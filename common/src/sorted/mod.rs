@@ -0,0 +1,79 @@
+use crate::prelude::*;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * Variants of the vision queries returning `BTreeSet`s, which iterate in
+ * ascending tile order so snapshots and diffs of them are reproducible.
+ */
+impl GameState {
+    /**
+     * Same as `common_vision` in ascending order.
+     */
+    pub fn common_vision_sorted(&self) -> BTreeSet<usize> {
+        self.common_vision_sorted_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Same as `common_vision_with_config` in ascending order.
+     */
+    pub fn common_vision_sorted_with_config(&self, config: &VisionConfig) -> BTreeSet<usize> {
+        self.common_vision_with_config(config).into_iter().collect()
+    }
+
+    /**
+     * Same as `visible_to_team` in ascending order.
+     */
+    pub fn visible_to_team_sorted(&self, team: usize) -> BTreeSet<usize> {
+        self.visible_to_team_sorted_with_config(team, &VisionConfig::default())
+    }
+
+    /**
+     * Same as `visible_to_team_with_config` in ascending order.
+     */
+    pub fn visible_to_team_sorted_with_config(
+        &self,
+        team: usize,
+        config: &VisionConfig,
+    ) -> BTreeSet<usize> {
+        self.visible_to_team_with_config(team, config)
+            .into_iter()
+            .collect()
+    }
+
+    /**
+     * Same as `any_vision` in ascending order.
+     */
+    pub fn any_vision_sorted(&self) -> BTreeSet<usize> {
+        self.any_vision_sorted_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Same as `any_vision_with_config` in ascending order.
+     */
+    pub fn any_vision_sorted_with_config(&self, config: &VisionConfig) -> BTreeSet<usize> {
+        self.any_vision_with_config(config).into_iter().collect()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::game_state;
+
+    #[test]
+    fn sorted_variants_match() {
+        let game_state = game_state!(".0inf f . .1tank\n. . . .\n. ~ ~ ~");
+
+        let common = game_state.common_vision_sorted();
+        assert_eq!(game_state.common_vision(), common.iter().copied().collect());
+
+        assert_eq!(
+            game_state.visible_to_team(1),
+            game_state.visible_to_team_sorted(1).into_iter().collect()
+        );
+        assert_eq!(
+            game_state.any_vision(),
+            game_state.any_vision_sorted().into_iter().collect()
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::GameState;
+
+impl GameState {
+    /**
+     * Dives the Submarine or hides the Stealth at `location`.
+     *
+     * Returns false and changes nothing if there is no unit at `location`,
+     * it can not stealth or it already is.
+     */
+    pub fn dive(&mut self, location: usize) -> bool {
+        self.set_stealthed(location, true)
+    }
+
+    /**
+     * Surfaces the Submarine or unhides the Stealth at `location`.
+     *
+     * Returns false and changes nothing if there is no unit at `location` or
+     * it is not stealthed.
+     */
+    pub fn surface(&mut self, location: usize) -> bool {
+        self.set_stealthed(location, false)
+    }
+
+    fn set_stealthed(&mut self, location: usize, stealthed: bool) -> bool {
+        match self.units.get_mut(&location) {
+            Some(unit) if unit.kind.can_stealth() && unit.stealthed != stealthed => {
+                unit.stealthed = stealthed;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game_state;
+
+    #[test]
+    fn dive_and_surface() {
+        let mut game_state = game_state!("~0sub ~0bship . .0stl .");
+
+        assert!(!game_state.dive(1));
+        assert!(!game_state.dive(2));
+        assert!(!game_state.surface(0));
+
+        assert!(game_state.dive(0));
+        assert!(game_state.unit(0).unwrap().stealthed());
+        assert!(!game_state.dive(0));
+
+        assert!(game_state.dive(3));
+        assert!(game_state.surface(3));
+        assert!(!game_state.unit(3).unwrap().stealthed());
+    }
+
+    #[test]
+    fn dived_sub_is_hidden() {
+        let mut game_state = game_state!("~0sub ~ ~1bship");
+        assert!(game_state.visible_to_team(1).contains(&0));
+
+        game_state.dive(0);
+        assert!(!game_state.visible_to_team(1).contains(&0));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashSet};
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::data::{TileData, UnitData};
+use crate::grid::Grid;
+use crate::map::{CountryKind, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::unit::{UnitKind, MAX_HP};
+use crate::weather::Weather;
+use crate::{GameState, UnitState};
+
+impl Arbitrary for TileKind {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<TileKind>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        proptest::sample::select(TileKind::ALL).boxed()
+    }
+}
+
+impl Arbitrary for UnitKind {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<UnitKind>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        proptest::sample::select(UnitKind::ALL).boxed()
+    }
+}
+
+impl Arbitrary for UnitState {
+    /** How many players the unit may belong to, at least 1. */
+    type Parameters = usize;
+    type Strategy = BoxedStrategy<UnitState>;
+
+    /**
+     * A unit owned by one of the first `players` players, only stealthed if
+     * its kind can be, with no cargo.
+     */
+    fn arbitrary_with(players: usize) -> Self::Strategy {
+        (
+            0..players.max(1),
+            any::<UnitKind>(),
+            any::<bool>(),
+            1..=MAX_HP,
+            any::<u8>(),
+            any::<u8>(),
+        )
+            .prop_map(|(player, kind, stealthed, hp, fuel, ammo)| {
+                let stealthed = stealthed && kind.can_stealth();
+                UnitState::new(player, stealthed, kind)
+                    .with_hp(hp)
+                    .with_fuel(fuel)
+                    .with_ammo(ammo)
+            })
+            .boxed()
+    }
+}
+
+/**
+ * Bounds on the states `GameState::arbitrary_with` generates.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StateBounds {
+    pub max_width: usize,
+    pub max_height: usize,
+    pub max_players: usize,
+    pub max_units: usize,
+}
+
+impl Default for StateBounds {
+    fn default() -> Self {
+        StateBounds {
+            max_width: 8,
+            max_height: 8,
+            max_players: 4,
+            max_units: 16,
+        }
+    }
+}
+
+impl Arbitrary for GameState {
+    type Parameters = StateBounds;
+    type Strategy = BoxedStrategy<GameState>;
+
+    /**
+     * A state within `bounds` which `GameState::validate` accepts: every
+     * player is on a team and units are only placed on terrain they could
+     * move onto, so some generated units are dropped.
+     */
+    fn arbitrary_with(bounds: StateBounds) -> Self::Strategy {
+        (
+            1..=bounds.max_width.max(1),
+            1..=bounds.max_height.max(1),
+            1..=bounds.max_players.max(1),
+        )
+            .prop_flat_map(move |(width, height, players)| {
+                (
+                    vec(any::<TileKind>(), width * height),
+                    vec(0..players, players),
+                    vec(
+                        (0..width * height, any_with::<UnitState>(players)),
+                        0..=bounds.max_units,
+                    ),
+                    any::<bool>(),
+                )
+                    .prop_map(move |(map, team_of, units, fog)| {
+                        let mut teams = vec![HashSet::new(); players];
+                        for (player, team) in team_of.into_iter().enumerate() {
+                            teams[team].insert(player);
+                        }
+                        teams.retain(|team| !team.is_empty());
+
+                        let units = units
+                            .into_iter()
+                            .filter(|(location, unit)| {
+                                map[*location]
+                                    .movement_cost(unit.kind.movement_kind())
+                                    .is_some()
+                            })
+                            .collect::<BTreeMap<usize, UnitState>>();
+
+                        GameState {
+                            map: Grid::new(map, (width, height))
+                                .expect("Tiles do not fill the map"),
+                            units,
+                            owners: BTreeMap::new(),
+                            players: (0..players)
+                                .map(|_| {
+                                    PlayerState::new(
+                                        CountryKind::OrangeStar,
+                                        OfficerKind::Andy,
+                                        PowerKind::None,
+                                    )
+                                })
+                                .collect(),
+                            teams,
+                            weather: Weather::Clear,
+                            settings: GameSettings {
+                                fog,
+                                ..GameSettings::fog_league()
+                            },
+                            reveals: Vec::new(),
+                            ruleset: Ruleset::Awbw,
+                            day: 1,
+                            active_player: 0,
+                        }
+                    })
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn generated_states_are_valid(game_state in any::<GameState>()) {
+            prop_assert_eq!(Ok(()), game_state.validate());
+        }
+
+        #[test]
+        fn common_vision_within_every_team(game_state in any::<GameState>()) {
+            let common = game_state.common_vision();
+            for team in 0..game_state.teams.len() {
+                prop_assert!(common.is_subset(&game_state.visible_to_team(team)));
+            }
+            prop_assert!(common.is_subset(&game_state.any_vision()));
+        }
+
+        #[test]
+        fn common_vision_mask_agrees(game_state in any::<GameState>()) {
+            prop_assert_eq!(
+                game_state.common_vision(),
+                game_state.common_vision_mask().to_set()
+            );
+        }
+
+        #[test]
+        fn fog_off_sees_everything(mut game_state in any::<GameState>()) {
+            game_state.settings.fog = false;
+            prop_assert_eq!(game_state.map.len(), game_state.common_vision().len());
+        }
+    }
+}
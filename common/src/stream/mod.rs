@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use crate::tracker::VisionDelta;
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * The analysis of one state (turn) of a stream.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnRecord {
+    /** How many states came before this one. */
+    pub turn: usize,
+    pub common_vision: HashSet<usize>,
+    /** The tiles each team can see, indexed by team. */
+    pub team_vision: Vec<HashSet<usize>>,
+    /** How common vision changed since the previous state, everything
+     * visible has entered on the first. */
+    pub delta: VisionDelta,
+}
+
+/**
+ * Lazily analyzes each state of an iterator, such as the turns of a
+ * replay, as it is pulled.
+ *
+ * Only the previous state's common vision is kept between turns so long
+ * replays can be streamed without holding every state.
+ */
+pub struct TurnStream<I> {
+    states: I,
+    config: VisionConfig,
+    turn: usize,
+    previous: HashSet<usize>,
+}
+
+impl<I: Iterator<Item = GameState>> TurnStream<I> {
+    pub fn new(states: impl IntoIterator<IntoIter = I>, config: &VisionConfig) -> TurnStream<I> {
+        TurnStream {
+            states: states.into_iter(),
+            config: config.clone(),
+            turn: 0,
+            previous: HashSet::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = GameState>> Iterator for TurnStream<I> {
+    type Item = TurnRecord;
+
+    fn next(&mut self) -> Option<TurnRecord> {
+        let state = self.states.next()?;
+
+        let common_vision = state.common_vision_with_config(&self.config);
+        let team_vision = (0..state.teams.len())
+            .map(|team| state.visible_to_team_with_config(team, &self.config))
+            .collect::<Vec<HashSet<usize>>>();
+        let delta = VisionDelta::between(&self.previous, &common_vision);
+
+        let record = TurnRecord {
+            turn: self.turn,
+            common_vision: common_vision.clone(),
+            team_vision,
+            delta,
+        };
+        self.turn += 1;
+        self.previous = common_vision;
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.states.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn records_each_turn() {
+        let first = game_state!(".0inf . . . . . .1inf");
+        let mut second = first.clone();
+        second.relocate_unit(0, 4).unwrap();
+
+        let records = TurnStream::new(
+            vec![first.clone(), second.clone()],
+            &VisionConfig::default(),
+        )
+        .collect::<Vec<TurnRecord>>();
+
+        assert_eq!(2, records.len());
+        assert_eq!(0, records[0].turn);
+        assert_eq!(first.common_vision(), records[0].common_vision);
+        assert!(records[0].delta.is_empty());
+        assert_eq!(first.visible_to_team(1), records[0].team_vision[1]);
+
+        assert_eq!(1, records[1].turn);
+        assert_eq!(VisionDelta::diff(&first, &second), records[1].delta);
+        assert_eq!(second.visible_to_team(0), records[1].team_vision[0]);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::builder::BuildError;
+use crate::prelude::*;
+use crate::GameState;
+
+/**
+ * Which players are on which team, checked with `validate` so every player
+ * ends up on exactly one team before vision is worked out from it.
+ *
+ * Teams are numbered from 0 in the order they are added.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Teams {
+    teams: Vec<HashSet<usize>>,
+}
+
+impl Teams {
+    pub fn new() -> Teams {
+        Teams::default()
+    }
+
+    /**
+     * Returns `players` teams of one, player `n` on team `n`.
+     */
+    pub fn ffa(players: usize) -> Teams {
+        Teams {
+            teams: (0..players)
+                .map(|player| [player].into_iter().collect())
+                .collect(),
+        }
+    }
+
+    /**
+     * Returns teams from the team of each player in order, so `[0, 1, 0]`
+     * puts players 0 and 2 together against player 1. Team numbers that no
+     * player uses become empty teams, which `validate` rejects.
+     */
+    pub fn from_assignments(team_of: &[usize]) -> Teams {
+        let mut teams =
+            vec![HashSet::new(); team_of.iter().map(|team| team + 1).max().unwrap_or(0)];
+        for (player, team) in team_of.iter().enumerate() {
+            teams[*team].insert(player);
+        }
+        Teams { teams }
+    }
+
+    /**
+     * Adds a team made up of `players`.
+     */
+    pub fn team(mut self, players: impl IntoIterator<Item = usize>) -> Teams {
+        self.teams.push(players.into_iter().collect());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.teams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.teams.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[HashSet<usize>] {
+        &self.teams
+    }
+
+    /**
+     * Returns the team `player` is on, the first if there are several.
+     */
+    pub fn team_of(&self, player: usize) -> Option<usize> {
+        self.teams.iter().position(|team| team.contains(&player))
+    }
+
+    /**
+     * Checks the teams for a game of `players` players: every team lists
+     * only players in the game, has at least one player, and every player
+     * is on exactly one team. Returns every problem found.
+     */
+    pub fn validate(&self, players: usize) -> Result<(), Vec<BuildError>> {
+        let mut problems = membership_problems(&self.teams, players);
+        problems.extend(coverage_problems(&self.teams, players));
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+impl From<Teams> for Vec<HashSet<usize>> {
+    fn from(teams: Teams) -> Vec<HashSet<usize>> {
+        teams.teams
+    }
+}
+
+/**
+ * Returns the team members that are not in the game or are on more than
+ * one team, which stop vision being computed at all.
+ */
+pub(crate) fn membership_problems(teams: &[HashSet<usize>], players: usize) -> Vec<BuildError> {
+    let mut problems = Vec::new();
+
+    let mut seen = HashSet::new();
+    for (team, members) in teams.iter().enumerate() {
+        for player in members.iter().collect::<BTreeSet<&usize>>() {
+            if *player >= players {
+                problems.push(BuildError::UnknownTeamMember {
+                    team,
+                    player: *player,
+                });
+            }
+            if !seen.insert(*player) {
+                problems.push(BuildError::PlayerOnMultipleTeams { player: *player });
+            }
+        }
+    }
+
+    problems
+}
+
+/**
+ * Returns the players on no team and the teams with nobody on them, which
+ * leave common vision wrong rather than failing.
+ */
+pub(crate) fn coverage_problems(teams: &[HashSet<usize>], players: usize) -> Vec<BuildError> {
+    let on_team = teams.iter().flatten().collect::<HashSet<&usize>>();
+    let mut problems = (0..players)
+        .filter(|player| !on_team.contains(player))
+        .map(|player| BuildError::NoTeam { player })
+        .collect::<Vec<BuildError>>();
+
+    for (team, _) in teams
+        .iter()
+        .enumerate()
+        .filter(|(_, members)| members.is_empty())
+    {
+        problems.push(BuildError::EmptyTeam { team });
+    }
+    problems
+}
+
+impl GameState {
+    /**
+     * Returns the teams of the state.
+     */
+    pub fn teams(&self) -> Teams {
+        Teams {
+            teams: self.teams.clone(),
+        }
+    }
+
+    /**
+     * Replaces the teams with `teams`.
+     *
+     * Returns every problem and changes nothing if `teams` does not put
+     * each player in the game on exactly one team, see `Teams::validate`.
+     */
+    pub fn set_teams(&mut self, teams: Teams) -> Result<(), Vec<BuildError>> {
+        teams.validate(self.players.len())?;
+        self.teams = teams.into();
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn ffa_and_assignments() {
+        assert_eq!(Teams::new().team([0]).team([1]).team([2]), Teams::ffa(3));
+        assert_eq!(Ok(()), Teams::ffa(3).validate(3));
+
+        let teams = Teams::from_assignments(&[0, 1, 0]);
+        assert_eq!(Teams::new().team([0, 2]).team([1]), teams);
+        assert_eq!(Some(0), teams.team_of(2));
+        assert_eq!(None, teams.team_of(3));
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let teams = Teams::new().team([0, 1]).team([1, 4]).team([]);
+        assert_eq!(
+            Err(vec![
+                BuildError::PlayerOnMultipleTeams { player: 1 },
+                BuildError::UnknownTeamMember { team: 1, player: 4 },
+                BuildError::NoTeam { player: 2 },
+                BuildError::EmptyTeam { team: 2 },
+            ]),
+            teams.validate(3)
+        );
+        assert_eq!(
+            Err(vec![BuildError::EmptyTeam { team: 1 }]),
+            Teams::from_assignments(&[0, 2, 0]).validate(3)
+        );
+    }
+
+    #[test]
+    fn set_teams_checks_players() {
+        let mut game_state = game_state!(".0inf .1inf .2inf");
+        assert_eq!(Teams::ffa(3), game_state.teams());
+
+        assert_eq!(
+            Err(vec![BuildError::NoTeam { player: 2 }]),
+            game_state.set_teams(Teams::new().team([0, 1]))
+        );
+        assert_eq!(Teams::ffa(3), game_state.teams());
+
+        let allied = Teams::new().team([0, 2]).team([1]);
+        assert_eq!(Ok(()), game_state.set_teams(allied.clone()));
+        assert_eq!(allied, game_state.teams());
+    }
+}
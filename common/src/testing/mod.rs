@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::grid::Grid;
+use crate::map::{CountryKind, Map, TileKind};
+use crate::officer::{OfficerKind, PowerKind};
+use crate::player::PlayerState;
+use crate::ruleset::Ruleset;
+use crate::settings::GameSettings;
+use crate::unit::UnitKind;
+use crate::weather::Weather;
+use crate::{GameState, UnitState};
+
+/**
+ * Countries handed out to the players of a fixture in order.
+ */
+const COUNTRIES: [CountryKind; 4] = [
+    CountryKind::OrangeStar,
+    CountryKind::BlueMoon,
+    CountryKind::GreenEarth,
+    CountryKind::YellowComet,
+];
+
+/**
+ * Returns a `width` by `height` map where every tile is `fill`.
+ */
+pub fn fixture_map(width: usize, height: usize, fill: TileKind) -> Map {
+    Map::new(vec![fill; width * height], (width, height))
+}
+
+/**
+ * Returns a state on `map` where every entry of `teams` is a team of
+ * players and `units` lists each (location, player, unit).
+ *
+ * Players are numbered from 0 in the order they first appear in `teams`
+ * and are given Andy with no power active and a distinct country.
+ */
+pub fn fixture_state(
+    map: &Map,
+    teams: &[&[usize]],
+    units: &[(usize, usize, UnitKind)],
+) -> GameState {
+    let player_count = teams
+        .iter()
+        .flat_map(|team| team.iter())
+        .map(|player| player + 1)
+        .max()
+        .unwrap_or(0);
+
+    GameState {
+        map: Grid::new(map.tiles().to_vec(), map.dimensions()).expect("Tiles do not fill the map"),
+        units: units
+            .iter()
+            .map(|(location, player, kind)| {
+                (*location, UnitState::new(*player, false, kind.clone()))
+            })
+            .collect::<BTreeMap<usize, UnitState>>(),
+        players: (0..player_count)
+            .map(|player| {
+                PlayerState::new(
+                    COUNTRIES[player % COUNTRIES.len()].clone(),
+                    OfficerKind::Andy,
+                    PowerKind::None,
+                )
+            })
+            .collect(),
+        teams: teams
+            .iter()
+            .map(|team| team.iter().copied().collect::<HashSet<usize>>())
+            .collect(),
+        weather: Weather::Clear,
+        settings: GameSettings::fog_league(),
+        owners: BTreeMap::new(),
+        reveals: Vec::new(),
+        ruleset: Ruleset::Awbw,
+        day: 1,
+        active_player: 0,
+    }
+}
+
+/**
+ * Two players on a 5x5 plain each with an infantry in opposite corners.
+ */
+pub fn duel() -> GameState {
+    fixture_state(
+        &fixture_map(5, 5, TileKind::Plain),
+        &[&[0], &[1]],
+        &[(0, 0, UnitKind::Infantry), (24, 1, UnitKind::Infantry)],
+    )
+}
+
+/**
+ * Two teams of two on a 6x6 plain with a forest band through the middle.
+ *
+ * Players 0 and 1 hold the top rows and players 2 and 3 the bottom rows.
+ */
+pub fn two_versus_two() -> GameState {
+    let mut tiles = vec![TileKind::Plain; 36];
+    for tile in tiles.iter_mut().skip(12).take(12) {
+        *tile = TileKind::Forest;
+    }
+
+    fixture_state(
+        &Map::new(tiles, (6, 6)),
+        &[&[0, 1], &[2, 3]],
+        &[
+            (1, 0, UnitKind::Recon),
+            (4, 1, UnitKind::Tank),
+            (31, 2, UnitKind::Recon),
+            (34, 3, UnitKind::Tank),
+        ],
+    )
+}
+
+/**
+ * Three players on their own teams on a 7x7 plain, one in each of three
+ * corners.
+ */
+pub fn free_for_all() -> GameState {
+    fixture_state(
+        &fixture_map(7, 7, TileKind::Plain),
+        &[&[0], &[1], &[2]],
+        &[
+            (0, 0, UnitKind::Infantry),
+            (6, 1, UnitKind::Infantry),
+            (48, 2, UnitKind::Infantry),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture_map_fills() {
+        let map = fixture_map(3, 2, TileKind::Sea);
+        assert_eq!((3, 2), map.dimensions());
+        assert_eq!(vec![TileKind::Sea; 6], map.tiles());
+    }
+
+    #[test]
+    fn fixture_state_numbers_players() {
+        let state = fixture_state(
+            &fixture_map(2, 1, TileKind::Plain),
+            &[&[0, 2], &[1]],
+            &[(1, 2, UnitKind::Mech)],
+        );
+
+        assert_eq!(3, state.players.len());
+        assert_eq!(Some(0), state.team_of(2));
+        assert_eq!(Some(1), state.team_of(1));
+        assert_eq!(
+            Some(&UnitState::new(2, false, UnitKind::Mech)),
+            state.units.get(&1)
+        );
+    }
+
+    #[test]
+    fn scenarios_lint_clean() {
+        for state in [duel(), two_versus_two(), free_for_all()] {
+            assert_eq!(Vec::<crate::lint::Lint>::new(), state.lint());
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{OfficerData, UnitData};
+use crate::vision::VisionConfig;
+use crate::{GameState, UnitState};
+
+impl GameState {
+    /**
+     * Returns every tile the unit at `location` could attack this turn.
+     * Empty if there is no unit there or it cannot attack.
+     *
+     * Direct units can move then attack an adjacent tile while indirect
+     * units can only fire from where they stand, at the range of their
+     * kind changed by their officer, such as Grit's bonus or Max's
+     * penalty.
+     */
+    pub fn attack_range(&self, location: usize) -> HashSet<usize> {
+        self.attackable_tiles(location)
+    }
+
+    /**
+     * Returns the (minimum, maximum) distance `unit` can attack at once its
+     * officer is accounted for, or None if it cannot attack at all.
+     */
+    pub(crate) fn unit_attack_range(&self, unit: &UnitState) -> Option<(u8, u8)> {
+        let (min_range, max_range) = unit.kind.attack_range()?;
+        if min_range <= 1 {
+            return Some((min_range, max_range));
+        }
+
+        let bonus = self
+            .players
+            .get(unit.player)
+            .map(|player| player.officer.range_bonus(&player.power, &unit.kind))
+            .unwrap_or(0);
+        // Max's penalty never brings the range below the minimum.
+        Some((
+            min_range,
+            max_range.saturating_add_signed(bonus).max(min_range),
+        ))
+    }
+
+    /**
+     * For a given location returns every tile the unit on it could attack
+     * next turn.
+     *
+     * Direct units can move then attack an adjacent tile while indirect
+     * units can only fire from where they stand.
+     */
+    pub(crate) fn attackable_tiles(&self, location: usize) -> HashSet<usize> {
+        let Some(unit) = self.units.get(&location) else {
+            return HashSet::new();
+        };
+
+        let Some((min_range, max_range)) = self.unit_attack_range(unit) else {
+            return HashSet::new();
+        };
+
+        if min_range <= 1 {
+            let mut tiles = HashSet::new();
+            for reachable in self.reachable_tiles(location).into_keys() {
+                tiles.extend(
+                    self.neighbors(reachable, max_range as usize)
+                        .into_iter()
+                        .filter(|tile| *tile != reachable && *tile != location),
+                );
+            }
+            return tiles;
+        }
+
+        let too_close = self.neighbors(location, min_range as usize - 1);
+        self.neighbors(location, max_range as usize)
+            .into_iter()
+            .filter(|tile| !too_close.contains(tile))
+            .collect()
+    }
+
+    /**
+     * Returns the earliest day (starting from 1) on which the unit at
+     * `attacker` could attack `target`, considering at most `max_days` days
+     * of movement.
+     */
+    pub(crate) fn earliest_attack_day(
+        &self,
+        attacker: usize,
+        target: usize,
+        max_days: u32,
+    ) -> Option<u32> {
+        let unit = self.units.get(&attacker)?;
+        let (min_range, max_range) = self.unit_attack_range(unit)?;
+
+        let too_close = self.neighbors(target, min_range as usize - 1);
+        let firing_positions = self
+            .neighbors(target, max_range as usize)
+            .into_iter()
+            .filter(|tile| !too_close.contains(tile))
+            .collect::<HashSet<usize>>();
+
+        let turns = self.turns_to_reach(
+            attacker,
+            unit.kind.movement_kind(),
+            unit.kind.movement_points(),
+            max_days,
+        );
+
+        firing_positions
+            .iter()
+            .filter_map(|tile| turns.get(tile))
+            .map(|turn| {
+                if min_range <= 1 {
+                    // Direct units move and attack on the same day.
+                    std::cmp::max(*turn, 1)
+                } else {
+                    // Indirect units need to stop moving before firing.
+                    turn + 1
+                }
+            })
+            .filter(|day| *day <= max_days)
+            .min()
+    }
+
+    /**
+     * Returns for each tile how many enemies of `team` could attack it next
+     * turn, whether or not `team` can see them. Tiles no enemy can reach
+     * are omitted, as is everything if there is no such team.
+     */
+    pub fn threat_map(&self, team: usize) -> HashMap<usize, usize> {
+        if team >= self.teams.len() {
+            return HashMap::new();
+        }
+
+        self.threat_counts(self.enemies_of_team(team))
+    }
+
+    /**
+     * Same as `threat_map` but only counts the enemies `team` can see under
+     * the rules given by `config`, which is what a player could work out
+     * for themselves.
+     */
+    pub fn visible_threat_map(&self, team: usize, config: &VisionConfig) -> HashMap<usize, usize> {
+        if team >= self.teams.len() {
+            return HashMap::new();
+        }
+
+        let visible = self.visible_to_team_with_config(team, config);
+        self.threat_counts(
+            self.enemies_of_team(team)
+                .filter(|location| visible.contains(location)),
+        )
+    }
+
+    /**
+     * Iterates over the locations of units not on `team`.
+     */
+    fn enemies_of_team(&self, team: usize) -> impl Iterator<Item = usize> + '_ {
+        self.units
+            .iter()
+            .filter(move |(_, unit)| self.team_of(unit.player) != Some(team))
+            .map(|(location, _)| *location)
+    }
+
+    /**
+     * Returns for each tile how many of the units at `attackers` could
+     * attack it next turn. Tiles no attacker can reach are omitted.
+     */
+    pub(crate) fn threat_counts(
+        &self,
+        attackers: impl IntoIterator<Item = usize>,
+    ) -> HashMap<usize, usize> {
+        let mut counts = HashMap::new();
+        for attacker in attackers {
+            for tile in self.attackable_tiles(attacker) {
+                *counts.entry(tile).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::game_state;
+    use crate::officer::{OfficerKind, PowerKind};
+
+    #[test]
+    fn attackable_tiles_direct() {
+        assert_eq!(
+            [1, 2, 3, 4].into_iter().collect::<HashSet<usize>>(),
+            game_state!(".0inf . . . . . .").attackable_tiles(0)
+        );
+    }
+
+    #[test]
+    fn attackable_tiles_indirect() {
+        assert_eq!(
+            [2, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state!(".0art . . . . . .").attackable_tiles(0)
+        );
+        assert_eq!(
+            HashSet::new(),
+            game_state!(".0apc . . . . . .").attackable_tiles(0)
+        );
+    }
+
+    #[test]
+    fn attack_range_grit() {
+        let mut game_state = game_state!(".0art . . . . . .");
+        game_state.players[0].officer = OfficerKind::Grit;
+        assert_eq!(
+            [2, 3, 4].into_iter().collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+
+        game_state.add_charge(0, u32::MAX);
+        assert!(game_state.activate_power(0, PowerKind::Super));
+        assert_eq!(
+            [2, 3, 4, 5, 6].into_iter().collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+    }
+
+    #[test]
+    fn attack_range_max_and_jake() {
+        let mut game_state = game_state!(".0art . . . . . .");
+        game_state.players[0].officer = OfficerKind::Max;
+        assert_eq!(
+            [2].into_iter().collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+
+        game_state.players[0].officer = OfficerKind::Jake;
+        assert_eq!(
+            [2, 3].into_iter().collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+        game_state.add_charge(0, u32::MAX);
+        assert!(game_state.activate_power(0, PowerKind::Normal));
+        assert_eq!(
+            [2, 3, 4].into_iter().collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+
+        let mut game_state = game_state!("~0bship ~ ~ ~ ~ ~ ~ ~");
+        game_state.players[0].officer = OfficerKind::Jake;
+        game_state.add_charge(0, u32::MAX);
+        assert!(game_state.activate_power(0, PowerKind::Normal));
+        assert_eq!(
+            (2..=6).collect::<HashSet<usize>>(),
+            game_state.attack_range(0)
+        );
+    }
+
+    #[test]
+    fn threat_map_visible_enemies() {
+        let game_state = game_state!(".0inf . . . f1art . .1inf");
+        let config = crate::vision::VisionConfig::default();
+
+        let threats = game_state.threat_map(0);
+        assert_eq!(Some(&2), threats.get(&2));
+        assert_eq!(Some(&1), threats.get(&5));
+        assert_eq!(None, threats.get(&3));
+
+        assert!(game_state.visible_threat_map(0, &config).is_empty());
+        assert!(game_state.threat_map(1).contains_key(&1));
+        assert!(game_state.threat_map(2).is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::vision::VisionConfig;
+use crate::GameState;
+
+/**
+ * How common vision changed, with each list in ascending order.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct VisionDelta {
+    /** Tiles that are now commonly visible but were not before. */
+    pub entered: Vec<usize>,
+    /** Tiles that were commonly visible but are not any more. */
+    pub left: Vec<usize>,
+}
+
+impl VisionDelta {
+    pub(crate) fn between(before: &HashSet<usize>, after: &HashSet<usize>) -> VisionDelta {
+        let mut entered = after.difference(before).copied().collect::<Vec<usize>>();
+        let mut left = before.difference(after).copied().collect::<Vec<usize>>();
+        entered.sort();
+        left.sort();
+
+        VisionDelta { entered, left }
+    }
+
+    /**
+     * Returns how common vision changed from `previous` to `current` using
+     * the default `VisionConfig`.
+     */
+    pub fn diff(previous: &GameState, current: &GameState) -> VisionDelta {
+        VisionDelta::diff_with_config(previous, current, &VisionConfig::default())
+    }
+
+    /**
+     * Returns how common vision changed from `previous` to `current` under
+     * the rules given by `config`. The states need not share a map.
+     */
+    pub fn diff_with_config(
+        previous: &GameState,
+        current: &GameState,
+        config: &VisionConfig,
+    ) -> VisionDelta {
+        VisionDelta::between(
+            &previous.common_vision_with_config(config),
+            &current.common_vision_with_config(config),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entered.is_empty() && self.left.is_empty()
+    }
+}
+
+/**
+ * The tiles each unit able to see reveals for its team, remembered so the
+ * common vision fixed point can be rerun without scanning every unit's
+ * surroundings again.
+ */
+#[derive(Debug, Clone, Default)]
+struct Contributions {
+    /** For each unit able to see the team it sees for and the tiles it
+     * reveals. */
+    units: BTreeMap<usize, (usize, HashSet<usize>)>,
+}
+
+impl Contributions {
+    fn new(game_state: &GameState, config: &VisionConfig) -> Contributions {
+        let mut contributions = Contributions::default();
+        for location in game_state.units.keys() {
+            contributions.rescan(game_state, config, *location);
+        }
+        contributions
+    }
+
+    fn rescan(&mut self, game_state: &GameState, config: &VisionConfig, location: usize) {
+        let contribution = game_state
+            .vision_from_tiles(location, config)
+            .and_then(|(player, tiles)| Some((game_state.team_of(player)?, tiles)));
+
+        match contribution {
+            Some(contribution) => self.units.insert(location, contribution),
+            None => self.units.remove(&location),
+        };
+    }
+
+    /**
+     * Updates the remembered tiles after the unit at `from` moved to `to`,
+     * rescanning only the units whose vision reaches either tile.
+     *
+     * Returns the locations of the rescanned units in ascending order.
+     */
+    fn moved(
+        &mut self,
+        game_state: &GameState,
+        config: &VisionConfig,
+        from: usize,
+        to: usize,
+    ) -> Vec<usize> {
+        self.units.remove(&from);
+        let affected = game_state
+            .units
+            .iter()
+            .filter(|(location, unit)| {
+                let range = game_state.vision_range(**location, unit, config).0 as usize;
+                game_state.distance(**location, from) <= range
+                    || game_state.distance(**location, to) <= range
+            })
+            .map(|(location, _)| *location)
+            .collect::<Vec<usize>>();
+        for location in affected.iter() {
+            self.rescan(game_state, config, *location);
+        }
+        affected
+    }
+
+    /**
+     * Runs the same fixed point as `GameState::common_vision_with_config`
+     * over the remembered tiles.
+     */
+    fn common_vision(&self, game_state: &GameState, config: &VisionConfig) -> HashSet<usize> {
+        if !game_state.settings.fog {
+            return (0..game_state.map.len()).collect();
+        }
+
+        let fixed_vision = game_state.fixed_vision(config);
+        game_state
+            .run_fixpoint(|fixpoint, vision| {
+                for (location, (team, tiles)) in self.units.iter() {
+                    if fixpoint.counts(*location) {
+                        for tile in tiles {
+                            vision.insert(*tile, *team);
+                        }
+                    }
+                }
+                for (location, team) in fixed_vision.iter() {
+                    vision.insert(*location, *team);
+                }
+            })
+            .0
+            .map_or_else(HashSet::new, |fixpoint| {
+                fixpoint.visible_tiles(game_state.map.len())
+            })
+    }
+}
+
+/**
+ * Owns a state and keeps its common vision up to date as units move.
+ *
+ * The tiles each unit reveals are scanned once up front and remembered
+ * between moves, so a move only rescans the moved unit and the units that
+ * could see its old or new tile. The fixed point is still rerun after each
+ * move, but over the remembered tiles rather than by scanning every unit's
+ * surroundings again.
+ */
+#[derive(Debug, Clone)]
+pub struct VisionTracker {
+    game_state: GameState,
+    config: VisionConfig,
+    contributions: Contributions,
+    common_vision: HashSet<usize>,
+}
+
+impl VisionTracker {
+    pub fn new(game_state: GameState, config: &VisionConfig) -> VisionTracker {
+        let contributions = Contributions::new(&game_state, config);
+        let common_vision = contributions.common_vision(&game_state, config);
+
+        VisionTracker {
+            game_state,
+            config: config.clone(),
+            contributions,
+            common_vision,
+        }
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    pub fn into_game_state(self) -> GameState {
+        self.game_state
+    }
+
+    /**
+     * Returns the same tiles as `GameState::common_vision_with_config`.
+     */
+    pub fn common_vision(&self) -> &HashSet<usize> {
+        &self.common_vision
+    }
+
+    /**
+     * Moves the unit at `from` to `to` and returns how common vision
+     * changed.
+     *
+     * Returns None and changes nothing if `relocate_unit` would fail. The
+     * path is not checked.
+     */
+    pub fn move_unit(&mut self, from: usize, to: usize) -> Option<VisionDelta> {
+        self.game_state.relocate_unit(from, to).ok()?;
+
+        self.contributions
+            .moved(&self.game_state, &self.config, from, to);
+        let common_vision = self
+            .contributions
+            .common_vision(&self.game_state, &self.config);
+        let delta = VisionDelta::between(&self.common_vision, &common_vision);
+        self.common_vision = common_vision;
+        Some(delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn move_unit_delta() {
+        let game_state = game_state!(".0inf . . . . . .1inf");
+        let mut tracker = VisionTracker::new(game_state, &VisionConfig::default());
+        assert_eq!(None, tracker.move_unit(1, 2));
+        assert_eq!(None, tracker.move_unit(0, 6));
+        assert_eq!(None, tracker.move_unit(0, 7));
+
+        assert_eq!(
+            Some(VisionDelta {
+                entered: vec![4, 5, 6],
+                left: Vec::new(),
+            }),
+            tracker.move_unit(0, 4)
+        );
+        assert_eq!(
+            Some(VisionDelta {
+                entered: Vec::new(),
+                left: vec![4, 5, 6],
+            }),
+            tracker.move_unit(4, 1)
+        );
+    }
+
+    #[test]
+    fn move_rescans_nearby_units() {
+        let mut game_state = game_state!(".0inf .0inf . . . . . . . . . . .1inf .1inf");
+        let config = VisionConfig::default();
+        let mut contributions = Contributions::new(&game_state, &config);
+
+        game_state.relocate_unit(1, 2).unwrap();
+        assert_eq!(vec![0, 2], contributions.moved(&game_state, &config, 1, 2));
+        assert_eq!(
+            Contributions::new(&game_state, &config).units,
+            contributions.units
+        );
+    }
+
+    #[test]
+    fn diff_between_states() {
+        let previous = game_state!(".0inf . . . . . .1inf");
+        let mut current = previous.clone();
+        assert!(VisionDelta::diff(&previous, &current).is_empty());
+
+        current.relocate_unit(0, 4).unwrap();
+        assert_eq!(
+            VisionDelta {
+                entered: vec![4, 5, 6],
+                left: Vec::new(),
+            },
+            VisionDelta::diff(&previous, &current)
+        );
+        assert_eq!(
+            VisionDelta {
+                entered: Vec::new(),
+                left: vec![4, 5, 6],
+            },
+            VisionDelta::diff(&current, &previous)
+        );
+    }
+
+    #[test]
+    fn tracker_matches_common_vision() {
+        let game_state = game_state!(
+            "
+            .0inf . f   . .
+            .     ~ ~0sub* . .1rec
+            f1inf . ~   ~ ~1sub
+            "
+        );
+        let config = VisionConfig::default();
+        let mut tracker = VisionTracker::new(game_state.clone(), &config);
+        assert_eq!(&game_state.common_vision(), tracker.common_vision());
+
+        let mut expected = game_state;
+        for (from, to) in [(0, 1), (8, 12), (9, 4), (1, 6), (4, 3), (12, 7)] {
+            let previous = expected.clone();
+            let delta = expected
+                .relocate_unit(from, to)
+                .ok()
+                .map(|_| VisionDelta::diff(&previous, &expected));
+            assert_eq!(delta, tracker.move_unit(from, to));
+            assert_eq!(&expected.common_vision(), tracker.common_vision());
+        }
+        assert_eq!(&expected, tracker.game_state());
+    }
+}
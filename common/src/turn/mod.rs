@@ -0,0 +1,115 @@
+use crate::data::UnitData;
+use crate::GameState;
+
+/**
+ * The day a game starts on.
+ */
+pub const FIRST_DAY: u32 = 1;
+
+#[cfg(feature = "serde")]
+pub(crate) fn first_day() -> u32 {
+    FIRST_DAY
+}
+
+impl GameState {
+    /**
+     * Returns the day, starting from `FIRST_DAY`.
+     */
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /**
+     * Returns the player (index) whose turn it is.
+     */
+    pub fn active_player(&self) -> usize {
+        self.active_player
+    }
+
+    /**
+     * Ends the active player's turn and starts the next player's, moving on
+     * to the next day once every player has played.
+     *
+     * Revealed areas are forgotten and the next player's power ends. Their
+     * units then burn their daily fuel, and air and naval units that run
+     * out crash.
+     */
+    pub fn end_turn(&mut self) {
+        if self.players.is_empty() {
+            return;
+        }
+
+        let next = (self.active_player + 1) % self.players.len();
+        if next <= self.active_player {
+            self.day += 1;
+        }
+        self.active_player = next;
+
+        self.clear_reveals();
+        self.deactivate_power(next);
+
+        self.units.retain(|_, unit| {
+            let burnt = unit.kind.daily_fuel(unit.stealthed);
+            if unit.player != next || burnt == 0 {
+                return true;
+            }
+
+            let fuel = unit.fuel().saturating_sub(burnt);
+            unit.set_fuel(fuel);
+            fuel > 0
+        });
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::officer::PowerKind;
+    use crate::reveal::Reveal;
+    use crate::unit::UnitKind;
+
+    #[test]
+    fn end_turn_advances_players_and_days() {
+        let mut game_state = game_state!(".0inf . .1inf");
+        assert_eq!(
+            (FIRST_DAY, 0),
+            (game_state.day(), game_state.active_player())
+        );
+
+        game_state.end_turn();
+        assert_eq!(
+            (FIRST_DAY, 1),
+            (game_state.day(), game_state.active_player())
+        );
+        game_state.end_turn();
+        assert_eq!(
+            (FIRST_DAY + 1, 0),
+            (game_state.day(), game_state.active_player())
+        );
+    }
+
+    #[test]
+    fn end_turn_expires_powers_reveals_and_fuel() {
+        let mut game_state = game_state!("~0sub* ~1sub* ~ .1inf");
+        game_state.units.get_mut(&1).unwrap().set_fuel(5);
+        game_state.add_charge(1, u32::MAX);
+        assert!(game_state.activate_power(1, PowerKind::Normal));
+        game_state.add_reveal(Reveal {
+            player: Some(0),
+            center: 2,
+            radius: 1,
+        });
+
+        game_state.end_turn();
+        assert!(game_state.reveals().is_empty());
+        assert_eq!(Some(&PowerKind::None), game_state.power(1));
+        assert!(game_state.unit(1).is_none());
+        assert_eq!(
+            UnitKind::Submarine.max_fuel(),
+            game_state.unit(0).unwrap().fuel()
+        );
+        assert_eq!(99, game_state.unit(3).unwrap().fuel());
+    }
+}
@@ -1,9 +1,21 @@
+use crate::data::UnitData;
+use crate::map::TileKind;
 
+/**
+ * The hit points of a unit at full health.
+ */
+pub const MAX_HP: u8 = 10;
+
+#[cfg(feature = "serde")]
+pub(crate) fn max_hp() -> u8 {
+    MAX_HP
+}
 
 /**
  * All of the possible units that can be used in a game.
  */
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnitKind {
     AntiAir,
     Apc,
@@ -16,6 +28,7 @@ pub enum UnitKind {
     Carrier,
     Cruiser,
     Fighter,
+    Flare,
     Infantry,
     Lander,
     MediumTank,
@@ -32,8 +45,53 @@ pub enum UnitKind {
     Tank,
 }
 
-impl UnitKind {
-    pub fn vision(&self) -> u8 {
+/**
+ * How a unit moves across terrain, which determines per-tile movement costs.
+ */
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MovementKind {
+    Infantry,
+    Mech,
+    Tires,
+    Treads,
+    Air,
+    Sea,
+    Lander,
+    Pipe,
+}
+
+impl UnitData for UnitKind {
+    const ALL: &'static [UnitKind] = &[
+        UnitKind::AntiAir,
+        UnitKind::Apc,
+        UnitKind::Artillery,
+        UnitKind::BattleCopter,
+        UnitKind::BattleShip,
+        UnitKind::BlackBoat,
+        UnitKind::BlackBomb,
+        UnitKind::Bomber,
+        UnitKind::Carrier,
+        UnitKind::Cruiser,
+        UnitKind::Fighter,
+        UnitKind::Flare,
+        UnitKind::Infantry,
+        UnitKind::Lander,
+        UnitKind::MediumTank,
+        UnitKind::Mech,
+        UnitKind::MegaTank,
+        UnitKind::Missile,
+        UnitKind::NeoTank,
+        UnitKind::PipeRunner,
+        UnitKind::Recon,
+        UnitKind::Rocket,
+        UnitKind::Stealth,
+        UnitKind::Submarine,
+        UnitKind::TransportCopter,
+        UnitKind::Tank,
+    ];
+
+    fn vision(&self) -> u8 {
         match self {
             UnitKind::AntiAir => 2,
             UnitKind::Apc => 1,
@@ -46,6 +104,7 @@ impl UnitKind {
             UnitKind::Carrier => 4,
             UnitKind::Cruiser => 3,
             UnitKind::Fighter => 2,
+            UnitKind::Flare => 2,
             UnitKind::Infantry => 2,
             UnitKind::Lander => 1,
             UnitKind::MediumTank => 1,
@@ -62,4 +121,344 @@ impl UnitKind {
             UnitKind::Tank => 3,
         }
     }
+
+    fn movement_kind(&self) -> MovementKind {
+        match self {
+            UnitKind::AntiAir => MovementKind::Treads,
+            UnitKind::Apc => MovementKind::Treads,
+            UnitKind::Artillery => MovementKind::Treads,
+            UnitKind::BattleCopter => MovementKind::Air,
+            UnitKind::BattleShip => MovementKind::Sea,
+            UnitKind::BlackBoat => MovementKind::Lander,
+            UnitKind::BlackBomb => MovementKind::Air,
+            UnitKind::Bomber => MovementKind::Air,
+            UnitKind::Carrier => MovementKind::Sea,
+            UnitKind::Cruiser => MovementKind::Sea,
+            UnitKind::Fighter => MovementKind::Air,
+            UnitKind::Flare => MovementKind::Treads,
+            UnitKind::Infantry => MovementKind::Infantry,
+            UnitKind::Lander => MovementKind::Lander,
+            UnitKind::MediumTank => MovementKind::Treads,
+            UnitKind::Mech => MovementKind::Mech,
+            UnitKind::MegaTank => MovementKind::Treads,
+            UnitKind::Missile => MovementKind::Tires,
+            UnitKind::NeoTank => MovementKind::Treads,
+            UnitKind::PipeRunner => MovementKind::Pipe,
+            UnitKind::Recon => MovementKind::Tires,
+            UnitKind::Rocket => MovementKind::Tires,
+            UnitKind::Stealth => MovementKind::Air,
+            UnitKind::Submarine => MovementKind::Sea,
+            UnitKind::TransportCopter => MovementKind::Air,
+            UnitKind::Tank => MovementKind::Treads,
+        }
+    }
+
+    fn movement_points(&self) -> u8 {
+        match self {
+            UnitKind::AntiAir => 6,
+            UnitKind::Apc => 6,
+            UnitKind::Artillery => 5,
+            UnitKind::BattleCopter => 6,
+            UnitKind::BattleShip => 5,
+            UnitKind::BlackBoat => 7,
+            UnitKind::BlackBomb => 9,
+            UnitKind::Bomber => 7,
+            UnitKind::Carrier => 5,
+            UnitKind::Cruiser => 6,
+            UnitKind::Fighter => 9,
+            UnitKind::Flare => 5,
+            UnitKind::Infantry => 3,
+            UnitKind::Lander => 6,
+            UnitKind::MediumTank => 5,
+            UnitKind::Mech => 2,
+            UnitKind::MegaTank => 4,
+            UnitKind::Missile => 4,
+            UnitKind::NeoTank => 6,
+            UnitKind::PipeRunner => 9,
+            UnitKind::Recon => 8,
+            UnitKind::Rocket => 5,
+            UnitKind::Stealth => 6,
+            UnitKind::Submarine => 5,
+            UnitKind::TransportCopter => 6,
+            UnitKind::Tank => 6,
+        }
+    }
+
+    fn cost(&self) -> u32 {
+        match self {
+            UnitKind::AntiAir => 8000,
+            UnitKind::Apc => 5000,
+            UnitKind::Artillery => 6000,
+            UnitKind::BattleCopter => 9000,
+            UnitKind::BattleShip => 28000,
+            UnitKind::BlackBoat => 7500,
+            UnitKind::BlackBomb => 25000,
+            UnitKind::Bomber => 22000,
+            UnitKind::Carrier => 30000,
+            UnitKind::Cruiser => 18000,
+            UnitKind::Fighter => 20000,
+            UnitKind::Flare => 5000,
+            UnitKind::Infantry => 1000,
+            UnitKind::Lander => 12000,
+            UnitKind::MediumTank => 16000,
+            UnitKind::Mech => 3000,
+            UnitKind::MegaTank => 28000,
+            UnitKind::Missile => 12000,
+            UnitKind::NeoTank => 22000,
+            UnitKind::PipeRunner => 20000,
+            UnitKind::Recon => 4000,
+            UnitKind::Rocket => 15000,
+            UnitKind::Stealth => 24000,
+            UnitKind::Submarine => 20000,
+            UnitKind::TransportCopter => 5000,
+            UnitKind::Tank => 7000,
+        }
+    }
+
+    fn attack_range(&self) -> Option<(u8, u8)> {
+        match self {
+            UnitKind::AntiAir => Some((1, 1)),
+            UnitKind::Apc => None,
+            UnitKind::Artillery => Some((2, 3)),
+            UnitKind::BattleCopter => Some((1, 1)),
+            UnitKind::BattleShip => Some((2, 6)),
+            UnitKind::BlackBoat => None,
+            UnitKind::BlackBomb => None,
+            UnitKind::Bomber => Some((1, 1)),
+            UnitKind::Carrier => Some((3, 8)),
+            UnitKind::Cruiser => Some((1, 1)),
+            UnitKind::Fighter => Some((1, 1)),
+            UnitKind::Flare => Some((1, 1)),
+            UnitKind::Infantry => Some((1, 1)),
+            UnitKind::Lander => None,
+            UnitKind::MediumTank => Some((1, 1)),
+            UnitKind::Mech => Some((1, 1)),
+            UnitKind::MegaTank => Some((1, 1)),
+            UnitKind::Missile => Some((3, 5)),
+            UnitKind::NeoTank => Some((1, 1)),
+            UnitKind::PipeRunner => Some((2, 5)),
+            UnitKind::Recon => Some((1, 1)),
+            UnitKind::Rocket => Some((3, 5)),
+            UnitKind::Stealth => Some((1, 1)),
+            UnitKind::Submarine => Some((1, 1)),
+            UnitKind::TransportCopter => None,
+            UnitKind::Tank => Some((1, 1)),
+        }
+    }
+
+    fn max_fuel(&self) -> u8 {
+        match self {
+            UnitKind::AntiAir => 60,
+            UnitKind::Apc => 70,
+            UnitKind::Artillery => 50,
+            UnitKind::BattleCopter => 99,
+            UnitKind::BattleShip => 99,
+            UnitKind::BlackBoat => 60,
+            UnitKind::BlackBomb => 45,
+            UnitKind::Bomber => 99,
+            UnitKind::Carrier => 99,
+            UnitKind::Cruiser => 99,
+            UnitKind::Fighter => 99,
+            UnitKind::Flare => 60,
+            UnitKind::Infantry => 99,
+            UnitKind::Lander => 99,
+            UnitKind::MediumTank => 50,
+            UnitKind::Mech => 70,
+            UnitKind::MegaTank => 50,
+            UnitKind::Missile => 50,
+            UnitKind::NeoTank => 99,
+            UnitKind::PipeRunner => 99,
+            UnitKind::Recon => 80,
+            UnitKind::Rocket => 50,
+            UnitKind::Stealth => 60,
+            UnitKind::Submarine => 60,
+            UnitKind::TransportCopter => 99,
+            UnitKind::Tank => 70,
+        }
+    }
+
+    fn daily_fuel(&self, stealthed: bool) -> u8 {
+        match self {
+            UnitKind::AntiAir => 0,
+            UnitKind::Apc => 0,
+            UnitKind::Artillery => 0,
+            UnitKind::BattleCopter => 2,
+            UnitKind::BattleShip => 1,
+            UnitKind::BlackBoat => 1,
+            UnitKind::BlackBomb => 5,
+            UnitKind::Bomber => 5,
+            UnitKind::Carrier => 1,
+            UnitKind::Cruiser => 1,
+            UnitKind::Fighter => 5,
+            UnitKind::Flare => 0,
+            UnitKind::Infantry => 0,
+            UnitKind::Lander => 1,
+            UnitKind::MediumTank => 0,
+            UnitKind::Mech => 0,
+            UnitKind::MegaTank => 0,
+            UnitKind::Missile => 0,
+            UnitKind::NeoTank => 0,
+            UnitKind::PipeRunner => 0,
+            UnitKind::Recon => 0,
+            UnitKind::Rocket => 0,
+            UnitKind::Stealth if stealthed => 8,
+            UnitKind::Stealth => 5,
+            UnitKind::Submarine if stealthed => 5,
+            UnitKind::Submarine => 1,
+            UnitKind::TransportCopter => 2,
+            UnitKind::Tank => 0,
+        }
+    }
+
+    fn max_ammo(&self) -> u8 {
+        match self {
+            UnitKind::AntiAir => 9,
+            UnitKind::Apc => 0,
+            UnitKind::Artillery => 9,
+            UnitKind::BattleCopter => 6,
+            UnitKind::BattleShip => 9,
+            UnitKind::BlackBoat => 0,
+            UnitKind::BlackBomb => 0,
+            UnitKind::Bomber => 9,
+            UnitKind::Carrier => 9,
+            UnitKind::Cruiser => 9,
+            UnitKind::Fighter => 9,
+            UnitKind::Flare => 3,
+            UnitKind::Infantry => 0,
+            UnitKind::Lander => 0,
+            UnitKind::MediumTank => 8,
+            UnitKind::Mech => 3,
+            UnitKind::MegaTank => 3,
+            UnitKind::Missile => 4,
+            UnitKind::NeoTank => 9,
+            UnitKind::PipeRunner => 9,
+            UnitKind::Recon => 0,
+            UnitKind::Rocket => 6,
+            UnitKind::Stealth => 6,
+            UnitKind::Submarine => 6,
+            UnitKind::TransportCopter => 0,
+            UnitKind::Tank => 9,
+        }
+    }
+}
+
+impl UnitKind {
+    /**
+     * Returns true if the unit moves on the sea.
+     */
+    pub fn is_naval(&self) -> bool {
+        matches!(
+            self,
+            UnitKind::BattleShip
+                | UnitKind::BlackBoat
+                | UnitKind::Carrier
+                | UnitKind::Cruiser
+                | UnitKind::Lander
+                | UnitKind::Submarine
+        )
+    }
+
+    /**
+     * The kind of property the unit is built from.
+     */
+    pub fn built_at(&self) -> TileKind {
+        match self.movement_kind() {
+            MovementKind::Air => TileKind::Airport,
+            MovementKind::Sea | MovementKind::Lander => TileKind::Harbour,
+            _ => TileKind::Base,
+        }
+    }
+
+    /**
+     * Returns true if the unit can capture properties.
+     */
+    pub fn can_capture(&self) -> bool {
+        matches!(self, UnitKind::Infantry | UnitKind::Mech)
+    }
+
+    /**
+     * Returns the extra vision the unit gets from standing on `tile`, 3 for
+     * Infantry and Mech on a Mountain, under the rulesets that have the
+     * bonus, see `Ruleset::terrain_vision_bonus`.
+     */
+    pub fn terrain_vision_bonus(&self, tile: &TileKind) -> u8 {
+        if self.can_capture() && *tile == TileKind::Mountain {
+            3
+        } else {
+            0
+        }
+    }
+
+    /**
+     * Returns true if the unit can carry other units.
+     */
+    pub fn is_transport(&self) -> bool {
+        self.cargo_capacity() > 0
+    }
+
+    /**
+     * Returns how many units the unit can carry at once.
+     */
+    pub fn cargo_capacity(&self) -> usize {
+        match self {
+            UnitKind::Apc | UnitKind::TransportCopter => 1,
+            UnitKind::BlackBoat | UnitKind::Lander | UnitKind::Cruiser | UnitKind::Carrier => 2,
+            _ => 0,
+        }
+    }
+
+    /**
+     * Returns true if the unit can carry `cargo`, ignoring how full it is.
+     */
+    pub fn can_carry(&self, cargo: &UnitKind) -> bool {
+        match self {
+            UnitKind::Apc | UnitKind::TransportCopter | UnitKind::BlackBoat => cargo.can_capture(),
+            UnitKind::Lander => matches!(
+                cargo.movement_kind(),
+                MovementKind::Infantry
+                    | MovementKind::Mech
+                    | MovementKind::Tires
+                    | MovementKind::Treads
+            ),
+            UnitKind::Cruiser => {
+                matches!(cargo, UnitKind::BattleCopter | UnitKind::TransportCopter)
+            }
+            UnitKind::Carrier => cargo.movement_kind() == MovementKind::Air,
+            _ => false,
+        }
+    }
+
+    /**
+     * Returns true if the unit can dive / hide.
+     */
+    pub fn can_stealth(&self) -> bool {
+        matches!(self, UnitKind::Submarine | UnitKind::Stealth)
+    }
+
+    /**
+     * Returns true if the unit attacks `target` with its primary weapon,
+     * which uses up ammo.
+     *
+     * Mech, tanks, the Battle Copter and the Cruiser fall back on a machine
+     * or anti-air gun that needs no ammo against what their primary weapon
+     * can not hit, and the Flare's ammo is only for firing flares.
+     */
+    pub fn uses_ammo_against(&self, target: &UnitKind) -> bool {
+        let vehicle = matches!(
+            target.movement_kind(),
+            MovementKind::Tires | MovementKind::Treads | MovementKind::Pipe
+        );
+
+        match self {
+            UnitKind::Mech => vehicle,
+            UnitKind::Tank
+            | UnitKind::MediumTank
+            | UnitKind::NeoTank
+            | UnitKind::MegaTank
+            | UnitKind::BattleCopter => vehicle || target.is_naval(),
+            UnitKind::Cruiser => target.is_naval(),
+            UnitKind::Flare => false,
+            _ => self.max_ammo() > 0,
+        }
+    }
 }
@@ -0,0 +1,161 @@
+use crate::prelude::*;
+
+use crate::builder::BuildError;
+use crate::map::TileKind;
+use crate::teams::{coverage_problems, membership_problems};
+use crate::GameState;
+
+impl GameState {
+    /**
+     * Returns every structural problem with the state, such as units owned
+     * by players that do not exist, players on no team or a player owning
+     * two HQs.
+     *
+     * Builders and parsers reject any state this reports on, a state can
+     * still have problems after being deserialized or edited by hand.
+     * States that are merely unlikely, such as a unit on terrain it could
+     * never move onto, are left to `lint`.
+     */
+    pub fn validate(&self) -> Result<(), Vec<BuildError>> {
+        let mut problems = self.structural_problems();
+        problems.extend(coverage_problems(&self.teams, self.players.len()));
+
+        let mut head_quarters = BTreeMap::<usize, usize>::new();
+        for (location, player) in self.owners.iter() {
+            if self.map.cells().get(*location) == Some(&TileKind::HeadQuarters) {
+                *head_quarters.entry(*player).or_default() += 1;
+            }
+        }
+        for (player, _) in head_quarters.into_iter().filter(|(_, count)| *count > 1) {
+            problems.push(BuildError::DuplicateHeadQuarters { player });
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /**
+     * Returns the problems that stop vision being computed at all, in the
+     * order `validate` reports them.
+     */
+    pub(crate) fn structural_problems(&self) -> Vec<BuildError> {
+        let mut problems = Vec::new();
+
+        for (location, unit) in self.units.iter() {
+            if *location >= self.map.len() {
+                problems.push(BuildError::UnitOutOfBounds {
+                    location: *location,
+                });
+            }
+            if unit.player >= self.players.len() {
+                problems.push(BuildError::UnknownPlayer {
+                    location: *location,
+                    player: unit.player,
+                });
+            }
+        }
+
+        for (location, player) in self.owners.iter() {
+            if !self
+                .map
+                .cells()
+                .get(*location)
+                .map(|tile| tile.is_property())
+                .unwrap_or(false)
+            {
+                problems.push(BuildError::NotAProperty {
+                    location: *location,
+                });
+            }
+            if *player >= self.players.len() {
+                problems.push(BuildError::UnknownOwner {
+                    location: *location,
+                    player: *player,
+                });
+            }
+        }
+
+        problems.extend(membership_problems(&self.teams, self.players.len()));
+        problems
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::lint::Lint;
+    use crate::map::{CountryKind, Map};
+    use crate::officer::{OfficerKind, PowerKind};
+    use crate::player::PlayerState;
+    use crate::unit::UnitKind;
+    use crate::UnitState;
+
+    #[test]
+    fn validate_accepts_parsed_states() {
+        assert_eq!(Ok(()), game_state!(".0inf f ~1sub\n. . .").validate());
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let mut game_state = game_state!("q ~ q\n. = .");
+        game_state.players = vec![
+            PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+            PlayerState::new(CountryKind::BlueMoon, OfficerKind::Andy, PowerKind::None),
+        ];
+        game_state.teams = vec![[0].into_iter().collect()];
+        game_state.owners = [(0, 0), (2, 0)].into_iter().collect();
+        game_state.units = [
+            (1, UnitState::new(0, false, UnitKind::Tank)),
+            (4, UnitState::new(0, false, UnitKind::PipeRunner)),
+            (5, UnitState::new(2, false, UnitKind::Infantry)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            Err(vec![
+                BuildError::UnknownPlayer {
+                    location: 5,
+                    player: 2
+                },
+                BuildError::NoTeam { player: 1 },
+                BuildError::DuplicateHeadQuarters { player: 0 },
+            ]),
+            game_state.validate()
+        );
+    }
+
+    #[test]
+    fn builder_routes_through_validate() {
+        let builder = GameState::builder(&Map::new(vec![TileKind::Sea; 2], (2, 1))).player(
+            CountryKind::OrangeStar,
+            OfficerKind::Andy,
+            PowerKind::None,
+        );
+
+        assert_eq!(
+            Err(BuildError::NoTeam { player: 0 }),
+            builder.clone().build()
+        );
+
+        // Terrain a unit could never move onto is only linted.
+        let game_state = builder
+            .team([0])
+            .unit(1, UnitState::new(0, false, UnitKind::Infantry))
+            .build()
+            .unwrap();
+        assert_eq!(
+            vec![Lint::ImpassableTerrain {
+                location: 1,
+                kind: UnitKind::Infantry,
+                tile: TileKind::Sea
+            }],
+            game_state.lint()
+        );
+    }
+}
@@ -0,0 +1,695 @@
+use crate::coordinate::Position;
+use crate::grid::Grid;
+use crate::prelude::*;
+
+use crate::map::TileKind;
+use crate::mask::VisionMask;
+use crate::GameState;
+
+/**
+ * Rule toggles used by the vision computations.
+ *
+ * Community rule sets disagree on a handful of fog details so rather than
+ * hardcoding one set of answers they are collected here. The `Default`
+ * matches the behaviour of Advance Wars By Web.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VisionConfig {
+    /** If true then adjacent tiles are always revealed, even if the unit on
+     * them would otherwise be hidden (forest / reef / stealthed). */
+    pub adjacency_reveals_hidden: bool,
+    /** Tiles that hide units from anything but adjacent (or Sonja) vision. */
+    pub hiding_tiles: HashSet<TileKind>,
+    /** If true then a dived Submarine is only revealed by adjacent naval
+     * units rather than by any adjacent unit. */
+    pub dived_revealed_by_sea_only: bool,
+    /** If true then a property reveals its own tile to its owner. */
+    pub properties_grant_self_vision: bool,
+    /** If true then Sonja's units get her day-to-day +1 vision on top of any
+     * power. If false only her powers add vision. */
+    pub sonja_day_to_day_vision: bool,
+    /** If true then units get the ruleset's extra vision for the terrain
+     * they stand on, e.g. Infantry and Mech on a Mountain. */
+    pub terrain_vision_bonus: bool,
+}
+
+impl Default for VisionConfig {
+    fn default() -> Self {
+        VisionConfig {
+            adjacency_reveals_hidden: true,
+            hiding_tiles: [TileKind::Forest, TileKind::Reef].into_iter().collect(),
+            dived_revealed_by_sea_only: false,
+            properties_grant_self_vision: true,
+            sonja_day_to_day_vision: true,
+            terrain_vision_bonus: true,
+        }
+    }
+}
+
+impl VisionConfig {
+    pub fn hides_units(&self, tile: &TileKind) -> bool {
+        self.hiding_tiles.contains(tile)
+    }
+}
+
+/**
+ * For each tile which teams can see it, as worked out from every unit in
+ * one pass before common vision removes anything, see
+ * `GameState::vision_grid`.
+ *
+ * Kept as one row of flags per tile so it can be cleared and refilled
+ * between passes without allocating.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VisionGrid {
+    /** One row per tile, one column per team. */
+    seen: Grid<bool>,
+    /** For each tile how many teams can see it. */
+    teams_seeing: Grid<usize>,
+    /** The tile index of the first row, 0 unless the grid only covers a
+     * band of the map. */
+    first_tile: usize,
+}
+
+impl VisionGrid {
+    pub(crate) fn new(dimensions: (usize, usize), teams: usize) -> VisionGrid {
+        VisionGrid::band(dimensions, teams, 0)
+    }
+
+    /**
+     * Returns a grid of `dimensions` covering the tiles of a wider map from
+     * `first_tile` on. Tiles are still given by their index on the map.
+     */
+    pub(crate) fn band(dimensions: (usize, usize), teams: usize, first_tile: usize) -> VisionGrid {
+        let teams_seeing = Grid::filled(0, dimensions);
+        VisionGrid {
+            seen: Grid::filled(false, (teams, teams_seeing.len())),
+            teams_seeing,
+            first_tile,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.seen.cells_mut().fill(false);
+        self.teams_seeing.cells_mut().fill(0);
+    }
+
+    /**
+     * Returns where `tile` is stored, None if the grid does not cover it.
+     */
+    fn offset(&self, tile: usize) -> Option<usize> {
+        tile.checked_sub(self.first_tile)
+            .filter(|offset| *offset < self.teams_seeing.len())
+    }
+
+    /**
+     * Marks `tile` as seen by `team`, ignoring tiles and teams that do not
+     * exist.
+     */
+    pub(crate) fn insert(&mut self, tile: usize, team: usize) {
+        let Some(offset) = self.offset(tile).filter(|_| team < self.teams()) else {
+            return;
+        };
+
+        let seen = &mut self.seen[Position::new(team, offset)];
+        if !*seen {
+            *seen = true;
+            self.teams_seeing[offset] += 1;
+        }
+    }
+
+    /**
+     * Returns how many tiles the grid covers.
+     */
+    pub fn tiles(&self) -> usize {
+        self.teams_seeing.len()
+    }
+
+    /**
+     * Returns how many teams the grid covers.
+     */
+    pub fn teams(&self) -> usize {
+        self.seen.width()
+    }
+
+    /**
+     * Returns the tiles the grid covers in ascending order.
+     */
+    pub(crate) fn covered(&self) -> core::ops::Range<usize> {
+        self.first_tile..self.first_tile + self.tiles()
+    }
+
+    /**
+     * Returns true if `team` can see `tile`, false for tiles or teams that
+     * do not exist.
+     */
+    pub fn is_seen_by(&self, tile: usize, team: usize) -> bool {
+        team < self.teams()
+            && self
+                .offset(tile)
+                .is_some_and(|offset| self.seen[Position::new(team, offset)])
+    }
+
+    /**
+     * Returns how many teams can see `tile`.
+     */
+    pub fn teams_seeing(&self, tile: usize) -> usize {
+        self.offset(tile)
+            .map_or(0, |offset| self.teams_seeing[offset])
+    }
+
+    /**
+     * Returns the teams that can see `tile` in ascending order.
+     */
+    pub fn teams_at(&self, tile: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.teams()).filter(move |team| self.is_seen_by(tile, *team))
+    }
+
+    /**
+     * Returns the tiles `team` can see in ascending order.
+     */
+    pub fn tiles_seen_by(&self, team: usize) -> impl Iterator<Item = usize> + '_ {
+        self.covered()
+            .filter(move |tile| self.is_seen_by(*tile, team))
+    }
+}
+
+/**
+ * How the common vision fixed point converged.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct FixpointDiagnostics {
+    /** How many passes were run, including the final pass that changed
+     * nothing. */
+    pub iterations: usize,
+    /** For each pass the tiles it removed, in ascending order. */
+    pub removed_tiles: Vec<Vec<usize>>,
+    /** For each pass the locations of the units it removed, in ascending
+     * order. */
+    pub removed_units: Vec<Vec<usize>>,
+    /** True if the safety bound on passes was hit, in which case the
+     * returned vision is empty rather than the real answer. */
+    pub hit_bound: bool,
+}
+
+/**
+ * How far along a chunked vision computation is.
+ */
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ChunkProgress {
+    /** Which pass of the fixed point this is (starting from 0). */
+    pub iteration: usize,
+    /** The band that was just finished (starting from 0). */
+    pub band: usize,
+    /** How many bands each pass is split into. */
+    pub bands: usize,
+}
+
+/**
+ * What the common vision fixed point has ruled out so far.
+ *
+ * Both sets only ever grow: once a tile is unseen by some team it stays
+ * unseen as fewer units count towards vision. Every way of running the
+ * fixed point, whole, in bands, a unit at a time or from cached unit
+ * vision, fills a `VisionGrid` from the units still counted and hands it to
+ * `hide_unseen`.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct Fixpoint {
+    /** Locations of units that are not commonly visible, so no longer count
+     * towards anyone's vision. */
+    hidden_units: BTreeSet<usize>,
+    /** Tiles that are not commonly visible. */
+    hidden_tiles: HashSet<usize>,
+}
+
+impl Fixpoint {
+    /**
+     * Returns true if the unit at `location` still counts towards vision.
+     */
+    pub(crate) fn counts(&self, location: usize) -> bool {
+        !self.hidden_units.contains(&location)
+    }
+
+    /**
+     * The body of a pass, once `vision` holds what the counted units, owned
+     * properties and revealed areas see: every tile `vision` covers that
+     * some team can not see is hidden, along with the unit on it.
+     *
+     * Returns the tiles and the units newly hidden, in ascending order.
+     */
+    pub(crate) fn hide_unseen(
+        &mut self,
+        game_state: &GameState,
+        vision: &VisionGrid,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let mut removed_tiles = Vec::new();
+        let mut removed_units = Vec::new();
+
+        for location in vision.covered() {
+            if vision.teams_seeing(location) == vision.teams() {
+                continue;
+            }
+
+            #[cfg(feature = "tracing")]
+            if !self.hidden_tiles.contains(&location) {
+                tracing::trace!(
+                    location,
+                    teams_seeing = vision.teams_seeing(location),
+                    unit = game_state.units.contains_key(&location),
+                    "not seen by every team"
+                );
+            }
+            if game_state.units.contains_key(&location) && self.hidden_units.insert(location) {
+                removed_units.push(location);
+            }
+            if self.hidden_tiles.insert(location) {
+                removed_tiles.push(location);
+            }
+        }
+
+        (removed_tiles, removed_units)
+    }
+
+    /**
+     * Returns every tile of a map of `tiles` tiles that is not hidden.
+     */
+    pub(crate) fn visible_tiles(&self, tiles: usize) -> HashSet<usize> {
+        (0..tiles)
+            .filter(|tile| !self.hidden_tiles.contains(tile))
+            .collect()
+    }
+
+    /**
+     * Returns every tile of a map of `tiles` tiles that is not hidden as a
+     * `VisionMask`.
+     */
+    pub(crate) fn visible_mask(&self, tiles: usize) -> VisionMask {
+        let mut mask = VisionMask::full(tiles);
+        for tile in self.hidden_tiles.iter() {
+            mask.remove(*tile);
+        }
+        mask
+    }
+}
+
+impl GameState {
+    /**
+     * The most passes the common vision fixed point may take.
+     *
+     * Every pass that changes anything removes at least one tile (a unit is
+     * only removed along with its tile) so the fixed point is always reached
+     * within one pass per tile plus the final pass that changes nothing.
+     */
+    pub(crate) fn fixpoint_bound(&self) -> usize {
+        self.map.len() + 1
+    }
+
+    /**
+     * Returns each (location, team) that is seen no matter which units are
+     * visible: owned properties and revealed areas.
+     */
+    pub(crate) fn fixed_vision(&self, config: &VisionConfig) -> Vec<(usize, usize)> {
+        let mut vision = self.property_vision(config);
+        vision.extend(self.reveal_vision());
+        vision
+    }
+
+    /**
+     * Runs the common vision fixed point with fog of war on, calling `fill`
+     * at the start of each pass to mark in the cleared grid what the units
+     * `Fixpoint::counts` see along with `fixed_vision`.
+     *
+     * Returns what the settled fixed point hid, or None if it did not settle
+     * within `fixpoint_bound` passes.
+     */
+    pub(crate) fn run_fixpoint(
+        &self,
+        mut fill: impl FnMut(&Fixpoint, &mut VisionGrid),
+    ) -> (Option<Fixpoint>, FixpointDiagnostics) {
+        let mut diagnostics = FixpointDiagnostics::default();
+        let mut fixpoint = Fixpoint::default();
+        let mut vision = VisionGrid::new(self.map.dimensions(), self.teams.len());
+
+        for _ in 0..self.fixpoint_bound() {
+            #[cfg(feature = "tracing")]
+            let _pass = tracing::debug_span!(
+                "fixed_point_pass",
+                iteration = diagnostics.iterations,
+                hidden_units = fixpoint.hidden_units.len()
+            )
+            .entered();
+            diagnostics.iterations += 1;
+
+            vision.clear();
+            fill(&fixpoint, &mut vision);
+            let (removed_tiles, removed_units) = fixpoint.hide_unseen(self, &vision);
+
+            let vision_changed = !removed_tiles.is_empty() || !removed_units.is_empty();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(?removed_tiles, ?removed_units, "pass removed");
+            diagnostics.removed_tiles.push(removed_tiles);
+            diagnostics.removed_units.push(removed_units);
+
+            if !vision_changed {
+                return (Some(fixpoint), diagnostics);
+            }
+        }
+
+        // Algorithm is deterministic but avoid unbounded loops.
+        diagnostics.hit_bound = true;
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            iterations = diagnostics.iterations,
+            "fixed point did not settle"
+        );
+        (None, diagnostics)
+    }
+
+    /**
+     * Computes the same result as `common_vision_with_config` but only
+     * keeps per-team watcher data for `band_rows` rows of the map at a
+     * time, which bounds memory on very large maps.
+     *
+     * Each pass runs the same `Fixpoint` step as the whole map does, one
+     * band at a time, so between bands only what was found hidden so far
+     * is kept and the units are never copied.
+     *
+     * `progress` is called after each band of each pass.
+     */
+    pub fn common_vision_chunked(
+        &self,
+        config: &VisionConfig,
+        band_rows: usize,
+        mut progress: Option<&mut dyn FnMut(ChunkProgress)>,
+    ) -> HashSet<usize> {
+        let (width, height) = self.map.dimensions();
+        if width == 0 || height == 0 {
+            return HashSet::new();
+        }
+        if !self.settings.fog {
+            return (0..self.map.len()).collect();
+        }
+
+        let band_rows = core::cmp::max(band_rows, 1);
+        let bands = height.div_ceil(band_rows);
+        let fixed_vision = self.fixed_vision(config);
+
+        // Units further than this from a band can not see into it.
+        let reach = self
+            .units
+            .iter()
+            .map(|(location, unit)| {
+                core::cmp::max(self.vision_range(*location, unit, config).0 as usize, 1)
+            })
+            .max()
+            .unwrap_or(1);
+
+        let mut fixpoint = Fixpoint::default();
+
+        for iteration in 0..self.fixpoint_bound() {
+            let mut vision_changed = false;
+
+            for band in 0..bands {
+                let first_row = band * band_rows;
+                let last_row = core::cmp::min(first_row + band_rows, height);
+
+                let mut vision = VisionGrid::band(
+                    (width, last_row - first_row),
+                    self.teams.len(),
+                    first_row * width,
+                );
+                self.vision_for_units(
+                    self.units.keys().copied().filter(|location| {
+                        let row = location / width;
+                        row + reach >= first_row
+                            && row < last_row + reach
+                            && fixpoint.counts(*location)
+                    }),
+                    &fixed_vision,
+                    config,
+                    &mut vision,
+                );
+
+                let (removed_tiles, removed_units) = fixpoint.hide_unseen(self, &vision);
+                vision_changed =
+                    vision_changed || !removed_tiles.is_empty() || !removed_units.is_empty();
+
+                if let Some(progress) = progress.as_mut() {
+                    progress(ChunkProgress {
+                        iteration,
+                        band,
+                        bands,
+                    });
+                }
+            }
+
+            if !vision_changed {
+                return fixpoint.visible_tiles(self.map.len());
+            }
+        }
+
+        // Algorithm is deterministic but avoid unbounded loops.
+        HashSet::new()
+    }
+
+    /**
+     * Returns each commonly visible tile along with every (team, location)
+     * giving that team sight of it, using the default `VisionConfig`.
+     */
+    pub fn common_vision_detailed(&self) -> BTreeMap<usize, BTreeSet<(usize, usize)>> {
+        self.common_vision_detailed_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Returns each tile of `common_vision_with_config` along with every
+     * (team, location) giving that team sight of it.
+     *
+     * Only units that are themselves commonly visible count, as in the fixed
+     * point. Owned properties and revealed areas are listed with the tile
+     * itself as the location. With fog of war off every tile is included
+     * even if nothing sees it.
+     */
+    pub fn common_vision_detailed_with_config(
+        &self,
+        config: &VisionConfig,
+    ) -> BTreeMap<usize, BTreeSet<(usize, usize)>> {
+        let (tiles, diagnostics) = self.common_vision_with_diagnostics(config);
+        let removed_units = diagnostics
+            .removed_units
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<usize>>();
+
+        let mut detailed = tiles
+            .into_iter()
+            .map(|tile| (tile, BTreeSet::new()))
+            .collect::<BTreeMap<usize, BTreeSet<(usize, usize)>>>();
+
+        for location in self.units.keys() {
+            if removed_units.contains(location) {
+                continue;
+            }
+            let Some((player, seen)) = self.vision_from_tiles(*location, config) else {
+                continue;
+            };
+            let Some(team) = self.team_of(player) else {
+                continue;
+            };
+
+            for tile in seen {
+                if let Some(sources) = detailed.get_mut(&tile) {
+                    sources.insert((team, *location));
+                }
+            }
+        }
+
+        for (location, team) in self.fixed_vision(config) {
+            if let Some(sources) = detailed.get_mut(&location) {
+                sources.insert((team, location));
+            }
+        }
+
+        detailed
+    }
+
+    /**
+     * Returns for each tile how many teams can see it using the default
+     * `VisionConfig`.
+     */
+    pub fn vision_heatmap(&self) -> Grid<u8> {
+        self.vision_heatmap_with_config(&VisionConfig::default())
+    }
+
+    /**
+     * Returns for each tile how many teams can see it under the rules given
+     * by `config`.
+     */
+    pub fn vision_heatmap_with_config(&self, config: &VisionConfig) -> Grid<u8> {
+        self.vision_grid_with_config(config)
+            .teams_seeing
+            .map(|teams| u8::try_from(*teams).unwrap_or(u8::MAX))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::prelude::BTreeMap;
+
+    use super::*;
+
+    use crate::map::CountryKind;
+    use crate::officer::{OfficerKind, PowerKind};
+    use crate::player::PlayerState;
+    use crate::ruleset::Ruleset;
+    use crate::settings::GameSettings;
+    use crate::unit::UnitKind;
+    use crate::weather::Weather;
+    use crate::{game_state, UnitState};
+
+    #[test]
+    fn chunked_matches_common_vision() {
+        let mut map = vec![TileKind::Plain; 8 * 9];
+        for forest in [3, 10, 17, 30, 41, 50, 66] {
+            map[forest] = TileKind::Forest;
+        }
+
+        let mut game_state = GameState {
+            map: Grid::new(map, (8, 9)).expect("Tiles do not fill the map"),
+            units: [
+                (0, UnitState::new(0, false, UnitKind::Recon)),
+                (10, UnitState::new(1, false, UnitKind::Infantry)),
+                (27, UnitState::new(0, false, UnitKind::Tank)),
+                (36, UnitState::new(1, false, UnitKind::Artillery)),
+                (50, UnitState::new(0, false, UnitKind::Infantry)),
+                (63, UnitState::new(1, false, UnitKind::Recon)),
+                (70, UnitState::new(1, false, UnitKind::Mech)),
+            ]
+            .into_iter()
+            .collect(),
+            players: vec![
+                PlayerState::new(CountryKind::OrangeStar, OfficerKind::Andy, PowerKind::None),
+                PlayerState::new(CountryKind::BlueMoon, OfficerKind::Olaf, PowerKind::None),
+            ],
+            teams: vec![[0].into_iter().collect(), [1].into_iter().collect()],
+            weather: Weather::Clear,
+            settings: GameSettings::fog_league(),
+            owners: BTreeMap::new(),
+            reveals: Vec::new(),
+            ruleset: Ruleset::Awbw,
+            day: 1,
+            active_player: 0,
+        };
+
+        let config = VisionConfig::default();
+        let expected = game_state.common_vision_with_config(&config);
+
+        for band_rows in 1..=9 {
+            let mut calls = 0;
+            let mut progress = |_: ChunkProgress| calls += 1;
+            assert_eq!(
+                expected,
+                game_state.common_vision_chunked(&config, band_rows, Some(&mut progress))
+            );
+            assert!(calls > 0);
+        }
+
+        for dimensions in [(0, 3), (3, 0)] {
+            game_state.map = Grid::filled(TileKind::Plain, dimensions);
+            assert!(game_state
+                .common_vision_chunked(&config, 1, None)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn vision_heatmap_counts_teams() {
+        let game_state = game_state!(
+            "
+            teams: 0 1 2
+            .0inf . . . .1inf . .2inf
+            "
+        );
+
+        assert_eq!(&[1, 1, 2, 1, 2, 2, 2], game_state.vision_heatmap().cells());
+    }
+
+    #[test]
+    fn vision_grid_reuse() {
+        let mut vision = VisionGrid::new((3, 1), 2);
+        vision.insert(1, 0);
+        vision.insert(1, 0);
+        vision.insert(1, 1);
+        vision.insert(3, 0);
+        vision.insert(0, 2);
+        assert_eq!(2, vision.teams_seeing(1));
+        assert!(vision.is_seen_by(1, 1) && !vision.is_seen_by(0, 0) && !vision.is_seen_by(0, 2));
+
+        vision.clear();
+        assert_eq!(0, vision.teams_seeing(1));
+        assert!(!vision.is_seen_by(1, 0));
+    }
+
+    #[test]
+    fn vision_grid_band() {
+        let mut vision = VisionGrid::band((3, 1), 2, 3);
+        vision.insert(2, 0);
+        vision.insert(4, 0);
+        vision.insert(4, 1);
+        vision.insert(6, 1);
+        assert_eq!(3..6, vision.covered());
+        assert_eq!(2, vision.teams_seeing(4));
+        assert_eq!(0, vision.teams_seeing(2) + vision.teams_seeing(6));
+        assert_eq!(vec![4], vision.tiles_seen_by(1).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn heatmap_grid() {
+        let game_state = game_state!(".0inf f . .1inf\n. . . .");
+        let grid = game_state.vision_heatmap();
+        assert_eq!((4, 2), grid.dimensions());
+        assert_eq!(Some(&grid.cells()[4..]), grid.rows().nth(1));
+    }
+
+    #[test]
+    fn vision_grid_queries() {
+        let mut game_state = game_state!(".0inf f . .1inf\n. . . .");
+        let grid = game_state.vision_grid();
+        assert_eq!((8, 2), (grid.tiles(), grid.teams()));
+
+        for team in 0..2 {
+            assert_eq!(
+                game_state.visible_to_team(team),
+                grid.tiles_seen_by(team).collect()
+            );
+        }
+        assert_eq!(vec![0, 1], grid.teams_at(2).collect::<Vec<usize>>());
+        assert!(grid.is_seen_by(0, 0) && !grid.is_seen_by(3, 0));
+        assert!(!grid.is_seen_by(0, 2) && !grid.is_seen_by(8, 0));
+        assert_eq!(0, grid.teams_seeing(8));
+
+        game_state.settings.fog = false;
+        let grid = game_state.vision_grid();
+        assert!((0..8).all(|tile| grid.teams_seeing(tile) == 2));
+    }
+
+    #[test]
+    fn common_vision_detailed_sources() {
+        let mut game_state = game_state!("c .0inf . .1inf . .1inf");
+        game_state.set_owner(0, Some(1));
+
+        let detailed = game_state.common_vision_detailed();
+        assert_eq!(
+            game_state.common_vision(),
+            detailed.keys().copied().collect::<HashSet<usize>>()
+        );
+        assert_eq!(
+            Some(&[(0, 1), (1, 0)].into_iter().collect::<BTreeSet<_>>()),
+            detailed.get(&0)
+        );
+        assert_eq!(
+            Some(&[(0, 1), (1, 3)].into_iter().collect::<BTreeSet<_>>()),
+            detailed.get(&2)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::data::TileData;
+use crate::map::TileKind;
+use crate::unit::MovementKind;
+use crate::GameState;
+
+/**
+ * The weather for the current day.
+ */
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weather {
+    #[default]
+    Clear,
+    /** Every unit sees one tile less, though never less than one tile. */
+    Rain,
+    Snow,
+    Sandstorm,
+}
+
+impl Weather {
+    pub const ALL: &'static [Weather] = &[
+        Weather::Clear,
+        Weather::Rain,
+        Weather::Snow,
+        Weather::Sandstorm,
+    ];
+
+    /**
+     * Returns how much the weather reduces the vision of every unit.
+     */
+    pub fn vision_penalty(&self) -> u8 {
+        match self {
+            Weather::Rain => 1,
+            Weather::Clear | Weather::Snow | Weather::Sandstorm => 0,
+        }
+    }
+
+    /**
+     * Returns the movement points needed to enter `tile` in this weather,
+     * or None if units that move this way cannot enter it.
+     *
+     * Rain bogs down vehicles on plains and in forests. Snow slows down
+     * aircraft and ships everywhere and infantry and vehicles off road.
+     */
+    pub fn movement_cost(&self, tile: &TileKind, movement: MovementKind) -> Option<u8> {
+        use MovementKind::*;
+
+        let clear = tile.movement_cost(movement)?;
+        let cost = match (self, tile, movement) {
+            (Weather::Rain, TileKind::Plain | TileKind::Forest, Tires | Treads) => clear + 1,
+            (Weather::Snow, _, Air) => 2,
+            (Weather::Snow, TileKind::Sea | TileKind::Harbour, Sea | Lander) => 2,
+            (Weather::Snow, TileKind::Plain | TileKind::Forest, Infantry) => 2,
+            (Weather::Snow, TileKind::Plain, Tires) => 3,
+            (Weather::Snow, TileKind::Plain, Treads) => 2,
+            (Weather::Snow, TileKind::Mountain, Infantry | Mech) => clear * 2,
+            _ => clear,
+        };
+
+        Some(cost)
+    }
+}
+
+impl GameState {
+    /**
+     * Returns the weather for the current day.
+     */
+    pub fn weather(&self) -> &Weather {
+        &self.weather
+    }
+
+    /**
+     * Changes the weather, for example when a new day starts or a power
+     * changes it.
+     */
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::prelude::HashSet;
+
+    use super::*;
+
+    use crate::game_state;
+
+    #[test]
+    fn rain_reduces_vision() {
+        let mut game_state = game_state!(".0inf . . . .1inf");
+        assert_eq!(
+            [0, 1, 2].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team(0)
+        );
+
+        game_state.set_weather(Weather::Rain);
+        assert_eq!(&Weather::Rain, game_state.weather());
+        assert_eq!(
+            [0, 1].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team(0)
+        );
+    }
+
+    #[test]
+    fn rain_keeps_one_tile_of_vision() {
+        let mut game_state = game_state!(".0apc . .1inf");
+        game_state.set_weather(Weather::Rain);
+
+        assert_eq!(
+            [0, 1].into_iter().collect::<HashSet<usize>>(),
+            game_state.visible_to_team(0)
+        );
+    }
+}
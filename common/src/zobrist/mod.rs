@@ -0,0 +1,256 @@
+use core::hash::{Hash, Hasher};
+
+#[cfg(feature = "std")]
+use crate::action::{Action, ActionError};
+use crate::map::TileKind;
+use crate::officer::PowerKind;
+use crate::{GameState, UnitState};
+
+/**
+ * A Zobrist hash of a state's dimensions, terrain, units, property owners
+ * and active powers.
+ *
+ * Each (location, contents) pair has its own pseudo random key and the
+ * hash is all of them xor'd together, so when one tile changes the hash is
+ * updated by toggling out the old contents and toggling in the new ones
+ * instead of hashing the whole state again.
+ *
+ * Teams, weather, settings, reveals, the day and the active player are not
+ * hashed. Equal states always hash the same but states with the same hash
+ * still need comparing to be sure they are equal. Hashes are stable for a
+ * given build of this crate, not between versions or platforms.
+ */
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ZobristHash(u64);
+
+const DIMENSIONS: u64 = 0;
+const TILE: u64 = 1;
+const UNIT: u64 = 2;
+const OWNER: u64 = 3;
+const POWER: u64 = 4;
+
+impl ZobristHash {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn toggle_tile(&mut self, location: usize, tile: &TileKind) {
+        self.0 ^= key(TILE, location, tile);
+    }
+
+    pub fn toggle_unit(&mut self, location: usize, unit: &UnitState) {
+        self.0 ^= key(UNIT, location, unit);
+    }
+
+    pub fn toggle_owner(&mut self, location: usize, player: usize) {
+        self.0 ^= key(OWNER, location, &player);
+    }
+
+    pub fn toggle_power(&mut self, player: usize, power: &PowerKind) {
+        self.0 ^= key(POWER, player, power);
+    }
+
+    /**
+     * Toggles the unit and owner at `location` in `game_state`, leaving the
+     * terrain alone. Calling this before and after changing what is on a
+     * tile updates the hash for that change.
+     */
+    pub fn toggle_contents(&mut self, game_state: &GameState, location: usize) {
+        if let Some(unit) = game_state.units.get(&location) {
+            self.toggle_unit(location, unit);
+        }
+        if let Some(player) = game_state.owners.get(&location) {
+            self.toggle_owner(location, *player);
+        }
+    }
+
+    /**
+     * Toggles every player's active power in `game_state`.
+     */
+    pub fn toggle_powers(&mut self, game_state: &GameState) {
+        for (index, player) in game_state.players.iter().enumerate() {
+            self.toggle_power(index, &player.power);
+        }
+    }
+}
+
+impl GameState {
+    /**
+     * Hashes the whole state, see `ZobristHash`.
+     */
+    pub fn zobrist_hash(&self) -> ZobristHash {
+        let mut hash = ZobristHash(key(DIMENSIONS, 0, &self.map.dimensions()));
+        for (location, tile) in self.map.cells().iter().enumerate() {
+            hash.toggle_tile(location, tile);
+        }
+        for (location, unit) in self.units.iter() {
+            hash.toggle_unit(*location, unit);
+        }
+        for (location, player) in self.owners.iter() {
+            hash.toggle_owner(*location, *player);
+        }
+        hash.toggle_powers(self);
+        hash
+    }
+
+    /**
+     * Same as `apply_in_place` while keeping `hash`, this state's
+     * `zobrist_hash`, up to date by only rehashing the tiles `action`
+     * touches. Ending a turn rehashes every unit since any of them may burn
+     * fuel.
+     */
+    #[cfg(feature = "std")]
+    pub fn apply_hashed(
+        &mut self,
+        action: &Action,
+        hash: &mut ZobristHash,
+    ) -> Result<(), ActionError> {
+        let touched = match action {
+            Action::Move { from, to } => vec![*from, *to],
+            Action::Attack {
+                attacker, target, ..
+            } => vec![*attacker, *target],
+            Action::Capture { location }
+            | Action::Build { location, .. }
+            | Action::Dive { location }
+            | Action::Surface { location } => vec![*location],
+            Action::Load {
+                location,
+                transport,
+            } => vec![*location, *transport],
+            Action::Unload {
+                transport,
+                destination,
+                ..
+            } => vec![*transport, *destination],
+            Action::EndTurn => self.units.keys().copied().collect(),
+        };
+
+        for location in touched.iter() {
+            hash.toggle_contents(self, *location);
+        }
+        hash.toggle_powers(self);
+
+        let result = self.apply_in_place(action);
+
+        for location in touched.iter() {
+            hash.toggle_contents(self, *location);
+        }
+        hash.toggle_powers(self);
+        result
+    }
+}
+
+/**
+ * SplitMix64's finalizer over everything written, deterministic unlike the
+ * std hashers.
+ */
+struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = mix(self.0 ^ u64::from(*byte));
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = mix(self.0 ^ value);
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn key(kind: u64, index: usize, value: &impl Hash) -> u64 {
+    let mut hasher = KeyHasher(kind);
+    hasher.write_usize(index);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use crate::game_state;
+    use crate::unit::UnitKind;
+
+    #[test]
+    fn equal_states_hash_equal() {
+        let game_state = game_state!(".0inf c .1tank\n. . .");
+        assert_eq!(game_state.zobrist_hash(), game_state.clone().zobrist_hash());
+
+        assert_ne!(
+            game_state.zobrist_hash(),
+            game_state!(".0inf c .1tank\n. . f").zobrist_hash()
+        );
+        assert_ne!(
+            game_state.zobrist_hash(),
+            game_state!(".1inf c .1tank\n. . .").zobrist_hash()
+        );
+        assert_ne!(
+            game_state!(". . .\n. . .").zobrist_hash(),
+            game_state!(". .\n. .\n. .").zobrist_hash()
+        );
+
+        let mut owned = game_state.clone();
+        owned.set_owner(1, Some(0));
+        assert_ne!(game_state.zobrist_hash(), owned.zobrist_hash());
+    }
+
+    #[test]
+    fn toggling_twice_undoes() {
+        let game_state = game_state!(".0inf . .1inf");
+        let mut hash = game_state.zobrist_hash();
+        let unit = UnitState::new(0, false, UnitKind::Tank);
+        hash.toggle_unit(1, &unit);
+        assert_ne!(game_state.zobrist_hash(), hash);
+        hash.toggle_unit(1, &unit);
+        assert_eq!(game_state.zobrist_hash(), hash);
+    }
+
+    #[test]
+    fn apply_hashed_matches_rehashing() {
+        let mut game_state = game_state!(".0inf .1inf c .0apc\n. . . .");
+        let mut hash = game_state.zobrist_hash();
+
+        for action in [
+            Action::Move { from: 0, to: 4 },
+            Action::Attack {
+                attacker: 4,
+                target: 1,
+                damage: 3,
+            },
+            Action::Move { from: 4, to: 2 },
+            Action::Capture { location: 2 },
+            Action::Move { from: 2, to: 6 },
+            Action::Load {
+                location: 6,
+                transport: 3,
+            },
+            Action::EndTurn,
+            Action::Attack {
+                attacker: 1,
+                target: 1,
+                damage: 3,
+            },
+            Action::EndTurn,
+        ] {
+            let _ = game_state.apply_hashed(&action, &mut hash);
+            assert_eq!(game_state.zobrist_hash(), hash, "{:?}", action);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * C interface to the awbw_vision library, see ffi/src/lib.rs for the full
+ * documentation of each function.
+ *
+ * States are opaque handles read from JSON in the layout `GameState`
+ * serializes to and released with awbw_game_state_free. Masks hold one bit
+ * per tile in row order, tile i in bit i % 8 of byte i / 8. No function
+ * unwinds into the caller; a panic is reported like any other failure.
+ */
+#ifndef AWBW_VISION_H
+#define AWBW_VISION_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <sys/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct AwbwGameState AwbwGameState;
+
+/* Returns NULL if json is NULL or not a valid state. */
+AwbwGameState *awbw_game_state_from_json(const char *json);
+
+/* Releases a state, NULL is ignored. */
+void awbw_game_state_free(AwbwGameState *game_state);
+
+/* Returns false and writes nothing if any pointer is NULL. */
+bool awbw_game_state_dimensions(const AwbwGameState *game_state, size_t *width, size_t *height);
+
+/*
+ * Returns the number of bytes the mask needs, writing nothing if that is
+ * more than len, or -1 if the state is NULL or invalid.
+ */
+ssize_t awbw_common_vision(const AwbwGameState *game_state, uint8_t *mask, size_t len);
+
+/* Same as awbw_common_vision, -1 is also returned if there is no such team. */
+ssize_t awbw_team_vision(const AwbwGameState *game_state, size_t team, uint8_t *mask, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,229 @@
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+
+use common::mask::VisionMask;
+use common::vision::VisionConfig;
+use common::GameState;
+
+/**
+ * Reads a state in the layout `GameState` serializes to from the NUL
+ * terminated UTF-8 `json`.
+ *
+ * Returns null if `json` is null, not a valid state or reading it panics,
+ * otherwise a handle which must be released with `awbw_game_state_free`.
+ *
+ * # Safety
+ *
+ * `json` must be null or point to a NUL terminated string.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn awbw_game_state_from_json(json: *const c_char) -> *mut GameState {
+    guard(std::ptr::null_mut(), || {
+        if json.is_null() {
+            return std::ptr::null_mut();
+        }
+        let Ok(json) = CStr::from_ptr(json).to_str() else {
+            return std::ptr::null_mut();
+        };
+
+        match serde_json::from_str::<GameState>(json) {
+            Ok(game_state) => Box::into_raw(Box::new(game_state)),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/**
+ * Releases a state, null is ignored.
+ *
+ * # Safety
+ *
+ * `game_state` must be null or a handle from `awbw_game_state_from_json`
+ * which has not been freed yet.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn awbw_game_state_free(game_state: *mut GameState) {
+    guard((), || {
+        if !game_state.is_null() {
+            drop(Box::from_raw(game_state));
+        }
+    })
+}
+
+/**
+ * Writes the (width, height) of the map to `width` and `height`.
+ *
+ * Returns false and writes nothing if any pointer is null.
+ *
+ * # Safety
+ *
+ * `game_state` must be null or a live handle, `width` and `height` must be
+ * null or valid for writes.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn awbw_game_state_dimensions(
+    game_state: *const GameState,
+    width: *mut usize,
+    height: *mut usize,
+) -> bool {
+    guard(false, || {
+        let Some(game_state) = game_state.as_ref() else {
+            return false;
+        };
+        if width.is_null() || height.is_null() {
+            return false;
+        }
+
+        (*width, *height) = game_state.dimensions();
+        true
+    })
+}
+
+/**
+ * Writes the bitmask of the tiles every team can see to `mask`, one bit
+ * per tile in row order with tile `i` in bit `i % 8` of byte `i / 8`.
+ *
+ * Returns the number of bytes the mask needs, writing nothing if that is
+ * more than `len`, or -1 if the state is null or invalid or computing the
+ * mask panics.
+ *
+ * # Safety
+ *
+ * `game_state` must be null or a live handle and `mask` must be valid for
+ * `len` bytes of writes.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn awbw_common_vision(
+    game_state: *const GameState,
+    mask: *mut u8,
+    len: usize,
+) -> isize {
+    guard(-1, || {
+        let Some(game_state) = game_state.as_ref() else {
+            return -1;
+        };
+
+        match game_state.try_common_vision_mask_with_config(&VisionConfig::default()) {
+            Ok(visible) => write_mask(&visible, mask, len),
+            Err(_) => -1,
+        }
+    })
+}
+
+/**
+ * Same as `awbw_common_vision` for the tiles `team` can see, -1 is also
+ * returned if there is no such team.
+ *
+ * # Safety
+ *
+ * See `awbw_common_vision`.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn awbw_team_vision(
+    game_state: *const GameState,
+    team: usize,
+    mask: *mut u8,
+    len: usize,
+) -> isize {
+    guard(-1, || {
+        let Some(game_state) = game_state.as_ref() else {
+            return -1;
+        };
+
+        match game_state.try_visible_to_team_mask(team, &VisionConfig::default()) {
+            Ok(visible) => write_mask(&visible, mask, len),
+            Err(_) => -1,
+        }
+    })
+}
+
+/**
+ * Runs `body`, returning `fallback` rather than unwinding into the caller
+ * if it panics.
+ */
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+unsafe fn write_mask(visible: &VisionMask, mask: *mut u8, len: usize) -> isize {
+    let bytes = visible.to_bytes();
+    if !mask.is_null() && bytes.len() <= len {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), mask, bytes.len());
+    }
+    bytes.len() as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    use common::ascii::parse_game_state;
+
+    #[test]
+    fn vision_through_handles() {
+        let game_state = parse_game_state(".0inf f .1inf\n. . .").unwrap();
+        let json = CString::new(serde_json::to_string(&game_state).unwrap()).unwrap();
+
+        unsafe {
+            let handle = awbw_game_state_from_json(json.as_ptr());
+            assert!(!handle.is_null());
+
+            let (mut width, mut height) = (0, 0);
+            assert!(awbw_game_state_dimensions(handle, &mut width, &mut height));
+            assert_eq!((3, 2), (width, height));
+
+            let mut mask = [0u8; 1];
+            assert_eq!(1, awbw_common_vision(handle, std::ptr::null_mut(), 0));
+            assert_eq!(1, awbw_common_vision(handle, mask.as_mut_ptr(), mask.len()));
+            assert_eq!([0b010111], mask);
+
+            assert_eq!(
+                1,
+                awbw_team_vision(handle, 0, mask.as_mut_ptr(), mask.len())
+            );
+            assert_eq!(
+                -1,
+                awbw_team_vision(handle, 2, mask.as_mut_ptr(), mask.len())
+            );
+
+            awbw_game_state_free(handle);
+        }
+    }
+
+    #[test]
+    fn bad_input_is_rejected() {
+        let json = CString::new("{}").unwrap();
+
+        unsafe {
+            assert!(awbw_game_state_from_json(std::ptr::null()).is_null());
+            assert!(awbw_game_state_from_json(json.as_ptr()).is_null());
+            assert_eq!(
+                -1,
+                awbw_common_vision(std::ptr::null(), std::ptr::null_mut(), 0)
+            );
+            awbw_game_state_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        assert_eq!(-1, guard(-1, || panic!("caught")));
+        assert_eq!(1, guard(0, || 1));
+    }
+
+    #[test]
+    fn header_declares_every_entry_point() {
+        let header = include_str!("../include/awbw_vision.h");
+        for name in [
+            "awbw_game_state_from_json",
+            "awbw_game_state_free",
+            "awbw_game_state_dimensions",
+            "awbw_common_vision",
+            "awbw_team_vision",
+        ] {
+            assert!(header.contains(&format!("{}(", name)), "{}", name);
+        }
+    }
+}
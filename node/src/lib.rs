@@ -0,0 +1,107 @@
+use common::ascii::parse_game_state;
+use common::vision::VisionConfig;
+use napi::bindgen_prelude::{Buffer, Error, Result, Uint32Array};
+use napi_derive::napi;
+
+/**
+ * A `GameState` for Node, e.g. a Discord bot answering what a team can see.
+ *
+ * Masks are returned as a `Buffer` with one byte per tile in row order, 1 if
+ * the tile is visible and 0 otherwise.
+ */
+#[napi]
+pub struct GameState {
+    inner: common::GameState,
+}
+
+#[napi]
+impl GameState {
+    /**
+     * Reads a state in the layout `GameState` serializes to.
+     */
+    #[napi(factory)]
+    pub fn from_json(json: String) -> Result<GameState> {
+        let inner = serde_json::from_str(&json).map_err(to_error)?;
+        Ok(GameState { inner })
+    }
+
+    /**
+     * Reads a state drawn in ASCII, see `common::ascii::parse_game_state`.
+     */
+    #[napi(factory)]
+    pub fn from_ascii(text: String) -> Result<GameState> {
+        let inner = parse_game_state(&text).map_err(Error::from_reason)?;
+        Ok(GameState { inner })
+    }
+
+    #[napi]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&self.inner).map_err(to_error)
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.inner.dimensions().0 as u32
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> u32 {
+        self.inner.dimensions().1 as u32
+    }
+
+    /**
+     * The mask of the tiles every team can see.
+     */
+    #[napi]
+    pub fn common_vision(&self) -> Result<Buffer> {
+        let visible = self
+            .inner
+            .try_common_vision_mask_with_config(&VisionConfig::default())
+            .map_err(to_error)?;
+        Ok(visible.to_flags().into())
+    }
+
+    /**
+     * The `CommonVisionReport` of the state as JSON.
+     */
+    #[napi]
+    pub fn common_vision_report(&self) -> Result<String> {
+        let report = self
+            .inner
+            .common_vision_report(&VisionConfig::default())
+            .map_err(to_error)?;
+        serde_json::to_string(&report).map_err(to_error)
+    }
+
+    /**
+     * The indices of the tiles every team can see, in ascending order.
+     */
+    #[napi]
+    pub fn common_vision_tiles(&self) -> Result<Uint32Array> {
+        let visible = self
+            .inner
+            .try_common_vision_mask_with_config(&VisionConfig::default())
+            .map_err(to_error)?;
+        Ok(visible
+            .iter()
+            .map(|tile| tile as u32)
+            .collect::<Vec<u32>>()
+            .into())
+    }
+
+    /**
+     * The mask of the tiles `team` can see.
+     */
+    #[napi]
+    pub fn team_vision(&self, team: u32) -> Result<Buffer> {
+        let visible = self
+            .inner
+            .try_visible_to_team_mask(team as usize, &VisionConfig::default())
+            .map_err(to_error)?;
+        Ok(visible.to_flags().into())
+    }
+}
+
+fn to_error(error: impl std::fmt::Display) -> Error {
+    Error::from_reason(error.to_string())
+}
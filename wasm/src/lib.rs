@@ -0,0 +1,115 @@
+use common::ascii::parse_game_state;
+use common::vision::VisionConfig;
+use wasm_bindgen::prelude::*;
+
+/**
+ * A `GameState` for JavaScript, e.g. a userscript drawing vision over the
+ * AWBW map.
+ *
+ * Masks are returned as a `Uint8Array` with one entry per tile in row
+ * order, 1 if the tile is visible and 0 otherwise.
+ */
+#[wasm_bindgen]
+pub struct GameState {
+    inner: common::GameState,
+}
+
+#[wasm_bindgen]
+impl GameState {
+    /**
+     * Reads a state in the layout `GameState` serializes to.
+     */
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<GameState, JsError> {
+        let inner = serde_json::from_str(json)?;
+        Ok(GameState { inner })
+    }
+
+    /**
+     * Reads a state drawn in ASCII, see `common::ascii::parse_game_state`.
+     */
+    #[wasm_bindgen(js_name = fromAscii)]
+    pub fn from_ascii(text: &str) -> Result<GameState, JsError> {
+        let inner = parse_game_state(text).map_err(|error| JsError::new(&error))?;
+        Ok(GameState { inner })
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.inner)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.inner.dimensions().0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.inner.dimensions().1
+    }
+
+    /**
+     * The mask of the tiles every team can see.
+     */
+    #[wasm_bindgen(js_name = commonVision)]
+    pub fn common_vision(&self) -> Result<Vec<u8>, JsError> {
+        let visible = self
+            .inner
+            .try_common_vision_mask_with_config(&VisionConfig::default())?;
+        Ok(visible.to_flags())
+    }
+
+    /**
+     * The `CommonVisionReport` of the state as JSON.
+     */
+    #[wasm_bindgen(js_name = commonVisionReport)]
+    pub fn common_vision_report(&self) -> Result<String, JsError> {
+        let report = self.inner.common_vision_report(&VisionConfig::default())?;
+        Ok(serde_json::to_string(&report)?)
+    }
+
+    /**
+     * The indices of the tiles every team can see, in ascending order.
+     */
+    #[wasm_bindgen(js_name = commonVisionTiles)]
+    pub fn common_vision_tiles(&self) -> Result<Vec<u32>, JsError> {
+        let visible = self
+            .inner
+            .try_common_vision_mask_with_config(&VisionConfig::default())?;
+        Ok(visible.iter().map(|tile| tile as u32).collect())
+    }
+
+    /**
+     * The mask of the tiles `team` can see.
+     */
+    #[wasm_bindgen(js_name = teamVision)]
+    pub fn team_vision(&self, team: usize) -> Result<Vec<u8>, JsError> {
+        let visible = self
+            .inner
+            .try_visible_to_team_mask(team, &VisionConfig::default())?;
+        Ok(visible.to_flags())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vision_as_arrays() {
+        let game_state = GameState {
+            inner: parse_game_state(".0inf f .1inf\n. . .").unwrap(),
+        };
+
+        assert_eq!((3, 2), (game_state.width(), game_state.height()));
+        assert_eq!(
+            Some(vec![1, 1, 1, 0, 1, 0]),
+            game_state.common_vision().ok()
+        );
+        assert_eq!(
+            Some(vec![0, 1, 2, 4]),
+            game_state.common_vision_tiles().ok()
+        );
+    }
+}